@@ -92,7 +92,19 @@ fn render_mouse(tt: &mut tickit::TickitTerm, ev: Option<&tickit::TickitMouseEven
         }
         tickit::MouseWheelEvent{dir, line, col, mod_} =>
         {
-            tt.print(format!("wheel {} at ({},{})", if dir == tickit::c::TICKIT_MOUSEWHEEL_DOWN { "down" } else { "up" }, line, col).as_slice());
+            let dir = match dir
+            {
+                tickit::c::TICKIT_MOUSEWHEEL_UP => "up",
+                tickit::c::TICKIT_MOUSEWHEEL_DOWN => "down",
+                tickit::c::TICKIT_MOUSEWHEEL_LEFT => "left",
+                tickit::c::TICKIT_MOUSEWHEEL_RIGHT => "right",
+            };
+            tt.print(format!("wheel {} at ({},{})", dir, line, col).as_slice());
+            mod_
+        }
+        tickit::MouseMoveEvent{line, col, mod_} =>
+        {
+            tt.print(format!("move at ({},{})", line, col).as_slice());
             mod_
         }
     };
@@ -137,7 +149,7 @@ fn main()
     let mut tt = match tickit::TickitTerm::new()
     {
         Ok(o) => { o }
-        Err(errno) => { fail!("Cannot create TickitTerm - errno #{}", errno); }
+        Err(e) => { fail!("Cannot create TickitTerm - {}", e); }
     };
 
     tt.set_input_fd(libc::STDIN_FILENO);
@@ -145,10 +157,10 @@ fn main()
     let await = libc::timeval{ tv_sec: 0, tv_usec: 50000 };
     tt.await_started(Some(await));
 
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_DRAG as int);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_KEYPAD_APP, 1);
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1).unwrap();
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0).unwrap();
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_MOVE as int).unwrap();
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_KEYPAD_APP, 1).unwrap();
     tt.clear();
 
     tt.x_bind_event_forever(tickit::c::TICKIT_EV_KEY|tickit::c::TICKIT_EV_MOUSE, event);
@@ -33,7 +33,7 @@ fn main()
     let mut tt = match tickit::TickitTerm::new()
     {
         Ok(o) => { o }
-        Err(errno) => { fail!("Cannot create TickitTerm - errno #{}", errno); }
+        Err(e) => { fail!("Cannot create TickitTerm - {}", e); }
     };
 
     tt.set_input_fd(libc::STDIN_FILENO);
@@ -41,8 +41,8 @@ fn main()
     let await = libc::timeval{ tv_sec: 0, tv_usec: 50000 };
     tt.await_started(Some(await));
 
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0);
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1).unwrap();
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0).unwrap();
     tt.clear();
 
     let default_pen = tickit::TickitPen::new();
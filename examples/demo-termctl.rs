@@ -70,7 +70,7 @@ fn event(tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent)
             return;
         }
 
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, unsafe { vis } as int);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, unsafe { vis } as int).unwrap();
     }
 
     if line == 7
@@ -88,7 +88,7 @@ fn event(tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent)
             return;
         }
 
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORBLINK, unsafe { blink } as int);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORBLINK, unsafe { blink } as int).unwrap();
     }
 
     if line == 9
@@ -110,7 +110,7 @@ fn event(tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent)
             return;
         }
 
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORSHAPE, unsafe { shape } as int);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORSHAPE, unsafe { shape } as int).unwrap();
     }
 
     render_modes(tt);
@@ -121,7 +121,7 @@ fn main()
     let mut tt = match tickit::TickitTerm::new()
     {
         Ok(o) => { o }
-        Err(errno) => { fail!("Cannot create TickitTerm - errno #{}", errno); }
+        Err(e) => { fail!("Cannot create TickitTerm - {}", e); }
     };
 
     tt.set_input_fd(libc::STDIN_FILENO);
@@ -129,8 +129,8 @@ fn main()
     let await = libc::timeval{ tv_sec: 0, tv_usec: 50000 };
     tt.await_started(Some(await));
 
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_CLICK as int);
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1).unwrap();
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_CLICK as int).unwrap();
     tt.clear();
 
     tt.x_bind_event_forever(tickit::c::TICKIT_EV_MOUSE, event);
@@ -1,7 +1,6 @@
-extern crate libc;
-extern crate native;
+#![feature(unboxed_closures)]
 
-extern crate signals;
+extern crate native;
 
 extern crate tickit;
 
@@ -13,34 +12,24 @@ fn start(argc: int, argv: *const *const u8) -> int
 
 fn main()
 {
-    let mut tt = match tickit::TickitTerm::new()
+    let mut t = match tickit::Tickit::new()
     {
         Ok(o) => { o }
-        Err(errno) => { fail!("Cannot create TickitTerm - errno #{}", errno); }
+        Err(e) => { fail!("Cannot create TickitTerm - {}", e); }
     };
 
-    tt.set_input_fd(libc::STDIN_FILENO);
-    tt.set_output_fd(libc::STDOUT_FILENO);
-    let await = libc::timeval{ tv_sec: 0, tv_usec: 50000 };
-    tt.await_started(Some(await));
-
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0);
-    tt.clear();
+    t.tt.set_altscreen(true).unwrap();
+    t.tt.set_cursor_visible(false).unwrap();
+    t.tt.clear();
 
     let mut counter: int = 0;
 
-    let sig = signals::Signals::new().unwrap();
-    sig.subscribe(signals::Interrupt);
-
-    while sig.receiver().try_recv().is_err()
+    t.timer_every(1000, box move |&mut: t: &mut tickit::Tickit|
     {
-        let to = libc::timeval{ tv_sec: 1, tv_usec: 0 };
-        tt.input_wait(Some(to));
-
-        tt.goto(5, 5);
-        tt.print(format!("Counter {}", counter).as_slice());
+        t.tt.goto(5, 5);
+        t.tt.print(format!("Counter {}", counter).as_slice());
         counter += 1;
-    }
-}
+    });
 
+    t.run().unwrap();
+}
@@ -16,7 +16,7 @@ fn main()
     let mut tt = match tickit::TickitTerm::new()
     {
         Ok(o) => { o }
-        Err(errno) => { fail!("Cannot create TickitTerm - errno #{}", errno); }
+        Err(e) => { fail!("Cannot create TickitTerm - {}", e); }
     };
 
     tt.set_input_fd(libc::STDIN_FILENO);
@@ -25,9 +25,9 @@ fn main()
     tt.await_started(Some(await));
 
 
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
-    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0);
-    tt.setctl_str(tickit::c::TICKIT_TERMCTL_TITLE_TEXT, "XTerm256 colour demo");
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1).unwrap();
+    tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0).unwrap();
+    tt.setctl_str(tickit::c::TICKIT_TERMCTL_TITLE_TEXT, "XTerm256 colour demo").unwrap();
     tt.clear();
 
     let default_pen = tickit::TickitPen::new();
@@ -77,6 +77,20 @@ fn main()
         tt.print(format!("g{:02d}", i).as_slice());
     }
 
+    tt.goto(17, 0);
+    tt.setpen(&default_pen);
+    tt.print("RGB gradient (nearest palette colour without truecolour)");
+
+    pen.clear();
+    tt.goto(19, 0);
+    for x in range(0, 72)
+    {
+        let v = (x * 255 / 71) as u8;
+        pen.set_rgb_attr(tickit::c::TICKIT_PEN_BG, tickit::TickitPenRGB::new(v, 128, 255 - v));
+        tt.setpen(&pen);
+        tt.print(" ");
+    }
+
     let sig = signals::Signals::new().unwrap();
     sig.subscribe(signals::Interrupt);
 
@@ -0,0 +1,63 @@
+#!/usr/bin/env python3
+# Regenerate src/unicode/tables.rs from the Unicode database that ships
+# with Python: ./generate_unicode_tables.py > src/unicode/tables.rs
+
+import sys
+import unicodedata
+
+def is_private(cp):
+    return 0xE000 <= cp <= 0xF8FF or cp >= 0xF0000
+
+def ranges(pred):
+    out = []
+    start = None
+    for cp in range(0x110000):
+        if 0xD800 <= cp <= 0xDFFF:
+            hit = False
+        else:
+            hit = pred(cp, chr(cp))
+        if hit and start is None:
+            start = cp
+        elif not hit and start is not None:
+            out.append((start, cp - 1))
+            start = None
+    if start is not None:
+        out.append((start, 0x10FFFF))
+    return out
+
+def lit(cp):
+    if cp <= 0xFFFF:
+        return "'\\u%04x'" % cp
+    return "'\\U%08x'" % cp
+
+def table(name, doc, rs):
+    print('// %s' % doc)
+    print('pub static %s: &\'static [(char, char)] = &[' % name)
+    line = '   '
+    for lo, hi in rs:
+        item = ' (%s, %s),' % (lit(lo), lit(hi))
+        if len(line) + len(item) > 100:
+            print(line)
+            line = '   '
+        line += item
+    print(line)
+    print('];')
+    print()
+
+cat = unicodedata.category
+eaw = unicodedata.east_asian_width
+
+print('// Generated by generate_unicode_tables.py from Unicode %s; don\'t edit.' % unicodedata.unidata_version)
+print()
+table('COMBINING', 'Mn and Me: marks that draw over the character before them',
+      ranges(lambda cp, ch: cat(ch) in ('Mn', 'Me')))
+table('FORMAT', 'Cf: invisible format characters',
+      ranges(lambda cp, ch: cat(ch) == 'Cf'))
+table('SPACING_MARK', 'Mc: marks that take space of their own but stay with the character before them',
+      ranges(lambda cp, ch: cat(ch) == 'Mc'))
+table('WIDE', 'East Asian Wide and Fullwidth, including unassigned CJK ideographs',
+      ranges(lambda cp, ch: eaw(ch) in ('W', 'F')
+             or 0x20000 <= cp <= 0x2FFFD or 0x30000 <= cp <= 0x3FFFD))
+table('AMBIGUOUS', 'East Asian Ambiguous, outside the private use areas',
+      ranges(lambda cp, ch: eaw(ch) == 'A' and not is_private(cp)
+             and cat(ch) not in ('Mn', 'Me')))
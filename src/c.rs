@@ -15,6 +15,10 @@ bitset!(TickitEventType: c_uint
   TICKIT_EV_MOUSE  = 0x04, // Term = type(TickitMouseEventType), button, line, col
   TICKIT_EV_CHANGE = 0x08, // Pen = {none}
 
+  // libtickit doesn't know these; TickitTerm raises them itself
+  X_TICKIT_EV_PASTE = 0x10000, // Term = str
+  X_TICKIT_EV_FOCUS = 0x20000, // Term = type(1 for in, 0 for out)
+
   TICKIT_EV_UNBIND = 0x80000000 // event handler is being unbound
 })
 
@@ -32,6 +36,9 @@ pub enum TickitMouseEventType
   TICKIT_MOUSEEV_DRAG,
   TICKIT_MOUSEEV_RELEASE,
   TICKIT_MOUSEEV_WHEEL,
+
+  // libtickit doesn't know these; TickitTerm raises them itself
+  X_TICKIT_MOUSEEV_MOVE = 0x100, // motion with no button held
 }
 
 #[repr(C)] #[deriving(PartialEq, Show)]
@@ -39,6 +46,26 @@ pub enum X_Tickit_MouseWheel
 {
   TICKIT_MOUSEWHEEL_UP = 1,
   TICKIT_MOUSEWHEEL_DOWN,
+  TICKIT_MOUSEWHEEL_LEFT,
+  TICKIT_MOUSEWHEEL_RIGHT,
+}
+
+// The button numbers in mouse events, as xterm numbers them; 4 to 7 are
+// the wheel, and arrive as wheel events.
+#[deriving(PartialEq, Show)]
+pub enum X_Tickit_MouseButton
+{
+  TICKIT_MOUSE_BUTTON_LEFT = 1,
+  TICKIT_MOUSE_BUTTON_MIDDLE,
+  TICKIT_MOUSE_BUTTON_RIGHT,
+  TICKIT_MOUSE_BUTTON_WHEEL_UP,
+  TICKIT_MOUSE_BUTTON_WHEEL_DOWN,
+  TICKIT_MOUSE_BUTTON_WHEEL_LEFT,
+  TICKIT_MOUSE_BUTTON_WHEEL_RIGHT,
+  TICKIT_MOUSE_BUTTON_BACK,
+  TICKIT_MOUSE_BUTTON_FORWARD,
+  TICKIT_MOUSE_BUTTON_10,
+  TICKIT_MOUSE_BUTTON_11,
 }
 
 bitset!(X_Tickit_Mod: c_int
@@ -73,13 +100,32 @@ pub enum TickitPenAttr
   TICKIT_PEN_STRIKE,     /* bool */
   TICKIT_PEN_ALTFONT,    /* number */
 
-  TICKIT_N_PEN_ATTRS
+  TICKIT_N_PEN_ATTRS,
+
+  // libtickit doesn't know these; TickitPen keeps them itself
+  X_TICKIT_PEN_BLINK,    /* bool */
+  X_TICKIT_PEN_DIM,      /* bool */
+  X_TICKIT_PEN_OVERLINE, /* bool */
+  X_TICKIT_PEN_CONCEAL,  /* bool */
 }
 
+pub static X_TICKIT_PEN_ATTRS: [TickitPenAttr, ..4] = [X_TICKIT_PEN_BLINK, X_TICKIT_PEN_DIM, X_TICKIT_PEN_OVERLINE, X_TICKIT_PEN_CONCEAL];
+static X_TICKIT_PEN_NAMES: [&'static str, ..4] = ["blink", "dim", "overline", "conceal"];
+
 impl TickitPenAttr
 {
+    /// Where an attribute libtickit doesn't know is in X_TICKIT_PEN_ATTRS.
+    pub fn x_index(self) -> Option<uint>
+    {
+        X_TICKIT_PEN_ATTRS.iter().position(|&a| a == self)
+    }
+
     pub fn attrtype(self) -> TickitPenAttrType
     {
+        if self.x_index().is_some()
+        {
+            return TICKIT_PENTYPE_BOOL;
+        }
         unsafe
         {
             tickit_pen_attrtype(self)
@@ -87,6 +133,11 @@ impl TickitPenAttr
     }
     pub fn attrname_opt(self) -> Option<&'static str>
     {
+        match self.x_index()
+        {
+            Some(i) => return Some(X_TICKIT_PEN_NAMES[i]),
+            None => {}
+        }
         unsafe
         {
             let cstr = tickit_pen_attrname(self);
@@ -104,15 +155,33 @@ impl TickitPenAttr
     {
         self.attrname_opt().unwrap()
     }
-    pub fn lookup_attr(name: &str) -> TickitPenAttr
+    pub fn lookup_attr_opt(name: &str) -> Option<TickitPenAttr>
     {
-        unsafe
+        match X_TICKIT_PEN_NAMES.iter().position(|&n| n == name)
+        {
+            Some(i) => return Some(X_TICKIT_PEN_ATTRS[i]),
+            None => {}
+        }
+        // libtickit gives -1 for a name it doesn't know
+        let attr = unsafe
         {
             name.with_c_str(
                 |n| { tickit_pen_lookup_attr(n) }
             )
+        };
+        if attr >= 0 && attr < TICKIT_N_PEN_ATTRS as c_int
+        {
+            Some(unsafe { ::std::mem::transmute(attr) })
+        }
+        else
+        {
+            None
         }
     }
+    pub fn lookup_attr(name: &str) -> TickitPenAttr
+    {
+        TickitPenAttr::lookup_attr_opt(name).unwrap()
+    }
 }
 
 
@@ -178,7 +247,7 @@ pub fn tickit_pen_unbind_event_id(tt: *mut TickitPen, id: c_int);
 
 pub fn tickit_pen_attrtype(attr: TickitPenAttr) -> TickitPenAttrType;
 pub fn tickit_pen_attrname(attr: TickitPenAttr) -> *const c_char;
-pub fn tickit_pen_lookup_attr(name: *const c_char) -> TickitPenAttr;
+pub fn tickit_pen_lookup_attr(name: *const c_char) -> c_int;
 }
 
 
@@ -328,6 +397,7 @@ pub fn tickit_term_erasech(tt: *mut TickitTerm, count: c_int, moveend: c_int);
 }
 
 #[repr(C)]
+#[deriving(PartialEq, Clone, Show)]
 pub enum TickitTermCtl
 {
   TICKIT_TERMCTL_ALTSCREEN = 1,
@@ -340,9 +410,14 @@ pub enum TickitTermCtl
   TICKIT_TERMCTL_ICONTITLE_TEXT,
   TICKIT_TERMCTL_KEYPAD_APP,
   TICKIT_TERMCTL_COLORS, // read-only
+
+  // libtickit doesn't know these; TickitTerm handles them itself
+  X_TICKIT_TERMCTL_PASTE = 0x100,
+  X_TICKIT_TERMCTL_FOCUS,
 }
 
 #[repr(C)]
+#[deriving(PartialEq, Show)]
 pub enum TickitTermMouseMode
 {
   TICKIT_TERM_MOUSEMODE_OFF,
@@ -352,6 +427,7 @@ pub enum TickitTermMouseMode
 }
 
 #[repr(C)]
+#[deriving(PartialEq, Show)]
 pub enum TickitTermCursorShape
 {
   TICKIT_TERM_CURSORSHAPE_BLOCK = 1,
@@ -359,6 +435,57 @@ pub enum TickitTermCursorShape
   TICKIT_TERM_CURSORSHAPE_LEFT_BAR,
 }
 
+impl TickitTermMouseMode
+{
+    pub fn from_c(v: c_int) -> Option<TickitTermMouseMode>
+    {
+        match v
+        {
+            0 => Some(TICKIT_TERM_MOUSEMODE_OFF),
+            1 => Some(TICKIT_TERM_MOUSEMODE_CLICK),
+            2 => Some(TICKIT_TERM_MOUSEMODE_DRAG),
+            3 => Some(TICKIT_TERM_MOUSEMODE_MOVE),
+            _ => None,
+        }
+    }
+}
+
+impl X_Tickit_MouseButton
+{
+    pub fn from_c(v: c_int) -> Option<X_Tickit_MouseButton>
+    {
+        match v
+        {
+            1 => Some(TICKIT_MOUSE_BUTTON_LEFT),
+            2 => Some(TICKIT_MOUSE_BUTTON_MIDDLE),
+            3 => Some(TICKIT_MOUSE_BUTTON_RIGHT),
+            4 => Some(TICKIT_MOUSE_BUTTON_WHEEL_UP),
+            5 => Some(TICKIT_MOUSE_BUTTON_WHEEL_DOWN),
+            6 => Some(TICKIT_MOUSE_BUTTON_WHEEL_LEFT),
+            7 => Some(TICKIT_MOUSE_BUTTON_WHEEL_RIGHT),
+            8 => Some(TICKIT_MOUSE_BUTTON_BACK),
+            9 => Some(TICKIT_MOUSE_BUTTON_FORWARD),
+            10 => Some(TICKIT_MOUSE_BUTTON_10),
+            11 => Some(TICKIT_MOUSE_BUTTON_11),
+            _ => None,
+        }
+    }
+}
+
+impl TickitTermCursorShape
+{
+    pub fn from_c(v: c_int) -> Option<TickitTermCursorShape>
+    {
+        match v
+        {
+            1 => Some(TICKIT_TERM_CURSORSHAPE_BLOCK),
+            2 => Some(TICKIT_TERM_CURSORSHAPE_UNDER),
+            3 => Some(TICKIT_TERM_CURSORSHAPE_LEFT_BAR),
+            _ => None,
+        }
+    }
+}
+
 extern
 {
 pub fn tickit_term_getctl_int(tt: *mut TickitTerm, ctl: TickitTermCtl, value: *mut c_int) -> c_int;
@@ -0,0 +1,118 @@
+// 24-bit colours for pens, and their nearest equivalents in the xterm
+// palette for terminals (and libtickit code paths) that only know indices.
+
+use std;
+use std::fmt;
+
+/// An exact colour, as sent by `SGR 38;2;r;g;b`.
+#[deriving(PartialEq, Eq, Hash, Clone)]
+pub struct TickitPenRGB
+{
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+/// The value of a colour attribute: a palette index, or an exact colour.
+#[deriving(PartialEq, Eq, Hash, Clone, Show)]
+pub enum TickitPenColour
+{
+    IndexColour(int),
+    RGBColour(TickitPenRGB),
+}
+
+/// What TICKIT_TERMCTL_COLORS reports for a terminal that takes RGB.
+pub static TRUECOLOR_COLORS: int = 1 << 24;
+
+// xterm's defaults for the first 16 palette entries
+static BASE16: [(u8, u8, u8), ..16] =
+[
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+    (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+    (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+// channel values of the 6x6x6 cube at indices 16-231
+static CUBE_LEVELS: [u8, ..6] = [0, 95, 135, 175, 215, 255];
+
+impl TickitPenRGB
+{
+    pub fn new(r: u8, g: u8, b: u8) -> TickitPenRGB
+    {
+        TickitPenRGB{r: r, g: g, b: b}
+    }
+
+    /// Parse `#rrggbb`.
+    pub fn from_hex(s: &str) -> Option<TickitPenRGB>
+    {
+        if s.len() != 7 || !s.starts_with("#")
+        {
+            return None;
+        }
+        let channel = |i: uint| std::num::from_str_radix::<u8>(s.slice(i, i + 2), 16);
+        match (channel(1), channel(3), channel(5))
+        {
+            (Some(r), Some(g), Some(b)) => Some(TickitPenRGB::new(r, g, b)),
+            _ => None,
+        }
+    }
+
+    /// The colour xterm shows for a 256-colour palette index.
+    pub fn from_index(idx: int) -> TickitPenRGB
+    {
+        assert!(idx >= 0 && idx < 256);
+        if idx < 16
+        {
+            let (r, g, b) = BASE16[idx as uint];
+            TickitPenRGB::new(r, g, b)
+        }
+        else if idx < 232
+        {
+            let i = (idx - 16) as uint;
+            TickitPenRGB::new(CUBE_LEVELS[i / 36], CUBE_LEVELS[i / 6 % 6], CUBE_LEVELS[i % 6])
+        }
+        else
+        {
+            let v = (8 + 10 * (idx - 232)) as u8;
+            TickitPenRGB::new(v, v, v)
+        }
+    }
+
+    /// The perceptually closest of the first `limit` palette entries (at
+    /// most 256); ties go to the lower index.
+    pub fn nearest_index(&self, limit: int) -> int
+    {
+        let mut best = 0;
+        let mut best_dist = -1;
+        for idx in range(0, std::cmp::min(limit, 256))
+        {
+            let dist = self.distance(&TickitPenRGB::from_index(idx));
+            if best_dist < 0 || dist < best_dist
+            {
+                best = idx;
+                best_dist = dist;
+            }
+        }
+        best
+    }
+
+    // Squared distance, weighted for how the eye sees it ("redmean"):
+    // green matters most, and red more than blue in reddish colours.
+    fn distance(&self, other: &TickitPenRGB) -> int
+    {
+        let rmean = (self.r as int + other.r as int) / 2;
+        let dr = self.r as int - other.r as int;
+        let dg = self.g as int - other.g as int;
+        let db = self.b as int - other.b as int;
+        ((512 + rmean) * dr * dr >> 8) + 4 * dg * dg + ((767 - rmean) * db * db >> 8)
+    }
+}
+
+impl fmt::Show for TickitPenRGB
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
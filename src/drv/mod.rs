@@ -3,7 +3,7 @@
 use std;
 use termkey;
 
-use libc::{c_char,c_int,c_void,size_t};
+use libc::{c_char,c_int,size_t};
 
 use TickitPen;
 use TickitRect;
@@ -34,9 +34,7 @@ impl CDriverRef
     {
         unsafe
         {
-            let pen = c::tickit_termdrv_current_pen(self.driver);
-            let pen: &TickitPen = std::mem::transmute(&pen);
-            pen.clone()
+            TickitPen::clone_c(c::tickit_termdrv_current_pen(self.driver) as *const _)
         }
     }
 }
@@ -55,6 +53,11 @@ pub trait TickitTermDriverImpl
     fn erasech(&mut self, cdr: CDriverRef, count: int, moveend: Option<bool>);
     fn clear(&mut self, cdr: CDriverRef);
     fn chpen(&mut self, cdr: CDriverRef, delta: &TickitPen, final_: &TickitPen);
+    /// The attributes that never reach `chpen` because libtickit's pens
+    /// can't hold them (RGB colours on a truecolour terminal, underline style
+    /// and colour, and c::X_TICKIT_PEN_ATTRS), as they now are. Return false
+    /// to have them sent as SGR instead.
+    fn chpen_ext(&mut self, cdr: CDriverRef, pen: &TickitPen) -> bool { false }
     fn getctl_int(&mut self, cdr: CDriverRef, ctl: TickitTermCtl) -> Option<int>;
     fn setctl_int(&mut self, cdr: CDriverRef, ctl: TickitTermCtl, value: int) -> bool;
     fn setctl_str(&mut self, cdr: CDriverRef, ctl: TickitTermCtl, value: &str) -> bool;
@@ -67,21 +70,61 @@ struct RustTermDriver
     #[allow(dead_code)]
     driver: c::TickitTermDriver,
     vtable: Box<TickitTermDriverImpl+'static>,
+    // the Rust-side state of the term, made before it so attach can see it
+    term: *mut ::TermState,
+}
+
+// How a TickitTerm reaches its Rust driver for what the C vtable has no
+// slot for; the driver lives as long as the term does.
+pub struct DriverHandle
+{
+    driver: *mut RustTermDriver,
+}
+
+impl DriverHandle
+{
+    pub fn chpen_ext(&self, pen: &TickitPen) -> bool
+    {
+        unsafe
+        {
+            (*self.driver).vtable.chpen_ext(CDriverRef{driver: &mut (*self.driver).driver}, pen)
+        }
+    }
+    pub fn write_str(&self, str_: &str)
+    {
+        unsafe
+        {
+            CDriverRef{driver: &mut (*self.driver).driver}.write_str(str_)
+        }
+    }
+    pub fn setctl_int(&self, ctl: TickitTermCtl, value: int) -> bool
+    {
+        unsafe
+        {
+            (*self.driver).vtable.setctl_int(CDriverRef{driver: &mut (*self.driver).driver}, ctl, value)
+        }
+    }
 }
 
 impl ::TickitTerm
 {
-    pub fn new_for_driver<T: TickitTermDriverImpl + 'static>(driver_impl: T) -> ::TickitTerm
+    pub fn new_for_driver<T: TickitTermDriverImpl + 'static>(driver_impl: T) -> Result<::TickitTerm, ::TickitError>
     {
         unsafe
         {
             let vtable_impl = box driver_impl;
             let c_driver = c::TickitTermDriver{tt: std::ptr::null_mut(), vtable: &RUST_VTABLE};
-            let driver = box RustTermDriver{driver: c_driver, vtable: vtable_impl};
+            let mut x = box ::TermState::new();
+            let driver = box RustTermDriver{driver: c_driver, vtable: vtable_impl, term: &mut *x};
             let raw_driver: *mut c::TickitTermDriver = std::mem::transmute(driver);
             let tt = c::tickit_term_new_for_driver(raw_driver);
-            assert!(tt.is_not_null());
-            TickitTerm{tt: tt, output_hook: std::ptr::null_mut(), output_box: None}
+            if tt.is_null()
+            {
+                return Err(::TickitError::last_os_error());
+            }
+            let mut tt = ::TickitTerm{tt: tt, x: x};
+            tt.x.driver = Some(DriverHandle{driver: raw_driver as *mut RustTermDriver});
+            Ok(tt)
         }
     }
 }
@@ -91,9 +134,7 @@ extern fn rust_vtable_attach(ttd: *mut c::TickitTermDriver, tt: *mut super::c::T
     unsafe
     {
         let ttd: *mut RustTermDriver = std::mem::transmute(ttd);
-        let termp: &mut (_, *mut c_void, *mut c_void) = &mut (tt, std::ptr::null_mut(), std::ptr::null_mut());
-        let tt: &mut ::TickitTerm = std::mem::transmute(termp);
-        (*ttd).vtable.attach(CDriverRef{driver: &mut (*ttd).driver}, tt);
+        ::with_borrowed_term(tt, (*ttd).term, |tt| (*ttd).vtable.attach(CDriverRef{driver: &mut (*ttd).driver}, tt));
     }
 }
 
@@ -194,14 +235,12 @@ extern fn rust_vtable_clear(ttd: *mut c::TickitTermDriver)
     }
 }
 
-extern fn rust_vtable_chpen(ttd: *mut c::TickitTermDriver, mut delta: *const super::c::TickitPen, mut final_: *const super::c::TickitPen)
+extern fn rust_vtable_chpen(ttd: *mut c::TickitTermDriver, delta: *const super::c::TickitPen, final_: *const super::c::TickitPen)
 {
     unsafe
     {
         let ttd: *mut RustTermDriver = std::mem::transmute(ttd);
-        let delta: &mut TickitPen = std::mem::transmute(&mut delta);
-        let final_: &mut TickitPen = std::mem::transmute(&mut final_);
-        (*ttd).vtable.chpen(CDriverRef{driver: &mut (*ttd).driver}, delta, final_);
+        ::with_borrowed_pen(delta as *mut _, |delta| ::with_borrowed_pen(final_ as *mut _, |final_| (*ttd).vtable.chpen(CDriverRef{driver: &mut (*ttd).driver}, delta, final_)));
     }
 }
 
@@ -0,0 +1,75 @@
+use std;
+use std::fmt;
+
+use libc;
+use libc::c_int;
+
+use c::TickitTermCtl;
+
+#[deriving(PartialEq, Clone)]
+pub enum TickitError
+{
+    /// No terminfo entry (or driver) for the named terminal type.
+    UnknownTermTypeError(String),
+    /// A system call failed; holds the errno.
+    IoErrnoError(c_int),
+    /// The terminal driver does not support (or rejected) a control.
+    UnsupportedCtlError(TickitTermCtl),
+    /// A size or position that can never be valid, such as negative lines.
+    InvalidGeometryError(int, int),
+    /// A pen spec that doesn't parse: the byte offset of the bad token, the
+    /// token, and what is wrong with it.
+    PenSpecError(uint, String, &'static str),
+    /// A file couldn't be read: its path and why.
+    FileError(String, String),
+    /// A line of a theme file that doesn't parse: its number (from 1) and
+    /// what is wrong with it.
+    ThemeLineError(uint, String),
+}
+
+impl TickitError
+{
+    /// Capture the current errno, as set by a failing libtickit call.
+    pub fn last_os_error() -> TickitError
+    {
+        IoErrnoError(std::os::errno() as c_int)
+    }
+
+    // libtickit reports a missing terminfo entry as ENOENT
+    pub fn from_termtype_errno(name: &str) -> TickitError
+    {
+        let errno = std::os::errno() as c_int;
+        if errno == libc::ENOENT
+        {
+            UnknownTermTypeError(name.to_string())
+        }
+        else
+        {
+            IoErrnoError(errno)
+        }
+    }
+}
+
+impl fmt::Show for TickitError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self
+        {
+            UnknownTermTypeError(ref name) =>
+                write!(f, "unknown terminal type '{}'", name),
+            IoErrnoError(errno) =>
+                write!(f, "{} (errno #{})", std::os::error_string(errno as uint), errno),
+            UnsupportedCtlError(ctl) =>
+                write!(f, "terminal control {} not supported", ctl),
+            InvalidGeometryError(lines, cols) =>
+                write!(f, "invalid geometry {}x{}", lines, cols),
+            PenSpecError(offset, ref token, why) =>
+                write!(f, "bad pen spec at byte {}: {} '{}'", offset, why, token),
+            FileError(ref path, ref why) =>
+                write!(f, "cannot read {}: {}", path, why),
+            ThemeLineError(line, ref why) =>
+                write!(f, "theme line {}: {}", line, why),
+        }
+    }
+}
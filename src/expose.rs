@@ -0,0 +1,86 @@
+use TickitError;
+use TickitRect;
+use TickitRectSet;
+use TickitRenderBuffer;
+use TickitTerm;
+
+/// Accumulates damaged regions of a terminal and redraws only those.
+pub struct TickitExposeScheduler
+{
+    lines: int,
+    cols: int,
+    damage: TickitRectSet,
+}
+
+impl TickitExposeScheduler
+{
+    pub fn new(lines: int, cols: int) -> TickitExposeScheduler
+    {
+        TickitExposeScheduler{lines: lines, cols: cols, damage: TickitRectSet::new()}
+    }
+
+    pub fn get_size(&self) -> (int, int)
+    {
+        (self.lines, self.cols)
+    }
+
+    /// Change the size of the area; everything is damaged afterwards.
+    pub fn resize(&mut self, lines: int, cols: int)
+    {
+        self.lines = lines;
+        self.cols = cols;
+        self.damage.clear();
+        self.damage_all();
+    }
+
+    /// Mark a region as needing to be redrawn. Overlapping and adjacent
+    /// regions are coalesced.
+    pub fn damage(&mut self, rect: &TickitRect)
+    {
+        let bounds = TickitRect::init_sized(0, 0, self.lines, self.cols);
+        match rect.intersect(&bounds)
+        {
+            Some(rect) => self.damage.add(&rect),
+            None => {}
+        }
+    }
+    pub fn damage_all(&mut self)
+    {
+        let bounds = TickitRect::init_sized(0, 0, self.lines, self.cols);
+        self.damage.add(&bounds);
+    }
+
+    pub fn is_damaged(&self) -> bool
+    {
+        !self.damage.get_rects().is_empty()
+    }
+    pub fn get_rects(&self) -> Vec<TickitRect>
+    {
+        self.damage.get_rects()
+    }
+
+    /// Render each damaged region and send the result to the terminal.
+    ///
+    /// `render` is called once per region, with the buffer clipped to it.
+    /// Returns false if there was nothing to do.
+    pub fn flush(&mut self, tt: &mut TickitTerm, render: |&mut TickitRenderBuffer, &TickitRect|) -> Result<bool, TickitError>
+    {
+        let rects = self.damage.get_rects();
+        if rects.is_empty()
+        {
+            return Ok(false);
+        }
+
+        let mut rb = try!(TickitRenderBuffer::new(self.lines, self.cols));
+        self.damage.clear();
+        for rect in rects.iter()
+        {
+            rb.save();
+            rb.clip(rect);
+            render(&mut rb, rect);
+            rb.restore();
+        }
+        rb.flush_to_term(tt);
+        Ok(true)
+    }
+}
@@ -0,0 +1,362 @@
+// Terminal input that libtickit (through libtermkey) doesn't understand,
+// taken out of the bytes before they reach it: the text of a bracketed
+// paste, which would otherwise arrive as a key event per character,
+// newlines as Enter; focus reports; and SGR mouse reports, which libtermkey
+// reads without motion or the button released.
+//
+// Only the markers for the modes that are on are looked for. Anything that
+// may be the start of one but ends the bytes read so far is held back until
+// more arrives, or for HOLD_NS if none does; a lone Escape isn't, as it is
+// far more often the key than a marker cut short, and libtermkey waits on
+// it anyway. A paste whose end never comes is cut short after
+// PASTE_HOLD_NS without input.
+
+use std;
+
+use c;
+use X_Tickit_Mod;
+
+static ESC: u8 = 0x1b;
+
+static PASTE_START: &'static [u8] = b"\x1b[200~";
+static PASTE_END: &'static [u8] = b"\x1b[201~";
+static FOCUS_IN: &'static [u8] = b"\x1b[I";
+static FOCUS_OUT: &'static [u8] = b"\x1b[O";
+// then button;col;line, and M, or m for a release
+static SGR_MOUSE: &'static [u8] = b"\x1b[<";
+
+static HOLD_NS: u64 = 50_000_000;
+static PASTE_HOLD_NS: u64 = 1_000_000_000;
+
+/// A mouse event as the fields of a c::TickitEvent.
+pub struct MouseReport
+{
+    pub type_: c::TickitMouseEventType,
+    // a c::X_Tickit_MouseWheel for TICKIT_MOUSEEV_WHEEL
+    pub button: int,
+    pub line: int,
+    pub col: int,
+    pub mod_: X_Tickit_Mod,
+}
+
+pub enum InputItem
+{
+    // for libtickit
+    InputBytes(Vec<u8>),
+    InputPaste(String),
+    InputFocus(bool),
+    InputMouse(MouseReport),
+}
+
+pub struct InputFilter
+{
+    // the modes whose markers the terminal may send
+    pub paste_on: bool,
+    pub focus_on: bool,
+    pub mouse_on: bool,
+    held: Vec<u8>,
+    // from time::precise_time_ns, when input last arrived for 'held' or
+    // the paste
+    since: u64,
+    // the text so far, inside a paste
+    paste: Option<Vec<u8>>,
+}
+
+enum Marker
+{
+    PasteStart,
+    PasteEnd,
+    FocusIn,
+    FocusOut,
+    // None for reports that make no event, like a wheel's release
+    Mouse(Option<MouseReport>),
+}
+
+// What the bytes start with.
+enum Lead
+{
+    // of this many bytes
+    LeadMarker(uint, Marker),
+    // the start of a marker, cut short
+    LeadPartial,
+    LeadNone,
+}
+
+fn fixed(bytes: &[u8], marker: &[u8], what: Marker) -> Lead
+{
+    if bytes.starts_with(marker)
+    {
+        LeadMarker(marker.len(), what)
+    }
+    else if marker.starts_with(bytes)
+    {
+        LeadPartial
+    }
+    else
+    {
+        LeadNone
+    }
+}
+
+fn sgr_mouse(bytes: &[u8]) -> Lead
+{
+    if !bytes.starts_with(SGR_MOUSE)
+    {
+        return if SGR_MOUSE.starts_with(bytes) { LeadPartial } else { LeadNone };
+    }
+    let mut params = [0u, ..3];
+    let mut n = 0u;
+    for (i, &b) in bytes.iter().enumerate().skip(SGR_MOUSE.len())
+    {
+        match b
+        {
+            b'0'...b'9' if params[n] < 100000 => params[n] = params[n] * 10 + (b - b'0') as uint,
+            b';' if n < 2 => n += 1,
+            b'M' | b'm' if n == 2 =>
+            {
+                let report = mouse_report(params[0], params[1], params[2], b == b'm');
+                return LeadMarker(i + 1, Mouse(report));
+            }
+            _ => return LeadNone,
+        }
+    }
+    LeadPartial
+}
+
+// The event an SGR report makes: 'code' is xterm's button code, with the
+// modifiers in bits 2-4, motion in bit 5 and the higher buttons in 6-7;
+// col and line count from 1, so a report of 0 for either is dropped.
+fn mouse_report(code: uint, col: uint, line: uint, release: bool) -> Option<MouseReport>
+{
+    if col == 0 || line == 0
+    {
+        return None;
+    }
+    let mut mod_ = X_Tickit_Mod::empty();
+    if code & 4 != 0
+    {
+        mod_ = mod_ | c::TICKIT_MOD_SHIFT;
+    }
+    if code & 8 != 0
+    {
+        mod_ = mod_ | c::TICKIT_MOD_ALT;
+    }
+    if code & 16 != 0
+    {
+        mod_ = mod_ | c::TICKIT_MOD_CTRL;
+    }
+    // 3 in the low bits is no button, unless one of the higher ones
+    let held = code & 3 != 3 || code & 192 != 0;
+    let mut button = (code & 3) as int + 1;
+    if code & 64 != 0
+    {
+        button += 3;
+    }
+    if code & 128 != 0
+    {
+        button += 7;
+    }
+    let (type_, button) = if code & 32 != 0
+    {
+        if held { (c::TICKIT_MOUSEEV_DRAG, button) } else { (c::X_TICKIT_MOUSEEV_MOVE, 0) }
+    }
+    else if 4 <= button && button <= 7
+    {
+        if release
+        {
+            return None;
+        }
+        let dir = match button
+        {
+            4 => c::TICKIT_MOUSEWHEEL_UP,
+            5 => c::TICKIT_MOUSEWHEEL_DOWN,
+            6 => c::TICKIT_MOUSEWHEEL_LEFT,
+            _ => c::TICKIT_MOUSEWHEEL_RIGHT,
+        };
+        (c::TICKIT_MOUSEEV_WHEEL, dir as int)
+    }
+    else if release
+    {
+        (c::TICKIT_MOUSEEV_RELEASE, if held { button } else { 0 })
+    }
+    else if held
+    {
+        (c::TICKIT_MOUSEEV_PRESS, button)
+    }
+    else
+    {
+        return None;
+    };
+    Some(MouseReport{type_: type_, button: button, line: line as int - 1, col: col as int - 1, mod_: mod_})
+}
+
+// Pasted text as the application wants it: UTF-8, with newlines rather
+// than the CRs terminals send, and no NULs to cut it short for libtickit.
+fn paste_text(bytes: &[u8]) -> String
+{
+    let text = String::from_utf8_lossy(bytes).into_string();
+    text.replace("\r\n", "\n").replace("\r", "\n").replace("\0", "")
+}
+
+impl InputFilter
+{
+    pub fn new() -> InputFilter
+    {
+        InputFilter{paste_on: false, focus_on: false, mouse_on: false, held: Vec::new(), since: 0, paste: None}
+    }
+
+    fn lead(&self, bytes: &[u8]) -> Lead
+    {
+        // inside a paste, only its end means anything
+        if self.paste.is_some()
+        {
+            return fixed(bytes, PASTE_END, PasteEnd);
+        }
+        // none of these is the start of another, so the first that isn't
+        // LeadNone is the answer
+        if self.paste_on
+        {
+            match fixed(bytes, PASTE_START, PasteStart)
+            {
+                LeadNone => {}
+                lead => return lead,
+            }
+        }
+        if self.focus_on
+        {
+            match fixed(bytes, FOCUS_IN, FocusIn)
+            {
+                LeadNone => {}
+                lead => return lead,
+            }
+            match fixed(bytes, FOCUS_OUT, FocusOut)
+            {
+                LeadNone => {}
+                lead => return lead,
+            }
+        }
+        if self.mouse_on
+        {
+            return sgr_mouse(bytes);
+        }
+        LeadNone
+    }
+
+    pub fn feed(&mut self, bytes: &[u8], now: u64) -> Vec<InputItem>
+    {
+        let mut data = std::mem::replace(&mut self.held, Vec::new());
+        data.push_all(bytes);
+        let mut items = Vec::new();
+        // the bytes from 'start' to 'i' are yet to go anywhere
+        let mut start = 0u;
+        let mut i = 0u;
+        let mut end = data.len();
+        while i < data.len()
+        {
+            if data[i] != ESC
+            {
+                i += 1;
+                continue;
+            }
+            let (len, marker) = match self.lead(data.slice_from(i))
+            {
+                LeadMarker(len, marker) => (len, marker),
+                // a lone Escape goes on at once, but not out of a paste
+                LeadPartial if self.paste.is_some() || i + 1 < data.len() =>
+                {
+                    end = i;
+                    break;
+                }
+                LeadPartial | LeadNone =>
+                {
+                    i += 1;
+                    continue;
+                }
+            };
+            self.take(data.slice(start, i), &mut items);
+            i += len;
+            start = i;
+            match marker
+            {
+                PasteStart => self.paste = Some(Vec::new()),
+                PasteEnd =>
+                {
+                    let text = self.paste.take().unwrap();
+                    items.push(InputPaste(paste_text(text.as_slice())));
+                }
+                FocusIn => items.push(InputFocus(true)),
+                FocusOut => items.push(InputFocus(false)),
+                Mouse(Some(report)) => items.push(InputMouse(report)),
+                Mouse(None) => {}
+            }
+        }
+        self.take(data.slice(start, end), &mut items);
+        self.held.push_all(data.slice_from(end));
+        if !self.held.is_empty() || self.paste.is_some()
+        {
+            self.since = now;
+        }
+        items
+    }
+
+    // Bytes between markers: pasted text, or else for libtickit.
+    fn take(&mut self, bytes: &[u8], items: &mut Vec<InputItem>)
+    {
+        if bytes.is_empty()
+        {
+            return;
+        }
+        match self.paste
+        {
+            Some(ref mut text) => text.push_all(bytes),
+            None => items.push(InputBytes(bytes.to_vec())),
+        }
+    }
+
+    // How long what is waiting may wait, if anything is.
+    fn hold(&self) -> Option<u64>
+    {
+        if self.paste.is_some()
+        {
+            Some(PASTE_HOLD_NS)
+        }
+        else if !self.held.is_empty()
+        {
+            Some(HOLD_NS)
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Held bytes that have waited long enough to go on as they are, or
+    /// a paste that has waited too long for its end.
+    pub fn expire(&mut self, now: u64) -> Vec<InputItem>
+    {
+        let mut items = Vec::new();
+        match self.hold()
+        {
+            Some(hold) if now - self.since >= hold => {}
+            _ => return items,
+        }
+        let held = std::mem::replace(&mut self.held, Vec::new());
+        match self.paste.take()
+        {
+            Some(mut text) =>
+            {
+                text.push_all(held.as_slice());
+                items.push(InputPaste(paste_text(text.as_slice())));
+            }
+            None => items.push(InputBytes(held)),
+        }
+        items
+    }
+
+    /// Milliseconds until `expire` passes anything on, rounded up so that
+    /// waiting them is enough.
+    pub fn timeout(&self, now: u64) -> Option<uint>
+    {
+        self.hold().map(|hold| ((hold.saturating_sub(now - self.since) + 999_999) / 1_000_000) as uint)
+    }
+}
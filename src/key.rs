@@ -0,0 +1,196 @@
+// Keys as values, rather than the strings libtermkey names them by, so that
+// a mistyped key is a compile error instead of a binding that never fires.
+//
+// Show writes libtermkey's names and FromStr reads them back, for key
+// bindings in config files:
+//
+//     a  C-a  M-S-Up  F12  KP5  Space  Enter
+//
+// Modifiers come first, as M- (Alt), C- and S-; FromStr takes them in any
+// order. A space is written Space, as a bare one wouldn't survive a config
+// file.
+
+use std::fmt;
+use std::from_str::FromStr;
+
+use c;
+use X_Tickit_Mod;
+use {TickitKeyEvent,KeyKeyEvent,KeyTextEvent};
+
+#[deriving(PartialEq, Eq, Hash, Clone)]
+pub enum Key
+{
+    Char(char),
+    /// F1 to F35.
+    Function(uint),
+    Backspace,
+    Tab,
+    Enter,
+    Escape,
+    /// The DEL character, which some terminals send for Backspace.
+    Del,
+    Up,
+    Down,
+    Left,
+    Right,
+    Begin,
+    Find,
+    Insert,
+    Delete,
+    Select,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Keypad0,
+    Keypad1,
+    Keypad2,
+    Keypad3,
+    Keypad4,
+    Keypad5,
+    Keypad6,
+    Keypad7,
+    Keypad8,
+    Keypad9,
+    KeypadEnter,
+    KeypadPlus,
+    KeypadMinus,
+    KeypadMult,
+    KeypadDiv,
+    KeypadComma,
+    KeypadPeriod,
+    KeypadEquals,
+}
+
+static MAX_FUNCTION: uint = 35;
+
+// libtermkey's names for the keys that aren't characters or function keys
+static KEY_NAMES: &'static [(Key, &'static str)] = &[
+    (Backspace, "Backspace"), (Tab, "Tab"), (Enter, "Enter"), (Escape, "Escape"), (Del, "DEL"),
+    (Up, "Up"), (Down, "Down"), (Left, "Left"), (Right, "Right"), (Begin, "Begin"),
+    (Find, "Find"), (Insert, "Insert"), (Delete, "Delete"), (Select, "Select"),
+    (PageUp, "PageUp"), (PageDown, "PageDown"), (Home, "Home"), (End, "End"),
+    (Keypad0, "KP0"), (Keypad1, "KP1"), (Keypad2, "KP2"), (Keypad3, "KP3"), (Keypad4, "KP4"),
+    (Keypad5, "KP5"), (Keypad6, "KP6"), (Keypad7, "KP7"), (Keypad8, "KP8"), (Keypad9, "KP9"),
+    (KeypadEnter, "KPEnter"), (KeypadPlus, "KPPlus"), (KeypadMinus, "KPMinus"),
+    (KeypadMult, "KPMult"), (KeypadDiv, "KPDiv"), (KeypadComma, "KPComma"),
+    (KeypadPeriod, "KPPeriod"), (KeypadEquals, "KPEquals"),
+];
+
+// In the order libtermkey writes them.
+static MOD_PREFIXES: &'static [(X_Tickit_Mod, &'static str)] = &[
+    (c::TICKIT_MOD_ALT, "M-"),
+    (c::TICKIT_MOD_CTRL, "C-"),
+    (c::TICKIT_MOD_SHIFT, "S-"),
+];
+
+impl fmt::Show for Key
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match *self
+        {
+            Char(' ') => write!(f, "Space"),
+            Char(ch) => write!(f, "{}", ch),
+            Function(n) => write!(f, "F{}", n),
+            key =>
+            {
+                let &(_, name) = KEY_NAMES.iter().find(|&&(k, _)| k == key).unwrap();
+                write!(f, "{}", name)
+            }
+        }
+    }
+}
+
+impl FromStr for Key
+{
+    fn from_str(s: &str) -> Option<Key>
+    {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next())
+        {
+            (Some(ch), None) => return Some(Char(ch)),
+            (None, _) => return None,
+            _ => {}
+        }
+        if s == "Space"
+        {
+            return Some(Char(' '));
+        }
+        if s.starts_with("F")
+        {
+            match from_str::<uint>(s.slice_from(1))
+            {
+                Some(n) if 1 <= n && n <= MAX_FUNCTION => return Some(Function(n)),
+                _ => {}
+            }
+        }
+        KEY_NAMES.iter().find(|&&(_, name)| name == s).map(|&(key, _)| key)
+    }
+}
+
+/// A key with the modifiers held with it.
+#[deriving(PartialEq)]
+pub struct KeyPress
+{
+    pub key: Key,
+    pub mod_: X_Tickit_Mod,
+}
+
+impl KeyPress
+{
+    pub fn new(key: Key, mod_: X_Tickit_Mod) -> KeyPress
+    {
+        KeyPress{key: key, mod_: mod_}
+    }
+
+    /// The key of a key event, or None for one libtermkey names but this
+    /// doesn't know.
+    pub fn from_event(ev: &TickitKeyEvent) -> Option<KeyPress>
+    {
+        match *ev
+        {
+            // libtermkey's name carries the modifiers too
+            KeyKeyEvent{key, mod_} => from_str::<KeyPress>(key).map(|k| KeyPress::new(k.key, mod_)),
+            KeyTextEvent{text, mod_} => from_str::<Key>(text).map(|k| KeyPress::new(k, mod_)),
+        }
+    }
+}
+
+impl fmt::Show for KeyPress
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        for &(m, prefix) in MOD_PREFIXES.iter()
+        {
+            if self.mod_.contains(m)
+            {
+                try!(write!(f, "{}", prefix));
+            }
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for KeyPress
+{
+    fn from_str(s: &str) -> Option<KeyPress>
+    {
+        let mut mod_ = X_Tickit_Mod::empty();
+        let mut rest = s;
+        // "C--" is Ctrl and '-', so a prefix needs something after it
+        loop
+        {
+            match MOD_PREFIXES.iter().find(|&&(_, prefix)| rest.len() > prefix.len() && rest.starts_with(prefix))
+            {
+                Some(&(m, prefix)) =>
+                {
+                    mod_.insert(m);
+                    rest = rest.slice_from(prefix.len());
+                }
+                None => break,
+            }
+        }
+        from_str::<Key>(rest).map(|key| KeyPress::new(key, mod_))
+    }
+}
@@ -0,0 +1,162 @@
+// Laying text out in columns: wrapping it into lines, truncating it, and
+// padding it to a width. Widths are those StringPos counts, so wide
+// characters and grapheme clusters are never split.
+
+use StringPos;
+use mbswidth;
+use unicode;
+
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub enum TickitAlign
+{
+    AlignLeft,
+    AlignCentre,
+    AlignRight,
+}
+
+/// One line of wrapped text, as the positions in the whole string where it
+/// starts and ends. A newline counts as one codepoint and grapheme of no
+/// columns; the spaces a line was wrapped at belong to neither line.
+#[deriving(PartialEq, Eq, Clone, Show)]
+pub struct TextLine
+{
+    pub start: StringPos,
+    pub end: StringPos,
+}
+
+impl TextLine
+{
+    pub fn text<'a>(&self, text: &'a str) -> &'a str
+    {
+        text.slice(self.start.bytes, self.end.bytes)
+    }
+    pub fn columns(&self) -> uint
+    {
+        self.end.columns - self.start.columns
+    }
+}
+
+// The position after the grapheme at 'pos'; a control character (or CR LF)
+// counts as a grapheme with no width.
+fn step(text: &str, pos: StringPos) -> StringPos
+{
+    let mut next = pos;
+    let limit = Some(StringPos::limit_graphemes(pos.graphemes + 1));
+    if StringPos::countmore_with(text, &mut next, limit, unicode::ambiguous_wide()).is_none()
+    {
+        let len = unicode::cluster_len(text.slice_from(pos.bytes));
+        let cluster = text.slice(pos.bytes, pos.bytes + len);
+        next.bytes += cluster.len();
+        next.codepoints += cluster.chars().count();
+        next.graphemes += 1;
+    }
+    next
+}
+
+/// Split `text` into lines of at most `cols` columns, at newlines and else
+/// at the spaces between words; a word longer than a line is broken
+/// between graphemes. Trailing spaces are dropped from each line.
+pub fn wrap(text: &str, cols: uint) -> Vec<TextLine>
+{
+    let mut lines = Vec::new();
+    let mut pos = StringPos::zero();
+    let mut start = pos;
+    // where the run of spaces before 'pos' began, if 'pos' follows spaces
+    let mut spaces: Option<StringPos> = None;
+    // the latest place this line could wrap: the end of a word, and the
+    // start of the next
+    let mut wrap_at: Option<(StringPos, StringPos)> = None;
+    while pos.bytes < text.len()
+    {
+        let next = step(text, pos);
+        let grapheme = text.slice(pos.bytes, next.bytes);
+        if grapheme == "\n" || grapheme == "\r\n"
+        {
+            lines.push(TextLine{start: start, end: spaces.unwrap_or(pos)});
+            start = next;
+            spaces = None;
+            wrap_at = None;
+            pos = next;
+            continue;
+        }
+        if grapheme == " "
+        {
+            // spaces may run past the edge, as they go at a wrap anyway
+            if spaces.is_none()
+            {
+                spaces = Some(pos);
+            }
+            pos = next;
+            continue;
+        }
+        match spaces.take()
+        {
+            Some(end) if end.bytes > start.bytes => wrap_at = Some((end, pos)),
+            _ => {}
+        }
+        while next.columns - start.columns > cols
+        {
+            match wrap_at.take()
+            {
+                Some((end, resume)) =>
+                {
+                    lines.push(TextLine{start: start, end: end});
+                    start = resume;
+                }
+                None =>
+                {
+                    // no space to wrap at, so break the word; a grapheme
+                    // wider than the line gets one to itself
+                    if pos.bytes > start.bytes
+                    {
+                        lines.push(TextLine{start: start, end: pos});
+                        start = pos;
+                    }
+                    break;
+                }
+            }
+        }
+        pos = next;
+    }
+    lines.push(TextLine{start: start, end: spaces.unwrap_or(pos)});
+    lines
+}
+
+/// `text` cut to fit in `cols` columns, ending in `ellipsis` if anything
+/// was cut.
+pub fn truncate(text: &str, cols: uint, ellipsis: &str) -> String
+{
+    if mbswidth(text) <= cols
+    {
+        return text.to_string();
+    }
+    let mut keep = StringPos::zero();
+    let mut dots = StringPos::zero();
+    StringPos::count(ellipsis, &mut dots, Some(StringPos::limit_columns(cols)));
+    StringPos::count(text, &mut keep, Some(StringPos::limit_columns(cols - dots.columns)));
+    let mut out = text.slice_to(keep.bytes).to_string();
+    out.push_str(ellipsis.slice_to(dots.bytes));
+    out
+}
+
+/// `text` padded with spaces to `cols` columns; text already that wide is
+/// left as it is.
+pub fn align(text: &str, cols: uint, align: TickitAlign) -> String
+{
+    let width = mbswidth(text);
+    if width >= cols
+    {
+        return text.to_string();
+    }
+    let pad = cols - width;
+    let left = match align
+    {
+        AlignLeft => 0,
+        AlignCentre => pad / 2,
+        AlignRight => pad,
+    };
+    let mut out = String::from_char(left, ' ');
+    out.push_str(text);
+    out.push_str(String::from_char(pad - left, ' ').as_slice());
+    out
+}
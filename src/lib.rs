@@ -1,13 +1,19 @@
 #![feature(link_args)]
 #![feature(macro_rules)]
 #![feature(struct_variant)]
+#![feature(unboxed_closures)]
 #![feature(unsafe_destructor)]
 
 extern crate collections;
 extern crate libc;
+extern crate time;
 
 extern crate termkey;
 
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+
 use libc::c_char;
 use libc::c_int;
 use libc::c_long;
@@ -20,19 +26,45 @@ pub use c::TickitPenAttrType;
 use c::X_Tickit_Mod;
 use c::TickitTermCtl;
 use c::TickitLineStyle;
+#[cfg(not(feature = "rust-renderbuffer"))]
 use c::TickitLineCaps;
 
 mod bitset_macro;
+mod colour;
+mod error;
 pub mod c;
 pub mod drv;
+mod expose;
 mod generated_link;
+mod input;
+pub mod key;
+pub mod layout;
 pub mod mock;
+mod penspec;
+mod plainpen;
+#[cfg(feature = "rust-renderbuffer")]
+mod renderbuffer;
+mod runloop;
+mod theme;
+mod unicode;
+pub mod window;
+
+pub use colour::{TickitPenRGB,TickitPenColour,IndexColour,RGBColour,TRUECOLOR_COLORS};
+pub use error::{TickitError,UnknownTermTypeError,IoErrnoError,UnsupportedCtlError,InvalidGeometryError,PenSpecError,FileError,ThemeLineError};
+pub use expose::TickitExposeScheduler;
+#[cfg(feature = "rust-renderbuffer")]
+pub use renderbuffer::TickitRenderBuffer;
+pub use plainpen::TickitPlainPen;
+pub use runloop::{Tickit,TickitWatchId};
+pub use theme::Theme;
+pub use unicode::{Graphemes,graphemes,set_ambiguous_wide};
 
 fn const_<T>(v: *mut T) -> *const T
 {
     v as *const T
 }
 
+#[cfg(not(feature = "rust-renderbuffer"))]
 fn const_opt_pen(v: Option<&TickitPen>) -> *const c::TickitPen
 {
     v.map(|p| const_(p.pen)).unwrap_or(std::ptr::null())
@@ -45,12 +77,48 @@ pub enum TickitKeyEvent<'a>
     KeyTextEvent{pub text: &'a str, pub mod_: X_Tickit_Mod},
 }
 
+impl<'a> TickitKeyEvent<'a>
+{
+    /// The key as a value rather than libtermkey's name for it.
+    pub fn key(&self) -> Option<key::KeyPress>
+    {
+        key::KeyPress::from_event(self)
+    }
+}
+
 pub enum TickitMouseEvent
 {
     MousePressEvent{pub button: int, pub line: int, pub col: int, pub mod_: X_Tickit_Mod},
     MouseDragEvent{pub button: int, pub line: int, pub col: int, pub mod_: X_Tickit_Mod},
     MouseReleaseEvent{pub button: int, pub line: int, pub col: int, pub mod_: X_Tickit_Mod},
     MouseWheelEvent{pub dir: c::X_Tickit_MouseWheel, pub line: int, pub col: int, pub mod_: X_Tickit_Mod},
+    /// Only with TICKIT_TERM_MOUSEMODE_MOVE.
+    MouseMoveEvent{pub line: int, pub col: int, pub mod_: X_Tickit_Mod},
+}
+
+impl TickitMouseEvent
+{
+    /// The button pressed, dragged or released, or for the wheel, the
+    /// button xterm numbers its direction as; None for motion, and for a
+    /// release when the terminal doesn't say which button.
+    pub fn button(&self) -> Option<c::X_Tickit_MouseButton>
+    {
+        match *self
+        {
+            MousePressEvent{button, ..} | MouseDragEvent{button, ..} | MouseReleaseEvent{button, ..} =>
+            {
+                c::X_Tickit_MouseButton::from_c(button as c_int)
+            }
+            MouseWheelEvent{dir, ..} =>
+            {
+                c::X_Tickit_MouseButton::from_c(dir as c_int + 3)
+            }
+            MouseMoveEvent{..} =>
+            {
+                None
+            }
+        }
+    }
 }
 
 pub enum TickitEvent<'a>
@@ -58,15 +126,102 @@ pub enum TickitEvent<'a>
     ResizeEvent{pub lines: int, pub cols: int},
     KeyEvent(TickitKeyEvent<'a>),
     MouseEvent(TickitMouseEvent),
+    PasteEvent{pub text: &'a str},
+    FocusEvent{pub focused: bool},
     ChangeEvent,
     // UnbindEvent,
     UnknownEvent,
 }
 
 
+/// How TICKIT_PEN_UNDER is drawn; the value is the one `SGR 4:x` takes.
+#[deriving(PartialEq, Eq, Hash, Clone, Show)]
+pub enum TickitUnderline
+{
+    NoUnderline = 0,
+    SingleUnderline = 1,
+    DoubleUnderline = 2,
+    CurlyUnderline = 3,
+    DottedUnderline = 4,
+    DashedUnderline = 5,
+}
+
+impl TickitUnderline
+{
+    pub fn from_sgr(n: int) -> Option<TickitUnderline>
+    {
+        match n
+        {
+            0 => Some(NoUnderline),
+            1 => Some(SingleUnderline),
+            2 => Some(DoubleUnderline),
+            3 => Some(CurlyUnderline),
+            4 => Some(DottedUnderline),
+            5 => Some(DashedUnderline),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            NoUnderline => "none",
+            SingleUnderline => "single",
+            DoubleUnderline => "double",
+            CurlyUnderline => "curly",
+            DottedUnderline => "dotted",
+            DashedUnderline => "dashed",
+        }
+    }
+    pub fn from_name(name: &str) -> Option<TickitUnderline>
+    {
+        range(0, 6).filter_map(|n| TickitUnderline::from_sgr(n)).find(|style| style.name() == name)
+    }
+}
+
+/// The value of a pen attribute, of the kind its TickitPenAttrType says.
+#[deriving(PartialEq, Clone, Show)]
+pub enum TickitPenValue
+{
+    BoolValue(bool),
+    IntValue(int),
+    ColourValue(TickitPenColour),
+}
+
+// Every attribute a TickitPen can hold, in the order attrs() gives them
+static PEN_ATTRS: [TickitPenAttr, ..12] =
+[
+    c::TICKIT_PEN_FG, c::TICKIT_PEN_BG,
+    c::TICKIT_PEN_BOLD, c::TICKIT_PEN_UNDER, c::TICKIT_PEN_ITALIC, c::TICKIT_PEN_REVERSE, c::TICKIT_PEN_STRIKE,
+    c::TICKIT_PEN_ALTFONT,
+    c::X_TICKIT_PEN_BLINK, c::X_TICKIT_PEN_DIM, c::X_TICKIT_PEN_OVERLINE, c::X_TICKIT_PEN_CONCEAL,
+];
+
 pub struct TickitPen
 {
     pen: *mut c::TickitPen,
+    // exact fg and bg, if set that way; libtickit holds the nearest index
+    rgb: [Option<TickitPenRGB>, ..2],
+    // libtickit only knows whether there is an underline, not its style
+    // (None is single) or colour
+    under_style: Option<TickitUnderline>,
+    under_colour: Option<TickitPenColour>,
+    // the attributes in c::X_TICKIT_PEN_ATTRS
+    x_attrs: [Option<bool>, ..4],
+    // made with the pen's first binding; see PenHooks
+    hooks: Option<Rc<PenHooks>>,
+}
+
+// Which slot of TickitPen::rgb belongs to a colour attribute
+fn rgb_slot(attr: TickitPenAttr) -> Option<uint>
+{
+    match attr
+    {
+        c::TICKIT_PEN_FG => Some(0),
+        c::TICKIT_PEN_BG => Some(1),
+        _ => None,
+    }
 }
 
 impl TickitPen
@@ -77,9 +232,20 @@ impl TickitPen
         {
             let cpen = c::tickit_pen_new();
             assert!(cpen.is_not_null());
-            TickitPen{pen: cpen}
+            TickitPen::from_c(cpen)
         }
     }
+
+    // Take ownership of a pen from libtickit.
+    fn from_c(pen: *mut c::TickitPen) -> TickitPen
+    {
+        TickitPen{pen: pen, rgb: [None, None], under_style: None, under_colour: None, x_attrs: [None, ..4], hooks: None}
+    }
+    // A copy of a pen owned by libtickit; it can't carry RGB colours.
+    unsafe fn clone_c(pen: *const c::TickitPen) -> TickitPen
+    {
+        TickitPen::from_c(c::tickit_pen_clone(pen))
+    }
 }
 
 impl Clone for TickitPen
@@ -88,7 +254,16 @@ impl Clone for TickitPen
     {
         unsafe
         {
-            TickitPen{pen: c::tickit_pen_clone(const_(self.pen))}
+            TickitPen
+            {
+                pen: c::tickit_pen_clone(const_(self.pen)),
+                rgb: self.rgb,
+                under_style: self.under_style,
+                under_colour: self.under_colour,
+                x_attrs: self.x_attrs,
+                // libtickit doesn't copy the bindings either
+                hooks: None,
+            }
         }
     }
     fn clone_from(&mut self, other: &TickitPen)
@@ -97,6 +272,10 @@ impl Clone for TickitPen
         {
             c::tickit_pen_copy(self.pen, const_(other.pen), 1)
         }
+        self.rgb = other.rgb;
+        self.under_style = other.under_style;
+        self.under_colour = other.under_colour;
+        self.x_attrs = other.x_attrs;
     }
 }
 
@@ -115,6 +294,11 @@ impl TickitPen
 {
     pub fn has_attr(&self, attr: TickitPenAttr) -> bool
     {
+        match attr.x_index()
+        {
+            Some(i) => return self.x_attrs[i].is_some(),
+            None => {}
+        }
         unsafe
         {
             c::tickit_pen_has_attr(const_(self.pen), attr) != 0
@@ -125,10 +309,16 @@ impl TickitPen
         unsafe
         {
             c::tickit_pen_is_nonempty(const_(self.pen)) != 0
+                || self.x_attrs.iter().any(|v| v.is_some())
         }
     }
     pub fn nondefault_attr(&self, attr: TickitPenAttr) -> bool
     {
+        match attr.x_index()
+        {
+            Some(i) => return self.x_attrs[i] == Some(true),
+            None => {}
+        }
         unsafe
         {
             c::tickit_pen_nondefault_attr(const_(self.pen), attr) != 0
@@ -139,11 +329,17 @@ impl TickitPen
         unsafe
         {
             c::tickit_pen_is_nondefault(const_(self.pen)) != 0
+                || self.x_attrs.iter().any(|&v| v == Some(true))
         }
     }
 
     pub fn get_bool_attr(&self, attr: TickitPenAttr) -> bool
     {
+        match attr.x_index()
+        {
+            Some(i) => return self.x_attrs[i] == Some(true),
+            None => {}
+        }
         unsafe
         {
             c::tickit_pen_get_bool_attr(const_(self.pen), attr) != 0
@@ -151,6 +347,19 @@ impl TickitPen
     }
     pub fn set_bool_attr(&mut self, attr: TickitPenAttr, val: bool)
     {
+        match attr.x_index()
+        {
+            Some(i) =>
+            {
+                self.x_attrs[i] = Some(val);
+                return;
+            }
+            None => {}
+        }
+        if attr == c::TICKIT_PEN_UNDER
+        {
+            self.under_style = None;
+        }
         unsafe
         {
             c::tickit_pen_set_bool_attr(self.pen, attr, val as c_int);
@@ -224,6 +433,7 @@ impl TickitPen
     }
     pub fn set_colour_attr(&mut self, attr: TickitPenAttr, value: int)
     {
+        self.set_rgb_slot(attr, None);
         unsafe
         {
             c::tickit_pen_set_colour_attr(self.pen, attr, value as c_int);
@@ -234,18 +444,170 @@ impl TickitPen
         self.set_colour_attr(attr, val);
         self
     }
+    /// Accepts anything libtickit does (names and indices), and `#rrggbb`.
     pub fn set_colour_attr_desc(&mut self, attr: TickitPenAttr, value: &str) -> bool
     {
-        unsafe
+        if value.starts_with("#")
+        {
+            return match TickitPenRGB::from_hex(value)
+            {
+                Some(rgb) =>
+                {
+                    self.set_rgb_attr(attr, rgb);
+                    true
+                }
+                None => false,
+            };
+        }
+        let ok = unsafe
         {
             value.with_c_str(
                 |v| { c::tickit_pen_set_colour_attr_desc(self.pen, attr, v) }
             ) != 0
+        };
+        if ok
+        {
+            self.set_rgb_slot(attr, None);
+        }
+        ok
+    }
+
+    /// The exact colour of a colour attribute, if it was set as one.
+    pub fn get_rgb_attr(&self, attr: TickitPenAttr) -> Option<TickitPenRGB>
+    {
+        match rgb_slot(attr)
+        {
+            Some(slot) if self.has_attr(attr) => self.rgb[slot],
+            _ => None,
+        }
+    }
+    /// Set a colour attribute to an exact colour. `get_colour_attr`, and
+    /// anything inside libtickit, sees the nearest 256-colour index, and
+    /// pens that come back from libtickit carry only that.
+    pub fn set_rgb_attr(&mut self, attr: TickitPenAttr, rgb: TickitPenRGB)
+    {
+        unsafe
+        {
+            c::tickit_pen_set_colour_attr(self.pen, attr, rgb.nearest_index(256) as c_int);
+        }
+        self.set_rgb_slot(attr, Some(rgb));
+    }
+    pub fn with_rgb_attr(mut self, attr: TickitPenAttr, rgb: TickitPenRGB) -> TickitPen
+    {
+        self.set_rgb_attr(attr, rgb);
+        self
+    }
+
+    /// A colour attribute as whichever kind it was set as.
+    pub fn get_colour(&self, attr: TickitPenAttr) -> Option<TickitPenColour>
+    {
+        match self.get_rgb_attr(attr)
+        {
+            Some(rgb) => Some(RGBColour(rgb)),
+            None => self.maybe_get_colour_attr(attr).map(|idx| IndexColour(idx)),
+        }
+    }
+    pub fn set_colour(&mut self, attr: TickitPenAttr, colour: TickitPenColour)
+    {
+        match colour
+        {
+            IndexColour(idx) => self.set_colour_attr(attr, idx),
+            RGBColour(rgb) => self.set_rgb_attr(attr, rgb),
+        }
+    }
+
+    /// The underline style, NoUnderline if TICKIT_PEN_UNDER is false or
+    /// unset; setting the attribute as a bool means a single line.
+    pub fn get_under_style(&self) -> TickitUnderline
+    {
+        if !self.get_bool_attr(c::TICKIT_PEN_UNDER)
+        {
+            return NoUnderline;
+        }
+        self.under_style.unwrap_or(SingleUnderline)
+    }
+    pub fn maybe_get_under_style(&self) -> Option<TickitUnderline>
+    {
+        if self.has_attr(c::TICKIT_PEN_UNDER)
+        {
+            Some(self.get_under_style())
+        }
+        else
+        {
+            None
+        }
+    }
+    pub fn set_under_style(&mut self, style: TickitUnderline)
+    {
+        self.set_bool_attr(c::TICKIT_PEN_UNDER, style != NoUnderline);
+        self.under_style = match style
+        {
+            NoUnderline | SingleUnderline => None,
+            style => Some(style),
+        };
+    }
+    pub fn with_under_style(mut self, style: TickitUnderline) -> TickitPen
+    {
+        self.set_under_style(style);
+        self
+    }
+
+    /// The colour of the underline, if it differs from the text's.
+    pub fn get_under_colour(&self) -> Option<TickitPenColour>
+    {
+        self.under_colour
+    }
+    pub fn set_under_colour(&mut self, colour: TickitPenColour)
+    {
+        self.under_colour = Some(colour);
+    }
+    pub fn with_under_colour(mut self, colour: TickitPenColour) -> TickitPen
+    {
+        self.set_under_colour(colour);
+        self
+    }
+    pub fn clear_under_colour(&mut self)
+    {
+        self.under_colour = None;
+    }
+
+    // Take the attributes libtickit's pens can't hold from 'src'.
+    fn copy_ext(&mut self, src: &TickitPen)
+    {
+        if self.get_bool_attr(c::TICKIT_PEN_UNDER)
+        {
+            self.under_style = src.under_style;
+        }
+        self.under_colour = src.under_colour;
+        self.x_attrs = src.x_attrs;
+        self.rgb = src.rgb;
+    }
+
+    fn set_rgb_slot(&mut self, attr: TickitPenAttr, rgb: Option<TickitPenRGB>)
+    {
+        match rgb_slot(attr)
+        {
+            Some(slot) => self.rgb[slot] = rgb,
+            None => {}
         }
     }
 
     pub fn clear_attr(&mut self, attr: TickitPenAttr)
     {
+        match attr.x_index()
+        {
+            Some(i) =>
+            {
+                self.x_attrs[i] = None;
+                return;
+            }
+            None => {}
+        }
+        self.set_rgb_slot(attr, None);
+        if attr == c::TICKIT_PEN_UNDER
+        {
+            self.under_style = None;
+        }
         unsafe
         {
             c::tickit_pen_clear_attr(self.pen, attr);
@@ -253,17 +615,31 @@ impl TickitPen
     }
     pub fn clear(&mut self)
     {
+        self.rgb = [None, None];
+        self.under_style = None;
+        self.under_colour = None;
+        self.x_attrs = [None, ..4];
         unsafe
         {
             c::tickit_pen_clear(self.pen);
         }
     }
 
+    /// For TICKIT_PEN_UNDER this also compares the style and colour of the
+    /// underline.
     pub fn equiv_attr(&self, b: &TickitPen, attr: TickitPenAttr) -> bool
     {
+        if attr.x_index().is_some()
+        {
+            return self.get_bool_attr(attr) == b.get_bool_attr(attr);
+        }
+        let same_under = attr != c::TICKIT_PEN_UNDER
+            || (self.get_under_style() == b.get_under_style() && self.under_colour == b.under_colour);
         unsafe
         {
             c::tickit_pen_equiv_attr(const_(self.pen), const_(b.pen), attr) != 0
+                && self.get_rgb_attr(attr) == b.get_rgb_attr(attr)
+                && same_under
         }
     }
     pub fn equiv(&self, b: &TickitPen) -> bool
@@ -271,11 +647,41 @@ impl TickitPen
         unsafe
         {
             c::tickit_pen_equiv(const_(self.pen), const_(b.pen)) != 0
+                && self.get_rgb_attr(c::TICKIT_PEN_FG) == b.get_rgb_attr(c::TICKIT_PEN_FG)
+                && self.get_rgb_attr(c::TICKIT_PEN_BG) == b.get_rgb_attr(c::TICKIT_PEN_BG)
+                && self.get_under_style() == b.get_under_style()
+                && self.under_colour == b.under_colour
+                && c::X_TICKIT_PEN_ATTRS.iter().all(|&attr| self.get_bool_attr(attr) == b.get_bool_attr(attr))
         }
     }
 
+    /// Copying TICKIT_PEN_UNDER brings the underline style and colour too.
     pub fn copy_attr(&mut self, src: &TickitPen, attr: TickitPenAttr)
     {
+        match attr.x_index()
+        {
+            Some(i) =>
+            {
+                if src.x_attrs[i].is_some()
+                {
+                    self.x_attrs[i] = src.x_attrs[i];
+                }
+                return;
+            }
+            None => {}
+        }
+        if src.has_attr(attr)
+        {
+            self.set_rgb_slot(attr, src.get_rgb_attr(attr));
+            if attr == c::TICKIT_PEN_UNDER
+            {
+                self.under_style = src.under_style;
+                if src.under_colour.is_some()
+                {
+                    self.under_colour = src.under_colour;
+                }
+            }
+        }
         unsafe
         {
             c::tickit_pen_copy_attr(self.pen, const_(src.pen), attr);
@@ -283,11 +689,138 @@ impl TickitPen
     }
     pub fn copy(&mut self, src: &TickitPen, overwrite: bool)
     {
+        for &attr in [c::TICKIT_PEN_FG, c::TICKIT_PEN_BG].iter()
+        {
+            if src.has_attr(attr) && (overwrite || !self.has_attr(attr))
+            {
+                self.set_rgb_slot(attr, src.get_rgb_attr(attr));
+            }
+        }
+        if src.has_attr(c::TICKIT_PEN_UNDER) && (overwrite || !self.has_attr(c::TICKIT_PEN_UNDER))
+        {
+            self.under_style = src.under_style;
+        }
+        if src.under_colour.is_some() && (overwrite || self.under_colour.is_none())
+        {
+            self.under_colour = src.under_colour;
+        }
+        for i in range(0, self.x_attrs.len())
+        {
+            if src.x_attrs[i].is_some() && (overwrite || self.x_attrs[i].is_none())
+            {
+                self.x_attrs[i] = src.x_attrs[i];
+            }
+        }
         unsafe
         {
             c::tickit_pen_copy(self.pen, const_(src.pen), overwrite as c_int);
         }
     }
+
+    // Make this pen equivalent to `new` where it is, firing
+    // TICKIT_EV_CHANGE once if that changes anything, even only what
+    // libtickit doesn't hold. libtickit fires on each step of the way, so
+    // those are swallowed and the event is fired through the bindings.
+    fn update(&mut self, new: &TickitPen)
+    {
+        if self.equiv(new)
+        {
+            return;
+        }
+        let hooks = match self.hooks.clone()
+        {
+            Some(hooks) => hooks,
+            None =>
+            {
+                self.clear();
+                self.copy(new, true);
+                return;
+            }
+        };
+        let was_quiet = hooks.quiet.get();
+        hooks.quiet.set(true);
+        self.clear();
+        self.copy(new, true);
+        hooks.quiet.set(was_quiet);
+        if !was_quiet
+        {
+            hooks.fire(self.pen, c::TICKIT_EV_CHANGE);
+        }
+    }
+}
+
+impl TickitPen
+{
+    pub fn get_value(&self, attr: TickitPenAttr) -> Option<TickitPenValue>
+    {
+        match attr.attrtype()
+        {
+            c::TICKIT_PENTYPE_BOOL => self.maybe_get_bool_attr(attr).map(|v| BoolValue(v)),
+            c::TICKIT_PENTYPE_INT => self.maybe_get_int_attr(attr).map(|v| IntValue(v)),
+            c::TICKIT_PENTYPE_COLOUR => self.get_colour(attr).map(|v| ColourValue(v)),
+        }
+    }
+    pub fn set_value(&mut self, attr: TickitPenAttr, value: TickitPenValue)
+    {
+        match value
+        {
+            BoolValue(v) => self.set_bool_attr(attr, v),
+            IntValue(v) => self.set_int_attr(attr, v),
+            ColourValue(v) => self.set_colour(attr, v),
+        }
+    }
+
+    /// The attributes that are set, with their values. The underline
+    /// style and colour aren't attributes of their own; see
+    /// get_under_style and get_under_colour.
+    pub fn attrs<'a>(&'a self) -> TickitPenAttrs<'a>
+    {
+        TickitPenAttrs{pen: self, next: 0}
+    }
+
+    /// What chpen would hand the driver to go from this pen to `new`: the
+    /// attributes `new` sets that this one doesn't, or sets differently.
+    /// Those `new` leaves unset are left out, as chpen leaves them alone.
+    pub fn diff(&self, new: &TickitPen) -> TickitPen
+    {
+        let mut delta = TickitPen::new();
+        for (attr, _) in new.attrs()
+        {
+            if !self.has_attr(attr) || !self.equiv_attr(new, attr)
+            {
+                delta.copy_attr(new, attr);
+            }
+        }
+        if new.under_colour.is_some() && new.under_colour != self.under_colour
+        {
+            delta.under_colour = new.under_colour;
+        }
+        delta
+    }
+}
+
+pub struct TickitPenAttrs<'a>
+{
+    pen: &'a TickitPen,
+    next: uint,
+}
+
+impl<'a> Iterator<(TickitPenAttr, TickitPenValue)> for TickitPenAttrs<'a>
+{
+    fn next(&mut self) -> Option<(TickitPenAttr, TickitPenValue)>
+    {
+        while self.next < PEN_ATTRS.len()
+        {
+            let attr = PEN_ATTRS[self.next];
+            self.next += 1;
+            match self.pen.get_value(attr)
+            {
+                Some(value) => return Some((attr, value)),
+                None => {}
+            }
+        }
+        None
+    }
 }
 
 fn event_args<'a>(ty: c::TickitEventType, ar: &'a mut c::TickitEvent) -> TickitEvent<'a>
@@ -339,9 +872,22 @@ fn event_args<'a>(ty: c::TickitEventType, ar: &'a mut c::TickitEvent) -> TickitE
                         let dir: c::X_Tickit_MouseWheel = unsafe { std::mem::transmute(ar.button) };
                         MouseWheelEvent{dir: dir, line: ar.line as int, col: ar.col as int, mod_: ar.mod_}
                     }
+                    c::X_TICKIT_MOUSEEV_MOVE =>
+                    {
+                        MouseMoveEvent{line: ar.line as int, col: ar.col as int, mod_: ar.mod_}
+                    }
                 }
             )
         }
+        x if x == c::X_TICKIT_EV_PASTE =>
+        {
+            let text: &'a str = unsafe { std::str::raw::c_str_to_static_slice(ar.str_) };
+            PasteEvent{text: text}
+        }
+        x if x == c::X_TICKIT_EV_FOCUS =>
+        {
+            FocusEvent{focused: ar.type_ != 0}
+        }
         x if x == c::TICKIT_EV_CHANGE =>
         {
             ChangeEvent
@@ -353,25 +899,180 @@ fn event_args<'a>(ty: c::TickitEventType, ar: &'a mut c::TickitEvent) -> TickitE
     }
 }
 
-extern fn pen_hacky_forever_bind_function(mut pen: *mut c::TickitPen, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+// A TickitPen that doesn't own its pointer, for passing to callbacks.
+unsafe fn with_borrowed_pen<R>(pen: *mut c::TickitPen, f: |&mut TickitPen| -> R) -> R
+{
+    with_borrowed_pen_hooks(pen, None, f)
+}
+
+// The same, sharing the bindings of the TickitPen that made the one
+// being called.
+unsafe fn with_borrowed_pen_hooks<R>(pen: *mut c::TickitPen, hooks: Option<Rc<PenHooks>>, f: |&mut TickitPen| -> R) -> R
+{
+    let mut pen_ = TickitPen::from_c(pen);
+    pen_.hooks = hooks;
+    let ret = f(&mut pen_);
+    pen_.hooks = None;
+    std::mem::forget(pen_);
+    ret
+}
+
+// What a pen's bindings share beyond libtickit's, as its callbacks only get
+// the C pen.
+struct PenHooks
+{
+    // set while TickitPen::update swallows libtickit's change events
+    quiet: Cell<bool>,
+    // each binding's events, id, function and data, so that update can
+    // fire its own change event
+    bound: RefCell<Vec<(c::TickitEventType, c_int, c::TickitPenEventFn, *mut c_void)>>,
+}
+
+impl PenHooks
+{
+    fn new() -> PenHooks
+    {
+        PenHooks{quiet: Cell::new(false), bound: RefCell::new(Vec::new())}
+    }
+
+    // Call those bound to 'ev', as libtickit would.
+    fn fire(&self, pen: *mut c::TickitPen, ev: c::TickitEventType)
+    {
+        let bound: Vec<(c::TickitEventType, c_int, c::TickitPenEventFn, *mut c_void)> = self.bound.borrow().iter().map(|&b| b).collect();
+        for &(bound_ev, id, fun, data) in bound.iter()
+        {
+            // an earlier callback may have unbound it
+            if !bound_ev.intersects(ev) || !self.bound.borrow().iter().any(|&(_, i, _, _)| i == id)
+            {
+                continue;
+            }
+            let mut args = x_event_args();
+            (fun.unwrap())(pen, ev, &mut args, data);
+        }
+    }
+
+    // libtickit has unbound the binding with 'data'.
+    fn unbound(&self, data: *mut c_void)
+    {
+        self.bound.borrow_mut().retain(|&(_, _, _, d)| d != data);
+    }
+}
+
+impl TickitPen
+{
+    // Every binding goes through here, so that update can find it.
+    unsafe fn bind_c(&mut self, ev: c::TickitEventType, fun: c::TickitPenEventFn, data: *mut c_void) -> c_int
+    {
+        let id = c::tickit_pen_bind_event(self.pen, ev, fun, data);
+        self.get_hooks().bound.borrow_mut().push((ev, id, fun, data));
+        id
+    }
+
+    fn get_hooks(&mut self) -> Rc<PenHooks>
+    {
+        if self.hooks.is_none()
+        {
+            self.hooks = Some(Rc::new(PenHooks::new()));
+        }
+        self.hooks.clone().unwrap()
+    }
+}
+
+pub type PenEventFn = Box<FnMut(&mut TickitPen, &TickitEvent) + 'static>;
+
+// Shared between the guard and libtickit, so that either may go first.
+struct PenBinding
+{
+    // null once unbound, either by the guard or by the pen being destroyed
+    pen: *mut c::TickitPen,
+    hooks: Rc<PenHooks>,
+    id: c_int,
+    cb: Option<PenEventFn>,
+}
+
+/// Keeps a closure bound to a pen event; unbinds it when dropped.
+#[must_use]
+pub struct PenEventGuard
+{
+    binding: Rc<RefCell<PenBinding>>,
+}
+
+impl PenEventGuard
+{
+    pub fn is_bound(&self) -> bool
+    {
+        self.binding.borrow().pen.is_not_null()
+    }
+    /// Leave the closure bound until the pen is destroyed.
+    pub fn forget(self)
+    {
+        unsafe
+        {
+            std::mem::forget(self);
+        }
+    }
+}
+
+impl Drop for PenEventGuard
 {
-    if ev == c::TICKIT_EV_UNBIND
+    fn drop(&mut self)
     {
-        return;
+        let (pen, id) = { let b = self.binding.borrow(); (b.pen, b.id) };
+        if pen.is_not_null()
+        {
+            unsafe
+            {
+                c::tickit_pen_unbind_event_id(pen, id);
+            }
+        }
     }
+}
+
+extern fn pen_binding_callback(pen: *mut c::TickitPen, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+{
     unsafe
     {
-        let penp = &mut pen;
-        let pen_: &mut TickitPen = std::mem::transmute(penp);
-        let args_ = event_args(ev, &mut *args);
-        let cb: fn(&mut TickitPen, &TickitEvent) = std::mem::transmute(data);
-        cb(pen_, &args_);
+        let slot = data as *mut Rc<RefCell<PenBinding>>;
+        if ev == c::TICKIT_EV_UNBIND
+        {
+            let slot: Box<Rc<RefCell<PenBinding>>> = std::mem::transmute(slot);
+            let mut b = slot.borrow_mut();
+            b.hooks.unbound(data);
+            b.pen = std::ptr::null_mut();
+            b.cb = None;
+            return;
+        }
+        let hooks = (*slot).borrow().hooks.clone();
+        if ev == c::TICKIT_EV_CHANGE && hooks.quiet.get()
+        {
+            return;
+        }
+
+        // keep the binding alive even if the callback drops its guard
+        let binding = (*slot).clone();
+        let cb = binding.borrow_mut().cb.take();
+        match cb
+        {
+            Some(mut cb) =>
+            {
+                let args_ = event_args(ev, &mut *args);
+                with_borrowed_pen_hooks(pen, Some(hooks), |pen_| cb.call_mut((pen_, &args_)));
+                let mut b = binding.borrow_mut();
+                if b.pen.is_not_null()
+                {
+                    b.cb = Some(cb);
+                }
+            }
+            None => {}
+        }
     }
 }
 
 struct LivelyPenData<'a>
 {
-    pen: *mut TickitPen,
+    // null once unbound
+    pen: *mut c::TickitPen,
+    hooks: Rc<PenHooks>,
     cb: |&mut TickitPen, &TickitEvent|: 'a,
 }
 
@@ -391,41 +1092,50 @@ impl<'a> Drop for LivelyPenEvent<'a>
         {
             unsafe
             {
-                c::tickit_pen_unbind_event_id((*self.data.pen).pen, self.id);
+                c::tickit_pen_unbind_event_id(self.data.pen, self.id);
             }
         }
     }
 }
 
-extern fn pen_lively_callback(mut pen: *mut c::TickitPen, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+extern fn pen_lively_callback(pen: *mut c::TickitPen, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
 {
     unsafe
     {
         let lively: *mut LivelyPenData = std::mem::transmute(data);
         if ev == c::TICKIT_EV_UNBIND
         {
+            (*lively).hooks.unbound(data);
             (*lively).pen = std::ptr::null_mut();
         }
-        else
+        else if ev != c::TICKIT_EV_CHANGE || !(*lively).hooks.quiet.get()
         {
-            let penp = &mut pen;
-            let pen_: &mut TickitPen = std::mem::transmute(penp);
             let args_ = event_args(ev, &mut *args);
-            ((*lively).cb)(pen_, &args_);
+            let hooks = (*lively).hooks.clone();
+            with_borrowed_pen_hooks(pen, Some(hooks), |pen_| ((*lively).cb)(pen_, &args_));
         }
     }
 }
 
 impl TickitPen
 {
-    pub fn x_bind_event_forever(&mut self, ev: c::TickitEventType, cb: fn(&mut TickitPen, &TickitEvent))
+    pub fn bind(&mut self, ev: c::TickitEventType, cb: PenEventFn) -> PenEventGuard
     {
+        let binding = Rc::new(RefCell::new(PenBinding{pen: self.pen, hooks: self.get_hooks(), id: 0, cb: Some(cb)}));
         unsafe
         {
-            let fun = Some(pen_hacky_forever_bind_function);
-            let data: *mut c_void = std::mem::transmute(cb);
-            c::tickit_pen_bind_event(self.pen, ev, fun, data);
+            let fun = Some(pen_binding_callback);
+            let data: *mut c_void = std::mem::transmute(box binding.clone());
+            let ev = ev | c::TICKIT_EV_UNBIND;
+            let id = self.bind_c(ev, fun, data);
+            binding.borrow_mut().id = id;
         }
+        PenEventGuard{binding: binding}
+    }
+
+    pub fn x_bind_event_forever(&mut self, ev: c::TickitEventType, cb: fn(&mut TickitPen, &TickitEvent))
+    {
+        self.bind(ev, box move |&mut: pen: &mut TickitPen, args: &TickitEvent| cb(pen, args)).forget();
     }
 
     pub fn bind_event_lively<'a>(&mut self, ev: c::TickitEventType, cb: |&mut TickitPen, &TickitEvent|: 'a) -> LivelyPenEvent<'a>
@@ -436,10 +1146,10 @@ impl TickitPen
         unsafe
         {
             let fun = Some(pen_lively_callback);
-            let mut data = box LivelyPenData::<'a>{pen: self as *mut _, cb: cb};
+            let mut data = box LivelyPenData::<'a>{pen: self.pen, hooks: self.get_hooks(), cb: cb};
             let raw_data: *mut c_void = &mut *data as *mut _ as *mut c_void;
             let ev = ev | c::TICKIT_EV_UNBIND;
-            let id = c::tickit_pen_bind_event(self.pen, ev, fun, raw_data);
+            let id = self.bind_c(ev, fun, raw_data);
             LivelyPenEvent{id: id, data: data}
         }
     }
@@ -621,28 +1331,78 @@ impl TickitRectSet
 pub struct TickitTerm
 {
     tt: *mut c::TickitTerm,
+    x: Box<TermState>,
+}
+
+// What the binding keeps for a term beyond libtickit's. It is boxed so that
+// callbacks, which only get the C term, can be handed a TickitTerm sharing
+// it; see with_borrowed_term.
+struct TermState
+{
     output_hook: *mut c_void, // really LivelyTermOutData<'?>
-    output_box: Option<Box<TermOutputDataWrapper>>
+    output_box: Option<Box<TermOutputDataWrapper>>,
+    // exact fg and bg last sent to the terminal, see send_rgb
+    rgb_out: [Option<TickitPenRGB>, ..2],
+    colour_map: Option<Vec<int>>,
+    // what libtickit last sent, and the attributes it can't hold that we
+    // sent after it; see send_ext
+    ext_underline: bool,
+    c_out: Option<TickitPen>,
+    ext_out: TickitPen,
+    // set when the driver is a Rust one, which can take pen attributes
+    // that libtickit can't
+    driver: Option<drv::DriverHandle>,
+    // input libtickit doesn't understand, and the bindings for the events
+    // made from it; see fire_x
+    input: input::InputFilter,
+    x_hooks: Vec<Rc<RefCell<XTermHook>>>,
+}
+
+impl TermState
+{
+    fn new() -> TermState
+    {
+        TermState
+        {
+            output_hook: std::ptr::null_mut(),
+            output_box: None,
+            rgb_out: [None, None],
+            colour_map: None,
+            ext_underline: false,
+            c_out: None,
+            ext_out: TickitPen::new(),
+            driver: None,
+            input: input::InputFilter::new(),
+            x_hooks: Vec::new(),
+        }
+    }
 }
 
 impl TickitTerm
 {
-    pub fn new() -> Result<TickitTerm, c_int>
+    // Take ownership of a term from libtickit.
+    fn from_c(tt: *mut c::TickitTerm) -> TickitTerm
+    {
+        TickitTerm{tt: tt, x: box TermState::new()}
+    }
+
+    pub fn new() -> Result<TickitTerm, TickitError>
     {
         unsafe
         {
             let tt = c::tickit_term_new();
             if tt.is_not_null()
             {
-                Ok(TickitTerm{tt: tt, output_hook: std::ptr::null_mut(), output_box: None})
+                Ok(TickitTerm::from_c(tt))
             }
             else
             {
-                Err(std::os::errno() as c_int)
+                let termtype = std::os::getenv("TERM").unwrap_or_else(|| String::new());
+                Err(TickitError::from_termtype_errno(termtype.as_slice()))
             }
         }
     }
-    pub fn new_for_termtype(name: &str) -> Result<TickitTerm, c_int>
+    pub fn new_for_termtype(name: &str) -> Result<TickitTerm, TickitError>
     {
         unsafe
         {
@@ -651,11 +1411,11 @@ impl TickitTerm
             });
             if tt.is_not_null()
             {
-                Ok(TickitTerm{tt: tt, output_hook: std::ptr::null_mut(), output_box: None})
+                Ok(TickitTerm::from_c(tt))
             }
             else
             {
-                Err(std::os::errno() as c_int)
+                Err(TickitError::from_termtype_errno(name))
             }
         }
     }
@@ -665,13 +1425,23 @@ impl Drop for TickitTerm
 {
     fn drop(&mut self)
     {
+        if self.x.input.paste_on
+        {
+            let _ = self.set_bracketed_paste(false);
+        }
+        if self.x.input.focus_on
+        {
+            let _ = self.set_focus_reports(false);
+        }
+        // libtickit turns the mouse mode off itself
+        self.set_sgr_mouse(false);
         unsafe
         {
             c::tickit_term_destroy(self.tt);
-            let x = self.output_hook as *mut LivelyTermOutData<'static>;
-            if x.is_not_null()
+            let hook = self.x.output_hook as *mut LivelyTermOutData<'static>;
+            if hook.is_not_null()
             {
-                (*x).tt = std::ptr::null_mut();
+                (*hook).tt = std::ptr::null_mut();
             }
         }
     }
@@ -703,21 +1473,26 @@ impl TickitTerm
     }
 }
 
-extern fn term_hacky_forever_output_function(term: *mut c::TickitTerm, bytes: *const c_char, len: size_t, data: *mut c_void)
+// A TickitTerm that doesn't own its pointer, for passing to callbacks; it
+// shares the Rust-side state of the TickitTerm that made the binding.
+unsafe fn with_borrowed_term<R>(tt: *mut c::TickitTerm, x: *mut TermState, f: |&mut TickitTerm| -> R) -> R
 {
-    unsafe
-    {
-        let termp: &mut (_, *mut c_void, *mut c_void) = &mut (term, std::ptr::null_mut(), std::ptr::null_mut());
-        let term_: &mut TickitTerm = std::mem::transmute(termp);
-        let cb: fn(&mut TickitTerm, &[u8]) = std::mem::transmute(data);
-        let bytes: *const u8 = std::mem::transmute(bytes);
-        std::slice::raw::buf_as_slice(bytes, len as uint, |arr| { cb(term_, arr) });
-    }
+    let mut term_ = TickitTerm{tt: tt, x: std::mem::transmute(x)};
+    let ret = f(&mut term_);
+    std::mem::forget(term_);
+    ret
+}
+
+fn term_forever_output_callback(term: &mut TickitTerm, bytes: &[u8], cb: &mut fn(&mut TickitTerm, &[u8]))
+{
+    (*cb)(term, bytes)
 }
 
 struct LivelyTermOutData<'a>
 {
-    tt: *mut TickitTerm,
+    // null once the term is destroyed
+    tt: *mut c::TickitTerm,
+    x: *mut TermState,
     cb: |&mut TickitTerm, &[u8]|: 'a,
 }
 
@@ -736,8 +1511,8 @@ impl<'a> Drop for LivelyTermOutEvent<'a>
         {
             unsafe
             {
-                c::tickit_term_set_output_func((*self.data.tt).tt, None, std::ptr::null_mut());
-                (*self.data.tt).output_hook = std::ptr::null_mut();
+                c::tickit_term_set_output_func(self.data.tt, None, std::ptr::null_mut());
+                (*self.data.x).output_hook = std::ptr::null_mut();
             }
         }
     }
@@ -749,10 +1524,8 @@ extern fn term_out_lively_callback(term: *mut c::TickitTerm, bytes: *const c_cha
     {
         let lively: *mut LivelyTermOutData = std::mem::transmute(data);
         {
-            let termp: &mut (_, *mut c_void, *mut c_void) = &mut (term, std::ptr::null_mut(), std::ptr::null_mut());
-            let term_: &mut TickitTerm = std::mem::transmute(termp);
             let bytes: *const u8 = std::mem::transmute(bytes);
-            std::slice::raw::buf_as_slice(bytes, len as uint, |arr| { ((*lively).cb)(term_, arr) });
+            with_borrowed_term(term, (*lively).x, |term_| std::slice::raw::buf_as_slice(bytes, len as uint, |arr| { ((*lively).cb)(term_, arr) }));
         }
     }
 }
@@ -764,23 +1537,22 @@ struct TermOutputData<T>
 }
 
 // https://github.com/rust-lang/rust/issues/15513
-/* extern */ fn term_output_callback<T>(term: *mut c::TickitTerm, bytes: *const c_char, len: size_t, data: *mut c_void)
+/* extern */ fn term_output_callback<T>(term: *mut c::TickitTerm, x: *mut TermState, bytes: *const c_char, len: size_t, data: *mut c_void)
 {
     unsafe
     {
-        let termp: &mut (_, *mut c_void, *mut c_void) = &mut (term, std::ptr::null_mut(), std::ptr::null_mut());
-        let term_: &mut TickitTerm = std::mem::transmute(termp);
         let bytes: *const u8 = std::mem::transmute(bytes);
         let data: &mut TermOutputData<T> = std::mem::transmute(data);
-        std::slice::raw::buf_as_slice(bytes, len as uint, |arr| { (data.cb)(term_, arr, &mut data.data) });
+        with_borrowed_term(term, x, |term_| std::slice::raw::buf_as_slice(bytes, len as uint, |arr| { (data.cb)(term_, arr, &mut data.data) }));
     }
 }
 
 struct TermOutputDataWrapper
 {
-    rust_cb: Option<fn(*mut c::TickitTerm, *const c_char, size_t, *mut c_void)>,
+    rust_cb: Option<fn(*mut c::TickitTerm, *mut TermState, *const c_char, size_t, *mut c_void)>,
     rust_drop: Option<fn(*mut c_void)>,
     rust_data: *mut c_void,
+    term: *mut TermState,
 }
 
 impl Drop for TermOutputDataWrapper
@@ -796,7 +1568,7 @@ extern fn term_output_wrapper(term: *mut c::TickitTerm, bytes: *const c_char, le
     unsafe
     {
         let data = data as *mut TermOutputDataWrapper;
-        ((*data).rust_cb.unwrap())(term, bytes, len, (*data).rust_data);
+        ((*data).rust_cb.unwrap())(term, (*data).term, bytes, len, (*data).rust_data);
     }
 }
 
@@ -812,12 +1584,7 @@ impl TickitTerm
 {
     pub fn x_set_output_func_forever(&mut self, cb: fn(&mut TickitTerm, bytes: &[u8]))
     {
-        unsafe
-        {
-            let fun = Some(term_hacky_forever_output_function);
-            let data: *mut c_void = std::mem::transmute(cb);
-            c::tickit_term_set_output_func(self.tt, fun, data);
-        }
+        self.set_output_func(term_forever_output_callback, cb);
     }
 
     pub fn set_output_lively<'a>(&mut self, cb: |&mut TickitTerm, &[u8]|: 'a) -> LivelyTermOutEvent<'a>
@@ -829,10 +1596,10 @@ impl TickitTerm
         unsafe
         {
             let fun = Some(term_out_lively_callback);
-            let mut data = box LivelyTermOutData::<'a>{tt: self, cb: cb};
+            let mut data = box LivelyTermOutData::<'a>{tt: self.tt, x: &mut *self.x, cb: cb};
             let raw_data: *mut c_void = &mut *data as *mut _ as *mut c_void;
             c::tickit_term_set_output_func(self.tt, fun, raw_data);
-            self.output_hook = raw_data;
+            self.x.output_hook = raw_data;
             LivelyTermOutEvent{data: data}
         }
     }
@@ -844,8 +1611,9 @@ impl TickitTerm
             let fun = Some(term_output_callback::<T>);
             let raw_data: *mut c_void = std::mem::transmute(box TermOutputData::<T>{cb: cb, data: data});
             let wrap_fun = Some(term_output_wrapper);
-            self.output_box = Some(box TermOutputDataWrapper{rust_cb: fun, rust_drop: Some(term_output_drop::<T>), rust_data: raw_data});
-            let wrap_data: &TermOutputDataWrapper = &**self.output_box.as_ref().unwrap();
+            let x: *mut TermState = &mut *self.x;
+            self.x.output_box = Some(box TermOutputDataWrapper{rust_cb: fun, rust_drop: Some(term_output_drop::<T>), rust_data: raw_data, term: x});
+            let wrap_data: &TermOutputDataWrapper = &**self.x.output_box.as_ref().unwrap();
             let wrap_data = wrap_data as *const _ as *mut c_void;
             c::tickit_term_set_output_func(self.tt, wrap_fun, wrap_data);
         }
@@ -908,7 +1676,9 @@ impl TickitTerm
         }
     }
 
-    pub fn input_push_bytes(&mut self, bytes: &[u8])
+    // Input reaches libtickit only through here, once the input filter
+    // has taken out what it doesn't understand.
+    fn push_c_bytes(&mut self, bytes: &[u8])
     {
         unsafe
         {
@@ -916,33 +1686,63 @@ impl TickitTerm
             c::tickit_term_input_push_bytes(self.tt, b.as_ptr(), b.len() as size_t);
         }
     }
+
+    pub fn input_push_bytes(&mut self, bytes: &[u8])
+    {
+        let items = self.x.input.feed(bytes, time::precise_time_ns());
+        self.input_deliver(items);
+    }
+    fn input_deliver(&mut self, items: Vec<input::InputItem>)
+    {
+        for item in items.into_iter()
+        {
+            match item
+            {
+                input::InputBytes(bytes) => self.push_c_bytes(bytes.as_slice()),
+                input::InputPaste(text) => self.fire_paste(text.as_slice()),
+                input::InputFocus(focused) => self.fire_focus(focused),
+                input::InputMouse(report) => self.fire_mouse(report),
+            }
+        }
+    }
     pub fn input_readable(&mut self)
     {
-        unsafe
+        let mut buf = [0u8, ..4096];
+        let n = unsafe { libc::read(self.get_input_fd(), buf.as_mut_ptr() as *mut c_void, buf.len() as size_t) };
+        if n > 0
         {
-            c::tickit_term_input_readable(self.tt);
+            self.input_push_bytes(buf.slice_to(n as uint));
         }
     }
     pub fn input_check_timeout(&mut self) -> Option<uint>
     {
-        unsafe
+        let now = time::precise_time_ns();
+        let items = self.x.input.expire(now);
+        self.input_deliver(items);
+        let t = unsafe { c::tickit_term_input_check_timeout(self.tt) };
+        let t = if t != -1 { Some(t as uint) } else { None };
+        match (t, self.x.input.timeout(now))
         {
-            let t = c::tickit_term_input_check_timeout(self.tt);
-            if t != -1
-            {
-                Some(t as uint)
-            }
-            else
-            {
-                None
-            }
+            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+            (a, b) => a.or(b),
         }
     }
     pub fn input_wait(&mut self, timeout: Option<timeval>)
     {
-        unsafe
+        let mut ms = timeout.map_or(-1, |t| (t.tv_sec * 1000 + t.tv_usec / 1000) as c_int);
+        match self.input_check_timeout()
+        {
+            Some(t) if ms < 0 || (t as c_int) < ms => ms = t as c_int,
+            _ => {}
+        }
+        let mut pfd = libc::pollfd{fd: self.get_input_fd(), events: libc::POLLIN, revents: 0};
+        if unsafe { libc::poll(&mut pfd, 1, ms) } > 0
         {
-            c::tickit_term_input_wait(self.tt, match timeout { Some(ref x) => x as *const _, None => std::ptr::null() });
+            self.input_readable();
+        }
+        else
+        {
+            self.input_check_timeout();
         }
     }
 
@@ -972,25 +1772,96 @@ impl TickitTerm
     }
 }
 
-extern fn term_hacky_forever_bind_function(term: *mut c::TickitTerm, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+pub type TermEventFn = Box<FnMut(&mut TickitTerm, &TickitEvent) + 'static>;
+
+// Shared between the guard and libtickit, so that either may go first.
+struct TermBinding
+{
+    // null once unbound, either by the guard or by the term being destroyed
+    tt: *mut c::TickitTerm,
+    x: *mut TermState,
+    id: c_int,
+    cb: Option<TermEventFn>,
+}
+
+/// Keeps a closure bound to a term event; unbinds it when dropped.
+#[must_use]
+pub struct TermEventGuard
+{
+    binding: Rc<RefCell<TermBinding>>,
+}
+
+impl TermEventGuard
+{
+    pub fn is_bound(&self) -> bool
+    {
+        self.binding.borrow().tt.is_not_null()
+    }
+    /// Leave the closure bound until the term is destroyed.
+    pub fn forget(self)
+    {
+        unsafe
+        {
+            std::mem::forget(self);
+        }
+    }
+}
+
+impl Drop for TermEventGuard
 {
-    if ev == c::TICKIT_EV_UNBIND
+    fn drop(&mut self)
     {
-        return;
+        let (tt, id) = { let b = self.binding.borrow(); (b.tt, b.id) };
+        if tt.is_not_null()
+        {
+            unsafe
+            {
+                c::tickit_term_unbind_event_id(tt, id);
+            }
+        }
     }
+}
+
+extern fn term_binding_callback(term: *mut c::TickitTerm, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+{
     unsafe
     {
-        let termp: &mut (_, *mut c_void, *mut c_void) = &mut (term, std::ptr::null_mut(), std::ptr::null_mut());
-        let term_: &mut TickitTerm = std::mem::transmute(termp);
-        let args_ = event_args(ev, &mut *args);
-        let cb: fn(&mut TickitTerm, &TickitEvent) = std::mem::transmute(data);
-        cb(term_, &args_);
+        let slot = data as *mut Rc<RefCell<TermBinding>>;
+        if ev == c::TICKIT_EV_UNBIND
+        {
+            let slot: Box<Rc<RefCell<TermBinding>>> = std::mem::transmute(slot);
+            let mut b = slot.borrow_mut();
+            b.tt = std::ptr::null_mut();
+            b.cb = None;
+            return;
+        }
+
+        // keep the binding alive even if the callback drops its guard
+        let binding = (*slot).clone();
+        let cb = binding.borrow_mut().cb.take();
+        match cb
+        {
+            Some(mut cb) =>
+            {
+                let args_ = event_args(ev, &mut *args);
+                let x = binding.borrow().x;
+                with_borrowed_term(term, x, |term_| cb.call_mut((term_, &args_)));
+                let mut b = binding.borrow_mut();
+                if b.tt.is_not_null()
+                {
+                    b.cb = Some(cb);
+                }
+            }
+            None => {}
+        }
     }
 }
 
 struct LivelyTermData<'a>
 {
-    term: *mut TickitTerm,
+    // null once unbound
+    tt: *mut c::TickitTerm,
+    x: *mut TermState,
     cb: |&mut TickitTerm, &TickitEvent|: 'a,
 }
 
@@ -1006,11 +1877,11 @@ impl<'a> Drop for LivelyTermEvent<'a>
 {
     fn drop(&mut self)
     {
-        if self.data.term.is_not_null()
+        if self.data.tt.is_not_null()
         {
             unsafe
             {
-                c::tickit_term_unbind_event_id((*self.data.term).tt, self.id);
+                c::tickit_term_unbind_event_id(self.data.tt, self.id);
             }
         }
     }
@@ -1023,14 +1894,12 @@ extern fn term_lively_callback(term: *mut c::TickitTerm, ev: c::TickitEventType,
         let lively: *mut LivelyTermData = std::mem::transmute(data);
         if ev == c::TICKIT_EV_UNBIND
         {
-            (*lively).term = std::ptr::null_mut();
+            (*lively).tt = std::ptr::null_mut();
         }
         else
         {
-            let termp: &mut (_, *mut c_void, *mut c_void) = &mut (term, std::ptr::null_mut(), std::ptr::null_mut());
-            let term_: &mut TickitTerm = std::mem::transmute(termp);
             let args_ = event_args(ev, &mut *args);
-            ((*lively).cb)(term_, &args_);
+            with_borrowed_term(term, (*lively).x, |term_| ((*lively).cb)(term_, &args_));
         }
     }
 }
@@ -1042,7 +1911,7 @@ struct SplitTermData<T>
 }
 
 // https://github.com/rust-lang/rust/issues/15513
-/* extern */ fn term_split_callback<T>(term: *mut c::TickitTerm, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+/* extern */ fn term_split_callback<T>(term: *mut c::TickitTerm, x: *mut TermState, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
 -> bool
 {
     unsafe
@@ -1054,11 +1923,9 @@ struct SplitTermData<T>
         }
         else
         {
-            let termp: &mut (_, *mut c_void, *mut c_void) = &mut (term, std::ptr::null_mut(), std::ptr::null_mut());
-            let term_: &mut TickitTerm = std::mem::transmute(termp);
             let args_ = event_args(ev, &mut *args);
             let data: &mut SplitTermData<T> = std::mem::transmute(data);
-            (data.cb)(term_, &args_, &mut data.data);
+            with_borrowed_term(term, x, |term_| (data.cb)(term_, &args_, &mut data.data));
             false
         }
     }
@@ -1066,9 +1933,10 @@ struct SplitTermData<T>
 
 struct SplitTermDataWrapper
 {
-    rust_cb: Option<fn(*mut c::TickitTerm, c::TickitEventType, *mut c::TickitEvent, *mut c_void)
+    rust_cb: Option<fn(*mut c::TickitTerm, *mut TermState, c::TickitEventType, *mut c::TickitEvent, *mut c_void)
                  -> bool>,
     rust_data: *mut c_void,
+    term: *mut TermState,
 }
 
 extern fn term_split_callback_wrapper(term: *mut c::TickitTerm, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
@@ -1076,7 +1944,7 @@ extern fn term_split_callback_wrapper(term: *mut c::TickitTerm, ev: c::TickitEve
     unsafe
     {
         let data = data as *mut SplitTermDataWrapper;
-        let del = ((*data).rust_cb.unwrap())(term, ev, args, (*data).rust_data);
+        let del = ((*data).rust_cb.unwrap())(term, (*data).term, ev, args, (*data).rust_data);
         if del
         {
             let _: Box<SplitTermDataWrapper> = std::mem::transmute(data);
@@ -1094,105 +1962,541 @@ pub struct CancellableTermEvent
     nocopy: std::kinds::marker::NoCopy,
 }
 
+// The events TickitTerm raises itself, as libtickit has no bits for them,
+// or (for SGR mouse reports) reads them itself.
+fn x_term_events() -> c::TickitEventType
+{
+    c::X_TICKIT_EV_PASTE | c::X_TICKIT_EV_FOCUS | c::TICKIT_EV_MOUSE
+}
+
+// A binding that takes events libtickit doesn't know. libtickit still owns
+// it, calling x_hook_callback, so that unbinding works as for any other;
+// the term's list of them is how fire_x finds it.
+struct XTermHook
+{
+    ev: c::TickitEventType,
+    fun: c::TickitTermEventFn,
+    data: *mut c_void,
+    bound: bool,
+}
+
+fn x_event_args() -> c::TickitEvent
+{
+    c::TickitEvent
+    {
+        lines: 0, cols: 0,
+        type_: 0,
+        str_: std::ptr::null(),
+        button: 0,
+        line: 0, col: 0,
+        mod_: X_Tickit_Mod::empty(),
+    }
+}
+
+extern fn x_hook_callback(term: *mut c::TickitTerm, ev: c::TickitEventType, args: *mut c::TickitEvent, data: *mut c_void)
+{
+    unsafe
+    {
+        let slot = data as *mut Rc<RefCell<XTermHook>>;
+        let (hook_ev, fun, hook_data) = { let h = (*slot).borrow(); (h.ev, h.fun, h.data) };
+        // libtickit only passes on UNBIND to those that asked for it
+        if ev != c::TICKIT_EV_UNBIND || hook_ev.contains(c::TICKIT_EV_UNBIND)
+        {
+            (fun.unwrap())(term, ev, args, hook_data);
+        }
+        if ev == c::TICKIT_EV_UNBIND
+        {
+            let slot: Box<Rc<RefCell<XTermHook>>> = std::mem::transmute(slot);
+            slot.borrow_mut().bound = false;
+        }
+    }
+}
+
+impl TickitTerm
+{
+    // Every binding goes through here, so that those for x_term_events can
+    // be found by fire_x.
+    unsafe fn bind_c(&mut self, ev: c::TickitEventType, fun: c::TickitTermEventFn, data: *mut c_void) -> c_int
+    {
+        if !ev.intersects(x_term_events())
+        {
+            return c::tickit_term_bind_event(self.tt, ev, fun, data);
+        }
+        let hook = Rc::new(RefCell::new(XTermHook{ev: ev, fun: fun, data: data, bound: true}));
+        self.x.x_hooks.push(hook.clone());
+        let slot: *mut c_void = std::mem::transmute(box hook);
+        c::tickit_term_bind_event(self.tt, ev | c::TICKIT_EV_UNBIND, Some(x_hook_callback), slot)
+    }
+
+    // Raise one of x_term_events, as libtickit would one of its own.
+    fn fire_x(&mut self, ev: c::TickitEventType, args: &mut c::TickitEvent)
+    {
+        self.x.x_hooks.retain(|h| h.borrow().bound);
+        let hooks = self.x.x_hooks.clone();
+        for hook in hooks.iter()
+        {
+            let (hook_ev, fun, data, bound) = { let h = hook.borrow(); (h.ev, h.fun, h.data, h.bound) };
+            // an earlier callback may have unbound it
+            if bound && hook_ev.intersects(ev)
+            {
+                (fun.unwrap())(self.tt, ev, args, data);
+            }
+        }
+    }
+
+    fn fire_paste(&mut self, text: &str)
+    {
+        let text = text.to_c_str();
+        let mut args = x_event_args();
+        args.str_ = text.as_ptr();
+        self.fire_x(c::X_TICKIT_EV_PASTE, &mut args);
+    }
+
+    fn fire_focus(&mut self, focused: bool)
+    {
+        let mut args = x_event_args();
+        args.type_ = focused as c_int;
+        self.fire_x(c::X_TICKIT_EV_FOCUS, &mut args);
+    }
+
+    fn fire_mouse(&mut self, report: input::MouseReport)
+    {
+        let mut args = x_event_args();
+        args.type_ = report.type_ as c_int;
+        args.button = report.button as c_int;
+        args.line = report.line as c_int;
+        args.col = report.col as c_int;
+        args.mod_ = report.mod_;
+        self.fire_x(c::TICKIT_EV_MOUSE, &mut args);
+    }
+}
+
 impl TickitTerm
 {
+    pub fn bind(&mut self, ev: c::TickitEventType, cb: TermEventFn) -> TermEventGuard
+    {
+        let binding = Rc::new(RefCell::new(TermBinding{tt: self.tt, x: &mut *self.x, id: 0, cb: Some(cb)}));
+        unsafe
+        {
+            let fun = Some(term_binding_callback);
+            let data: *mut c_void = std::mem::transmute(box binding.clone());
+            let ev = ev | c::TICKIT_EV_UNBIND;
+            let id = self.bind_c(ev, fun, data);
+            binding.borrow_mut().id = id;
+        }
+        TermEventGuard{binding: binding}
+    }
+
     pub fn x_bind_event_forever(&mut self, ev: c::TickitEventType, cb: fn(&mut TickitTerm, &TickitEvent)) -> int
     {
-        unsafe
+        let guard = self.bind(ev, box move |&mut: term: &mut TickitTerm, args: &TickitEvent| cb(term, args));
+        let id = guard.binding.borrow().id;
+        guard.forget();
+        id as int
+    }
+
+    pub fn bind_event_lively<'a>(&mut self, ev: c::TickitEventType, cb: |&mut TickitTerm, &TickitEvent|: 'a) -> LivelyTermEvent<'a>
+    {
+        // why aren't we taking the lifetime of 'self' ?
+        // First, because that would prevent anybody else from using it
+        // Second, because we don't need it - we get an "unregister" event.
+        unsafe
+        {
+            let fun = Some(term_lively_callback);
+            let mut data = box LivelyTermData::<'a>{tt: self.tt, x: &mut *self.x, cb: cb};
+            let raw_data: *mut c_void = &mut *data as *mut _ as *mut c_void;
+            let ev = ev | c::TICKIT_EV_UNBIND;
+            let id = self.bind_c(ev, fun, raw_data);
+            LivelyTermEvent{id: id, data: data}
+        }
+    }
+
+    pub fn bind_event<T>(&mut self, ev: c::TickitEventType, cb: fn(&mut TickitTerm, &TickitEvent, &mut T), data: T) -> CancellableTermEvent
+    {
+        unsafe
+        {
+            let fun = Some(term_split_callback::<T>);
+            let raw_data: *mut c_void = std::mem::transmute(box SplitTermData::<T>{cb: cb, data: data});
+            let ev = ev | c::TICKIT_EV_UNBIND;
+            let wrap_fun = Some(term_split_callback_wrapper);
+            let wrap_data: *mut c_void = std::mem::transmute(box SplitTermDataWrapper{rust_cb: fun, rust_data: raw_data, term: &mut *self.x});
+            let id = self.bind_c(ev, wrap_fun, wrap_data);
+            CancellableTermEvent{tt: self.tt, id: id, nocopy: std::kinds::marker::NoCopy}
+        }
+    }
+
+    pub fn unbind_event_id(&mut self, can: CancellableTermEvent)
+    {
+        assert!(self.tt == can.tt);
+        unsafe
+        {
+            c::tickit_term_unbind_event_id(can.tt, can.id);
+        }
+    }
+}
+
+impl TickitTerm
+{
+    pub fn print(&mut self, str_: &str)
+    {
+        unsafe
+        {
+            let s: &[c_char] = std::mem::transmute(str_.as_bytes());
+            c::tickit_term_printn(self.tt, s.as_ptr(), s.len() as size_t);
+        }
+    }
+    pub fn goto(&mut self, line: int, col: int) -> bool
+    {
+        unsafe
+        {
+            c::tickit_term_goto(self.tt, line as c_int, col as c_int) != 0
+        }
+    }
+    pub fn move_(&mut self, downward: int, rightward: int)
+    {
+        unsafe
+        {
+            c::tickit_term_move(self.tt, downward as c_int, rightward as c_int);
+        }
+    }
+    pub fn scrollrect(&mut self, rect: TickitRect, downward: int, rightward: int) -> bool
+    {
+        unsafe
+        {
+            c::tickit_term_scrollrect(self.tt, rect.top as c_int, rect.left as c_int, rect.lines as c_int, rect.cols as c_int, downward as c_int, rightward as c_int) != 0
+        }
+    }
+
+    pub fn chpen(&mut self, pen: &TickitPen)
+    {
+        self.change_pen(pen, false);
+    }
+    pub fn setpen(&mut self, pen: &TickitPen)
+    {
+        self.change_pen(pen, true);
+    }
+
+    fn change_pen(&mut self, pen: &TickitPen, is_set: bool)
+    {
+        let has_colour = pen.has_attr(c::TICKIT_PEN_FG) || pen.has_attr(c::TICKIT_PEN_BG);
+        if !has_colour && self.x.rgb_out.iter().all(|c| c.is_none())
+        {
+            self.send_pen(pen, is_set);
+        }
+        else
+        {
+            let colors = self.palette_size();
+            let palette = self.palette_pen(pen, colors);
+            self.send_pen(&palette, is_set);
+            if !self.send_ext(pen, is_set)
+            {
+                self.send_rgb(pen, &palette, is_set, colors);
+            }
+            return;
+        }
+        self.send_ext(pen, is_set);
+    }
+
+    fn send_pen(&mut self, pen: &TickitPen, is_set: bool)
+    {
+        unsafe
+        {
+            if is_set
+            {
+                c::tickit_term_setpen(self.tt, const_(pen.pen));
+            }
+            else
+            {
+                c::tickit_term_chpen(self.tt, const_(pen.pen));
+            }
+        }
+    }
+
+    fn palette_size(&mut self) -> int
+    {
+        self.get_colors().map(|v| v as int).unwrap_or(256)
+    }
+
+    /// Use `map[i]` in place of palette index `i`, for every `i` the table
+    /// covers, rather than the nearest colour the terminal advertises.
+    pub fn set_colour_map(&mut self, map: Option<Vec<int>>)
+    {
+        self.x.colour_map = map;
+    }
+
+    /// The palette index sent for `colour` to a terminal with `colors`
+    /// colours; RGB colours are only sent as such if it has 2**24.
+    pub fn palette_index(&self, colour: TickitPenColour, colors: int) -> int
+    {
+        let idx = match colour
+        {
+            IndexColour(idx) => idx,
+            RGBColour(rgb) if self.x.colour_map.is_none() => return rgb.nearest_index(colors),
+            RGBColour(rgb) => rgb.nearest_index(256),
+        };
+        match self.x.colour_map
+        {
+            Some(ref map) if idx >= 0 && (idx as uint) < map.len() => return map[idx as uint],
+            _ => {}
+        }
+        if idx < colors || idx < 0 || idx >= 256
+        {
+            idx
+        }
+        else
+        {
+            TickitPenRGB::from_index(idx).nearest_index(colors)
+        }
+    }
+
+    // Whether pens need remapping to fit the terminal's palette before
+    // libtickit is allowed to send them.
+    fn needs_palette(&mut self) -> bool
+    {
+        self.x.colour_map.is_some() || self.palette_size() < 256
+    }
+
+    // Whether the terminal has been left in RGB colours or attributes that
+    // libtickit doesn't know of, and so wouldn't undo.
+    fn has_ext_out(&self) -> bool
+    {
+        let ext = &self.x.ext_out;
+        let under = match ext.get_under_style()
         {
-            let fun = Some(term_hacky_forever_bind_function);
-            let data: *mut c_void = std::mem::transmute(cb);
-            c::tickit_term_bind_event(self.tt, ev, fun, data) as int
-        }
+            NoUnderline | SingleUnderline => ext.get_under_colour().is_some(),
+            _ => true,
+        };
+        self.x.rgb_out.iter().any(|c| c.is_some())
+            || ext.get_rgb_attr(c::TICKIT_PEN_FG).is_some() || ext.get_rgb_attr(c::TICKIT_PEN_BG).is_some()
+            || c::X_TICKIT_PEN_ATTRS.iter().any(|&attr| ext.get_bool_attr(attr))
+            || under
     }
 
-    pub fn bind_event_lively<'a>(&mut self, ev: c::TickitEventType, cb: |&mut TickitTerm, &TickitEvent|: 'a) -> LivelyTermEvent<'a>
+    // 'pen' with its colours replaced by what the terminal's palette can
+    // show, which is all libtickit knows how to send.
+    fn palette_pen(&self, pen: &TickitPen, colors: int) -> TickitPen
     {
-        // why aren't we taking the lifetime of 'self' ?
-        // First, because that would prevent anybody else from using it
-        // Second, because we don't need it - we get an "unregister" event.
-        unsafe
+        let mut out = pen.clone();
+        for &attr in [c::TICKIT_PEN_FG, c::TICKIT_PEN_BG].iter()
         {
-            let fun = Some(term_lively_callback);
-            let mut data = box LivelyTermData::<'a>{term: self as *mut _, cb: cb};
-            let raw_data: *mut c_void = &mut *data as *mut _ as *mut c_void;
-            let ev = ev | c::TICKIT_EV_UNBIND;
-            let id = c::tickit_term_bind_event(self.tt, ev, fun, raw_data);
-            LivelyTermEvent{id: id, data: data}
+            match pen.get_colour(attr)
+            {
+                Some(colour) => unsafe
+                {
+                    c::tickit_pen_set_colour_attr(out.pen, attr, self.palette_index(colour, colors) as c_int);
+                },
+                None => {}
+            }
         }
+        out
     }
 
-    pub fn bind_event<T>(&mut self, ev: c::TickitEventType, cb: fn(&mut TickitTerm, &TickitEvent, &mut T), data: T) -> CancellableTermEvent
+    // libtickit has just sent 'palette' for 'pen'; on a terminal that takes
+    // RGB, follow it with the exact colours. Terminals that don't
+    // understand 38;2 are still left with a sensible colour.
+    fn send_rgb(&mut self, pen: &TickitPen, palette: &TickitPen, is_set: bool, colors: int)
     {
-        unsafe
+        for (slot, &attr) in [c::TICKIT_PEN_FG, c::TICKIT_PEN_BG].iter().enumerate()
         {
-            let fun = Some(term_split_callback::<T>);
-            let raw_data: *mut c_void = std::mem::transmute(box SplitTermData::<T>{cb: cb, data: data});
-            let ev = ev | c::TICKIT_EV_UNBIND;
-            let wrap_fun = Some(term_split_callback_wrapper);
-            let wrap_data: *mut c_void = std::mem::transmute(box SplitTermDataWrapper{rust_cb: fun, rust_data: raw_data});
-            let id = c::tickit_term_bind_event(self.tt, ev, wrap_fun, wrap_data);
-            CancellableTermEvent{tt: self.tt, id: id, nocopy: std::kinds::marker::NoCopy}
+            if !is_set && !pen.has_attr(attr)
+            {
+                continue;
+            }
+            let sgr = if slot == 0 { 38 } else { 48 };
+            let old = self.x.rgb_out[slot];
+            let new = if colors >= TRUECOLOR_COLORS { pen.get_rgb_attr(attr) } else { None };
+            match (old, new)
+            {
+                (_, Some(rgb)) if old != new =>
+                {
+                    self.write_sgr(format!("{};2;{};{};{}", sgr, rgb.r, rgb.g, rgb.b).as_slice());
+                }
+                (Some(old), None) =>
+                {
+                    // libtickit stays quiet if the palette colour is unchanged
+                    let old_idx = self.palette_index(RGBColour(old), colors);
+                    match palette.maybe_get_colour_attr(attr)
+                    {
+                        Some(idx) if idx == old_idx =>
+                        {
+                            self.write_sgr(format!("{};5;{}", sgr, idx).as_slice());
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+            self.x.rgb_out[slot] = new;
         }
     }
 
-    pub fn unbind_event_id(&mut self, can: CancellableTermEvent)
+    /// Whether the terminal understands underline styles (`SGR 4:x`) and
+    /// colours (`SGR 58`). libtickit has no way to tell, so until this is
+    /// set every underline is sent as a plain one in the text's colour.
+    pub fn set_ext_underline(&mut self, on: bool)
     {
-        assert!(self.tt == can.tt);
-        unsafe
-        {
-            c::tickit_term_unbind_event_id(can.tt, can.id);
-        }
+        self.x.ext_underline = on;
     }
-}
 
-impl TickitTerm
-{
-    pub fn print(&mut self, str_: &str)
+    // Follow what libtickit has just sent for 'pen' with the attributes it
+    // can't hold, or let a Rust driver take them, RGB colours included;
+    // returns whether one did.
+    fn send_ext(&mut self, pen: &TickitPen, is_set: bool) -> bool
     {
-        unsafe
+        let (reset, unbold) = self.track_c_pen(pen, is_set);
+        if reset && self.x.driver.is_none()
         {
-            let s: &[c_char] = std::mem::transmute(str_.as_bytes());
-            c::tickit_term_printn(self.tt, s.as_ptr(), s.len() as size_t);
+            // a bare SGR has cleared everything on the terminal; a Rust
+            // driver's chpen keeps what chpen_ext last set
+            self.x.ext_out = TickitPen::new();
         }
-    }
-    pub fn goto(&mut self, line: int, col: int) -> bool
-    {
-        unsafe
+
+        let mut want = self.x.ext_out.clone();
+        if is_set || pen.has_attr(c::TICKIT_PEN_UNDER)
         {
-            c::tickit_term_goto(self.tt, line as c_int, col as c_int) != 0
+            want.set_under_style(pen.get_under_style());
         }
-    }
-    pub fn move_(&mut self, downward: int, rightward: int)
-    {
-        unsafe
+        match pen.get_under_colour()
         {
-            c::tickit_term_move(self.tt, downward as c_int, rightward as c_int);
+            Some(colour) => want.set_under_colour(colour),
+            None if is_set => want.clear_under_colour(),
+            None => {}
+        }
+        for &attr in c::X_TICKIT_PEN_ATTRS.iter()
+        {
+            if is_set || pen.has_attr(attr)
+            {
+                want.set_bool_attr(attr, pen.get_bool_attr(attr));
+            }
+        }
+        if self.x.driver.is_some()
+        {
+            let colors = self.palette_size();
+            for &attr in [c::TICKIT_PEN_FG, c::TICKIT_PEN_BG].iter()
+            {
+                if !is_set && !pen.has_attr(attr)
+                {
+                    continue;
+                }
+                match pen.get_rgb_attr(attr)
+                {
+                    Some(rgb) if colors >= TRUECOLOR_COLORS => want.set_rgb_attr(attr, rgb),
+                    _ => want.clear_attr(attr),
+                }
+            }
+        }
+
+        let handled = match self.x.driver
+        {
+            Some(ref driver) if !want.equiv(&self.x.ext_out) => driver.chpen_ext(&want),
+            Some(_) => true,
+            None => false,
+        };
+        if !handled
+        {
+            let old = self.x.ext_out.clone();
+            let bold = self.x.c_out.as_ref().map_or(false, |p| p.get_bool_attr(c::TICKIT_PEN_BOLD));
+            let params = self.ext_sgr(&old, &want, bold, unbold);
+            if !params.is_empty()
+            {
+                self.write_sgr(params.connect(";").as_slice());
+            }
         }
+        self.x.ext_out = want;
+        handled
     }
-    pub fn scrollrect(&mut self, rect: TickitRect, downward: int, rightward: int) -> bool
+
+    // The SGR parameters that take the terminal from 'old' to 'new', given
+    // whether it is now bold and whether libtickit just sent 22 to unbold.
+    fn ext_sgr(&mut self, old: &TickitPen, new: &TickitPen, bold: bool, unbold: bool) -> Vec<String>
     {
-        unsafe
+        let mut params = Vec::new();
+        for &(attr, on, off) in [(c::X_TICKIT_PEN_BLINK, 5i, 25i), (c::X_TICKIT_PEN_DIM, 2, 22),
+                                 (c::X_TICKIT_PEN_OVERLINE, 53, 55), (c::X_TICKIT_PEN_CONCEAL, 8, 28)].iter()
         {
-            c::tickit_term_scrollrect(self.tt, rect.top as c_int, rect.left as c_int, rect.lines as c_int, rect.cols as c_int, downward as c_int, rightward as c_int) != 0
+            let was = old.get_bool_attr(attr) && !(attr == c::X_TICKIT_PEN_DIM && unbold);
+            let is = new.get_bool_attr(attr);
+            if is && !was
+            {
+                params.push(on.to_string());
+            }
+            else if was && !is
+            {
+                params.push(off.to_string());
+                // 22 is also the end of bold
+                if attr == c::X_TICKIT_PEN_DIM && bold
+                {
+                    params.push("1".to_string());
+                }
+            }
+        }
+        if !self.x.ext_underline
+        {
+            return params;
+        }
+        let style = new.get_under_style();
+        let old_style = old.get_under_style();
+        // 4 and 24 have gone already if the underline came or went
+        let sent = style == NoUnderline || (style == SingleUnderline && old_style == NoUnderline);
+        if style != old_style && !sent
+        {
+            params.push(format!("4:{}", style as int));
+        }
+        let colour = new.get_under_colour();
+        if colour != old.get_under_colour()
+        {
+            let colors = self.palette_size();
+            params.push(match colour
+            {
+                Some(RGBColour(rgb)) if colors >= TRUECOLOR_COLORS => format!("58;2;{};{};{}", rgb.r, rgb.g, rgb.b),
+                Some(IndexColour(idx)) if idx < 0 => "59".to_string(),
+                Some(colour) => format!("58;5;{}", self.palette_index(colour, colors)),
+                None => "59".to_string(),
+            });
         }
+        params
     }
 
-    pub fn chpen(&mut self, pen: &TickitPen)
+    // Follow libtickit's own idea of the terminal's pen through a chpen or
+    // setpen, returning whether it will have sent a bare SGR (clearing
+    // everything) and whether it will have sent 22 (clearing dim too).
+    fn track_c_pen(&mut self, pen: &TickitPen, is_set: bool) -> (bool, bool)
     {
+        let mut new = if is_set { TickitPen::new() } else { self.x.c_out.clone().unwrap_or_else(|| TickitPen::new()) };
         unsafe
         {
-            c::tickit_term_chpen(self.tt, const_(pen.pen));
+            c::tickit_pen_copy(new.pen, const_(pen.pen), 1);
         }
+        let (changed, was_bold) = match self.x.c_out
+        {
+            Some(ref old) => unsafe
+            {
+                (c::tickit_pen_equiv(const_(old.pen), const_(new.pen)) == 0, old.get_bool_attr(c::TICKIT_PEN_BOLD))
+            },
+            None => (true, false),
+        };
+        let reset = changed && unsafe { c::tickit_pen_is_nondefault(const_(new.pen)) == 0 };
+        let unbold = was_bold && !new.get_bool_attr(c::TICKIT_PEN_BOLD);
+        self.x.c_out = Some(new);
+        (reset, unbold)
     }
-    pub fn setpen(&mut self, pen: &TickitPen)
+
+    // Raw output; libtickit's drivers pass printed text straight through,
+    // but a Rust driver would take it for text.
+    fn write_raw(&mut self, str_: &str)
     {
-        unsafe
+        match self.x.driver
         {
-            c::tickit_term_setpen(self.tt, const_(pen.pen));
+            Some(ref driver) => driver.write_str(str_),
+            None => self.print(str_),
         }
     }
 
+    fn write_sgr(&mut self, params: &str)
+    {
+        self.write_raw(format!("\x1b[{}m", params).as_slice());
+    }
+
     pub fn clear(&mut self)
     {
         unsafe
@@ -1213,103 +2517,294 @@ impl TickitTerm
 
 impl TickitTerm
 {
-    pub fn getctl_int(&mut self, ctl: TickitTermCtl) -> Option<int>
+    pub fn getctl_int(&mut self, ctl: TickitTermCtl) -> Result<int, TickitError>
     {
+        match ctl
+        {
+            c::X_TICKIT_TERMCTL_PASTE => return Ok(self.x.input.paste_on as int),
+            c::X_TICKIT_TERMCTL_FOCUS => return Ok(self.x.input.focus_on as int),
+            _ => {}
+        }
         let mut tmp: c_int = unsafe { std::mem::uninitialized() };
         let ok = unsafe { c::tickit_term_getctl_int(self.tt, ctl, &mut tmp) != 0 };
         if ok
         {
-            Some(tmp as int)
+            Ok(tmp as int)
         }
         else
         {
-            None
+            Err(UnsupportedCtlError(ctl))
         }
     }
-    pub fn setctl_int(&mut self, ctl: TickitTermCtl, value: int) -> bool
+    pub fn setctl_int(&mut self, ctl: TickitTermCtl, value: int) -> Result<(), TickitError>
     {
-        unsafe
+        match ctl
         {
-            c::tickit_term_setctl_int(self.tt, ctl, value as c_int) != 0
+            c::X_TICKIT_TERMCTL_PASTE | c::X_TICKIT_TERMCTL_FOCUS => return self.setctl_x(ctl, value),
+            _ => {}
         }
+        let ok = unsafe { c::tickit_term_setctl_int(self.tt, ctl, value as c_int) != 0 };
+        if !ok
+        {
+            return Err(UnsupportedCtlError(ctl));
+        }
+        if ctl == c::TICKIT_TERMCTL_MOUSE
+        {
+            self.set_sgr_mouse(value != 0);
+        }
+        Ok(())
     }
-    pub fn setctl_str(&mut self, ctl: TickitTermCtl, value: &str) -> bool
+    pub fn setctl_str(&mut self, ctl: TickitTermCtl, value: &str) -> Result<(), TickitError>
     {
-        unsafe
+        let ok = unsafe
         {
             value.with_c_str(
                 |v| { c::tickit_term_setctl_str(self.tt, ctl, v) != 0 }
             )
-        }
+        };
+        if ok { Ok(()) } else { Err(UnsupportedCtlError(ctl)) }
     }
 }
 
-
-pub struct StringPos
-{
-    pub bytes: uint,
-    pub codepoints: uint,
-    pub graphemes: uint,
-    pub columns: uint,
-}
-
-impl StringPos
+impl TickitTerm
 {
-    fn to_c(self) -> c::TickitStringPos
+    // The input filter reads SGR mouse reports (mode 1006) while a mouse
+    // mode is on. libtickit's xterm driver asks for them along with the
+    // mode; a Rust driver is only told the mode, so ask for them here.
+    fn set_sgr_mouse(&mut self, on: bool)
     {
-        c::TickitStringPos
+        if self.x.driver.is_some() && on != self.x.input.mouse_on
         {
-            bytes: self.bytes as size_t,
-            codepoints: self.codepoints as c_int,
-            graphemes: self.graphemes as c_int,
-            columns: self.columns as c_int,
+            self.write_raw(if on { "\x1b[?1006h" } else { "\x1b[?1006l" });
         }
+        self.x.input.mouse_on = on;
     }
 
-    fn from_c(lo: c::TickitStringPos) -> StringPos
+    // The controls libtickit doesn't know: a Rust driver may take them,
+    // else they go to the terminal as xterm's private modes.
+    fn setctl_x(&mut self, ctl: TickitTermCtl, value: int) -> Result<(), TickitError>
     {
-        StringPos
+        let ok = match self.x.driver
+        {
+            Some(ref driver) => driver.setctl_int(ctl, value),
+            None => true,
+        };
+        if !ok
+        {
+            return Err(UnsupportedCtlError(ctl));
+        }
+        let on = value != 0;
+        match ctl
+        {
+            c::X_TICKIT_TERMCTL_PASTE => self.x.input.paste_on = on,
+            c::X_TICKIT_TERMCTL_FOCUS => self.x.input.focus_on = on,
+            _ => unreachable!(),
+        }
+        if self.x.driver.is_none()
         {
-            bytes: lo.bytes as uint,
-            codepoints: lo.codepoints as uint,
-            graphemes: lo.graphemes as uint,
-            columns: lo.columns as uint,
+            let mode = match ctl
+            {
+                c::X_TICKIT_TERMCTL_PASTE => 2004i,
+                c::X_TICKIT_TERMCTL_FOCUS => 1004,
+                _ => unreachable!(),
+            };
+            self.write_raw(format!("\x1b[?{}{}", mode, if on { "h" } else { "l" }).as_slice());
         }
+        Ok(())
     }
 }
 
+// Typed wrappers around the above.
+impl TickitTerm
+{
+
+    pub fn get_altscreen(&mut self) -> Result<bool, TickitError>
+    {
+        self.getctl_int(c::TICKIT_TERMCTL_ALTSCREEN).map(|v| v != 0)
+    }
+    pub fn set_altscreen(&mut self, on: bool) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::TICKIT_TERMCTL_ALTSCREEN, on as int)
+    }
+
+    pub fn get_cursor_visible(&mut self) -> Result<bool, TickitError>
+    {
+        self.getctl_int(c::TICKIT_TERMCTL_CURSORVIS).map(|v| v != 0)
+    }
+    pub fn set_cursor_visible(&mut self, on: bool) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::TICKIT_TERMCTL_CURSORVIS, on as int)
+    }
+
+    pub fn get_cursor_blink(&mut self) -> Result<bool, TickitError>
+    {
+        self.getctl_int(c::TICKIT_TERMCTL_CURSORBLINK).map(|v| v != 0)
+    }
+    pub fn set_cursor_blink(&mut self, on: bool) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::TICKIT_TERMCTL_CURSORBLINK, on as int)
+    }
+
+    pub fn get_cursor_shape(&mut self) -> Result<c::TickitTermCursorShape, TickitError>
+    {
+        let ctl = c::TICKIT_TERMCTL_CURSORSHAPE;
+        self.getctl_int(ctl).and_then(|v| match c::TickitTermCursorShape::from_c(v as c_int) { Some(x) => Ok(x), None => Err(UnsupportedCtlError(ctl)) })
+    }
+    pub fn set_cursor_shape(&mut self, shape: c::TickitTermCursorShape) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::TICKIT_TERMCTL_CURSORSHAPE, shape as int)
+    }
+
+    pub fn get_mouse_mode(&mut self) -> Result<c::TickitTermMouseMode, TickitError>
+    {
+        let ctl = c::TICKIT_TERMCTL_MOUSE;
+        self.getctl_int(ctl).and_then(|v| match c::TickitTermMouseMode::from_c(v as c_int) { Some(x) => Ok(x), None => Err(UnsupportedCtlError(ctl)) })
+    }
+    pub fn set_mouse_mode(&mut self, mode: c::TickitTermMouseMode) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::TICKIT_TERMCTL_MOUSE, mode as int)
+    }
+
+    pub fn get_keypad_app(&mut self) -> Result<bool, TickitError>
+    {
+        self.getctl_int(c::TICKIT_TERMCTL_KEYPAD_APP).map(|v| v != 0)
+    }
+    pub fn set_keypad_app(&mut self, on: bool) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::TICKIT_TERMCTL_KEYPAD_APP, on as int)
+    }
+
+    pub fn set_title_text(&mut self, text: &str) -> Result<(), TickitError>
+    {
+        self.setctl_str(c::TICKIT_TERMCTL_TITLE_TEXT, text)
+    }
+    pub fn set_icon_text(&mut self, text: &str) -> Result<(), TickitError>
+    {
+        self.setctl_str(c::TICKIT_TERMCTL_ICON_TEXT, text)
+    }
+    pub fn set_icontitle_text(&mut self, text: &str) -> Result<(), TickitError>
+    {
+        self.setctl_str(c::TICKIT_TERMCTL_ICONTITLE_TEXT, text)
+    }
+
+    /// Whether pasted text arrives as one PasteEvent, bound with
+    /// c::X_TICKIT_EV_PASTE, rather than as keys.
+    pub fn get_bracketed_paste(&mut self) -> Result<bool, TickitError>
+    {
+        self.getctl_int(c::X_TICKIT_TERMCTL_PASTE).map(|v| v != 0)
+    }
+    pub fn set_bracketed_paste(&mut self, on: bool) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::X_TICKIT_TERMCTL_PASTE, on as int)
+    }
+
+    /// Whether the terminal reports gaining and losing focus, as FocusEvents
+    /// bound with c::X_TICKIT_EV_FOCUS.
+    pub fn get_focus_reports(&mut self) -> Result<bool, TickitError>
+    {
+        self.getctl_int(c::X_TICKIT_TERMCTL_FOCUS).map(|v| v != 0)
+    }
+    pub fn set_focus_reports(&mut self, on: bool) -> Result<(), TickitError>
+    {
+        self.setctl_int(c::X_TICKIT_TERMCTL_FOCUS, on as int)
+    }
+
+    /// Number of colours the terminal supports; there is no setter.
+    pub fn get_colors(&mut self) -> Result<u32, TickitError>
+    {
+        self.getctl_int(c::TICKIT_TERMCTL_COLORS).map(|v| v as u32)
+    }
+}
+
+
+#[deriving(Clone, PartialEq, Eq, Show)]
+pub struct StringPos
+{
+    pub bytes: uint,
+    pub codepoints: uint,
+    pub graphemes: uint,
+    pub columns: uint,
+}
+
 impl StringPos
 {
     // these are public just to let unit tests check failure correctly
     pub fn x_bcount(str_: &[u8], pos: &mut StringPos, limit: Option<StringPos>) -> uint
     {
-        unsafe
-        {
-            let s: &[c_char] = std::mem::transmute(str_);
-            let mut cpos = pos.to_c();
-            let rv = c::tickit_string_ncount(s.as_ptr(), s.len() as size_t, &mut cpos, match limit { Some(l) => { &l.to_c() as *const _ } None => { std::ptr::null() } });
-            *pos = StringPos::from_c(cpos);
-            rv as uint
-        }
+        *pos = StringPos::zero();
+        StringPos::x_bcountmore(str_, pos, limit).unwrap_or(-1)
     }
-    pub fn x_bcountmore(str_: &[u8], pos: &mut StringPos, limit: Option<StringPos>) -> uint
+    /// As countmore_with, failing as well on reaching bytes that aren't
+    /// UTF-8.
+    pub fn x_bcountmore(str_: &[u8], pos: &mut StringPos, limit: Option<StringPos>) -> Option<uint>
     {
-        unsafe
+        // count what is valid UTF-8, failing only on reaching the rest
+        let mut valid = 0;
+        while valid < str_.len()
+        {
+            let len = std::str::utf8_char_width(str_[valid]);
+            if len == 0 || valid + len > str_.len() || std::str::from_utf8(str_.slice(valid, valid + len)).is_none()
+            {
+                break;
+            }
+            valid += len;
+        }
+        let text = unsafe { std::str::raw::from_utf8(str_.slice_to(valid)) };
+        let rv = StringPos::countmore_with(text, pos, limit, unicode::ambiguous_wide());
+        // the invalid byte is only reached if the limit has room for it
+        let limit = limit.unwrap_or(StringPos::limit_none());
+        let at_limit = pos.bytes >= limit.bytes || pos.codepoints >= limit.codepoints
+            || pos.graphemes >= limit.graphemes || pos.columns >= limit.columns;
+        if rv.is_some() && valid < str_.len() && pos.bytes == valid && !at_limit
         {
-            let s: &[c_char] = std::mem::transmute(str_);
-            let mut cpos = pos.to_c();
-            let rv = c::tickit_string_ncountmore(s.as_ptr(), s.len() as size_t, &mut cpos, match limit { Some(l) => { &l.to_c() as *const _ } None => { std::ptr::null() } });
-            *pos = StringPos::from_c(cpos);
-            rv as uint
+            return None;
         }
+        rv
     }
     pub fn count(str_: &str, pos: &mut StringPos, limit: Option<StringPos>) -> uint
     {
-        StringPos::x_bcount(str_.as_bytes(), pos, limit)
+        *pos = StringPos::zero();
+        StringPos::countmore(str_, pos, limit)
     }
+    /// As countmore_with, giving libtickit's -1 for None.
     pub fn countmore(str_: &str, pos: &mut StringPos, limit: Option<StringPos>) -> uint
     {
-        StringPos::x_bcountmore(str_.as_bytes(), pos, limit)
+        StringPos::countmore_with(str_, pos, limit, unicode::ambiguous_wide()).unwrap_or(-1)
+    }
+
+    /// Count whole grapheme clusters from `pos` for as long as none of
+    /// `limit` is passed, returning the bytes counted, or None on reaching
+    /// a control character. `ambiguous_wide` is as for set_ambiguous_wide.
+    pub fn countmore_with(str_: &str, pos: &mut StringPos, limit: Option<StringPos>, ambiguous_wide: bool) -> Option<uint>
+    {
+        let limit = limit.unwrap_or(StringPos::limit_none());
+        let start = pos.bytes;
+        if start > str_.len() || !str_.is_char_boundary(start)
+        {
+            return None;
+        }
+        for cluster in unicode::graphemes(str_.slice_from(start))
+        {
+            let width = match unicode::cluster_width(cluster, ambiguous_wide)
+            {
+                Some(width) => width,
+                None => return None,
+            };
+            let next = StringPos
+            {
+                bytes: pos.bytes + cluster.len(),
+                codepoints: pos.codepoints + cluster.chars().count(),
+                graphemes: pos.graphemes + 1,
+                columns: pos.columns + width,
+            };
+            if next.bytes > limit.bytes || next.codepoints > limit.codepoints
+                || next.graphemes > limit.graphemes || next.columns > limit.columns
+            {
+                break;
+            }
+            *pos = next;
+        }
+        Some(pos.bytes - start)
     }
 }
 
@@ -1384,52 +2879,54 @@ impl StringPos
 
 pub fn mbswidth(str_: &str) -> uint
 {
-    unsafe
-    {
-        str_.with_c_str(
-            |s| { c::tickit_string_mbswidth(s) as uint }
-        )
-    }
+    let mut pos = StringPos::zero();
+    StringPos::count(str_, &mut pos, None);
+    pos.columns
 }
 
 pub fn byte2col(str_: &str, byte: uint) -> uint
 {
-    unsafe
-    {
-        str_.with_c_str(
-            |s| { c::tickit_string_byte2col(s, byte as size_t) as uint }
-        )
-    }
+    let mut pos = StringPos::zero();
+    StringPos::count(str_, &mut pos, Some(StringPos::limit_bytes(byte)));
+    pos.columns
 }
 
 pub fn col2byte(str_: &str, col: uint) -> uint
 {
-    unsafe
-    {
-        str_.with_c_str(
-            |s| { c::tickit_string_col2byte(s, col as c_int) as uint }
-        )
-    }
+    let mut pos = StringPos::zero();
+    StringPos::count(str_, &mut pos, Some(StringPos::limit_columns(col)));
+    pos.bytes
 }
 
 
+#[cfg(not(feature = "rust-renderbuffer"))]
 pub struct TickitRenderBuffer
 {
     rb: *mut c::TickitRenderBuffer,
 }
 
+#[cfg(not(feature = "rust-renderbuffer"))]
 impl TickitRenderBuffer
 {
-    pub fn new(lines: int, cols: int) -> TickitRenderBuffer
+    pub fn new(lines: int, cols: int) -> Result<TickitRenderBuffer, TickitError>
     {
+        if lines < 0 || cols < 0
+        {
+            return Err(InvalidGeometryError(lines, cols));
+        }
         unsafe
         {
             let rb = c::tickit_renderbuffer_new(lines as c_int, cols as c_int);
-            TickitRenderBuffer{ rb: rb }
+            if rb.is_null()
+            {
+                return Err(TickitError::last_os_error());
+            }
+            Ok(TickitRenderBuffer{ rb: rb })
         }
     }
 }
 
+#[cfg(not(feature = "rust-renderbuffer"))]
 impl Drop for TickitRenderBuffer
 {
     fn drop(&mut self)
@@ -1441,6 +2938,7 @@ impl Drop for TickitRenderBuffer
     }
 }
 
+#[cfg(not(feature = "rust-renderbuffer"))]
 impl TickitRenderBuffer
 {
     pub fn get_size(&self) -> (int, int)
@@ -1636,6 +3134,7 @@ impl TickitRenderBuffer
     }
 }
 
+#[cfg(not(feature = "rust-renderbuffer"))]
 impl TickitRenderBuffer
 {
     pub fn hline_at(&mut self, line: int, startcol: int, endcol: int, style: TickitLineStyle, pen: Option<&TickitPen>, caps: TickitLineCaps)
@@ -1655,10 +3154,99 @@ impl TickitRenderBuffer
 
     pub fn flush_to_term(&mut self, tt: &mut TickitTerm)
     {
+        if tt.needs_palette() || tt.has_ext_out()
+        {
+            // libtickit would send the pens as they are, and from a pen
+            // it thinks the terminal has, so walk the spans and let
+            // tt.setpen fit them to the terminal's palette and undo what
+            // it sent beyond libtickit.
+            self.flush_spans_to_term(tt);
+            return;
+        }
+        let last = self.last_span_pen();
         unsafe
         {
             c::tickit_renderbuffer_flush_to_term(self.rb, tt.tt);
         }
+        // libtickit has set the pens itself; follow it, as tt.setpen would
+        if last.is_some()
+        {
+            tt.x.c_out = last;
+        }
+    }
+
+    // The pen libtickit leaves the terminal in after a flush: that of the
+    // last span it draws, if any.
+    fn last_span_pen(&mut self) -> Option<TickitPen>
+    {
+        let (lines, cols) = self.get_size();
+        for line in range(0, lines).rev()
+        {
+            let mut last = None;
+            let mut col = 0;
+            while col < cols
+            {
+                match self.get_span(line, col)
+                {
+                    None => break,
+                    Some(SkipSpan{n_columns}) =>
+                    {
+                        col += n_columns;
+                    }
+                    Some(TextSpan{pen, n_columns, ..}) =>
+                    {
+                        last = Some(pen);
+                        col += n_columns;
+                    }
+                }
+            }
+            if last.is_some()
+            {
+                return last;
+            }
+        }
+        None
+    }
+
+    fn flush_spans_to_term(&mut self, tt: &mut TickitTerm)
+    {
+        let (lines, cols) = self.get_size();
+        for line in range(0, lines)
+        {
+            let mut phycol = -1;
+            let mut col = 0;
+            while col < cols
+            {
+                match self.get_span(line, col)
+                {
+                    None => break,
+                    Some(SkipSpan{n_columns}) =>
+                    {
+                        col += n_columns;
+                    }
+                    Some(TextSpan{pen, text, n_columns}) =>
+                    {
+                        if phycol != col
+                        {
+                            tt.goto(line, col);
+                        }
+                        tt.setpen(&pen);
+                        if text.is_empty()
+                        {
+                            // an erased span
+                            tt.erasech(n_columns, Some(true));
+                        }
+                        else
+                        {
+                            tt.print(text.as_slice());
+                        }
+                        col += n_columns;
+                        phycol = col;
+                    }
+                }
+            }
+        }
+        self.reset();
     }
 }
 
@@ -1673,6 +3261,7 @@ pub struct TickitRenderBufferLineMask
 
 impl TickitRenderBufferLineMask
 {
+    #[allow(dead_code)]
     fn from_c(lo: c::TickitRenderBufferLineMask) -> TickitRenderBufferLineMask
     {
         unsafe
@@ -1688,6 +3277,7 @@ impl TickitRenderBufferLineMask
 }
 
 #[experimental]
+#[cfg(not(feature = "rust-renderbuffer"))]
 impl TickitRenderBuffer
 {
     pub fn get_cell_active(&mut self, line: int, col: int) -> bool
@@ -1728,7 +3318,7 @@ impl TickitRenderBuffer
         unsafe
         {
             let cpen = c::tickit_renderbuffer_get_cell_pen(self.rb, line as c_int, col as c_int);
-            TickitPen{pen: c::tickit_pen_clone(cpen)}
+            TickitPen::clone_c(cpen)
         }
     }
 }
@@ -1737,14 +3327,16 @@ impl TickitRenderBuffer
 pub enum TickitRenderBufferSpanInfo
 {
     SkipSpan{pub n_columns: int},
-    TextSpan{pub pen: TickitPen, pub text: String},
+    TextSpan{pub pen: TickitPen, pub text: String, pub n_columns: int},
 }
 
 #[experimental]
+#[cfg(not(feature = "rust-renderbuffer"))]
 impl TickitRenderBuffer
 {
-// returns the text length or -1 on error
-    pub fn get_span(&mut self, line: int, startcol: int) -> TickitRenderBufferSpanInfo
+    /// The span covering `startcol` from there on, or None outside the
+    /// buffer.
+    pub fn get_span(&mut self, line: int, startcol: int) -> Option<TickitRenderBufferSpanInfo>
     {
         unsafe
         {
@@ -1757,11 +3349,11 @@ impl TickitRenderBuffer
             let badlen = c::tickit_renderbuffer_get_span(self.rb, line as c_int, startcol as c_int, &mut span_info, std::ptr::null_mut(), 0);
             if badlen == -1
             {
-                fail!();
+                return None;
             }
             if !(span_info.is_active != 0)
             {
-                SkipSpan{n_columns: span_info.n_columns as int}
+                Some(SkipSpan{n_columns: span_info.n_columns as int})
             }
             else
             {
@@ -1769,7 +3361,7 @@ impl TickitRenderBuffer
                 let goodlen = span_info.len;
                 let buf: Vec<u8> = Vec::from_fn(goodlen as uint, |_| { std::mem::uninitialized() });
                 c::tickit_renderbuffer_get_span(self.rb, line as c_int, startcol as c_int, &mut span_info, buf.as_ptr() as *mut c_char, goodlen);
-                TextSpan{pen: TickitPen{pen: c::tickit_pen_clone(const_(span_info.pen))}, text: collections::string::raw::from_utf8(buf)}
+                Some(TextSpan{pen: TickitPen::clone_c(const_(span_info.pen)), text: collections::string::raw::from_utf8(buf), n_columns: span_info.n_columns as int})
             }
         }
     }
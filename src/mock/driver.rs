@@ -0,0 +1,234 @@
+// A MockTerm that needs nothing from libtickit beyond TickitTerm itself:
+// the screen and the operation log live in Rust, fed by a term driver.
+
+use std;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use c::{TickitTermCtl,TICKIT_TERMCTL_COLORS,TICKIT_TERMCTL_MOUSE,X_TICKIT_TERMCTL_PASTE,X_TICKIT_TERMCTL_FOCUS};
+use drv::{CDriverRef,TickitTermDriverImpl};
+use TickitPen;
+use TickitRect;
+use TickitTerm;
+use {TickitError,InvalidGeometryError};
+
+use super::LogEntry;
+use super::{Goto,Print,EraseCh,Clear,ScrollRect,SetPen};
+use super::screen::MockScreen;
+
+// LogEntry, but owning its strings
+enum LogRecord
+{
+    GotoRecord(int, int),
+    PrintRecord(String),
+    EraseChRecord(int, Option<bool>),
+    ClearRecord,
+    ScrollRectRecord(int, int, TickitRect),
+    SetPenRecord(TickitPen),
+}
+
+impl LogRecord
+{
+    fn entry<'a>(&'a self) -> LogEntry<'a>
+    {
+        match *self
+        {
+            GotoRecord(line, col) => Goto{line: line, col: col},
+            PrintRecord(ref str_) => Print{str_: str_.as_slice()},
+            EraseChRecord(count, moveend) => EraseCh{count: count, moveend: moveend},
+            ClearRecord => Clear,
+            ScrollRectRecord(downward, rightward, rect) => ScrollRect{downward: downward, rightward: rightward, rect: rect},
+            SetPenRecord(ref pen) => SetPen{pen: pen.clone()},
+        }
+    }
+}
+
+struct MockState
+{
+    screen: MockScreen,
+    log: Vec<LogRecord>,
+    // TICKIT_TERMCTL_COLORS, which tests may set to try smaller palettes
+    colors: int,
+}
+
+struct MockDriver
+{
+    state: Rc<RefCell<MockState>>,
+}
+
+impl TickitTermDriverImpl for MockDriver
+{
+    fn print(&mut self, _cdr: CDriverRef, str_: &str)
+    {
+        let mut state = self.state.borrow_mut();
+        state.screen.print(str_);
+        state.log.push(PrintRecord(str_.to_string()));
+    }
+    fn goto_abs(&mut self, _cdr: CDriverRef, line: int, col: int) -> bool
+    {
+        let mut state = self.state.borrow_mut();
+        state.screen.goto(line, col);
+        state.log.push(GotoRecord(line, col));
+        true
+    }
+    fn move_rel(&mut self, cdr: CDriverRef, downward: int, rightward: int)
+    {
+        let (line, col) =
+        {
+            let state = self.state.borrow();
+            (state.screen.line + downward, state.screen.col + rightward)
+        };
+        self.goto_abs(cdr, line, col);
+    }
+    fn scrollrect(&mut self, _cdr: CDriverRef, rect: &TickitRect, downward: int, rightward: int) -> bool
+    {
+        let mut state = self.state.borrow_mut();
+        state.screen.scrollrect(rect, downward, rightward);
+        state.log.push(ScrollRectRecord(downward, rightward, *rect));
+        true
+    }
+    fn erasech(&mut self, _cdr: CDriverRef, count: int, moveend: Option<bool>)
+    {
+        let mut state = self.state.borrow_mut();
+        state.screen.erasech(count, moveend);
+        state.log.push(EraseChRecord(count, moveend));
+    }
+    fn clear(&mut self, _cdr: CDriverRef)
+    {
+        let mut state = self.state.borrow_mut();
+        state.screen.clear();
+        state.log.push(ClearRecord);
+    }
+    fn chpen(&mut self, _cdr: CDriverRef, _delta: &TickitPen, final_: &TickitPen)
+    {
+        let mut state = self.state.borrow_mut();
+        // libtickit's pen has lost what chpen_ext last set
+        let mut pen = final_.clone();
+        pen.copy_ext(&state.screen.pen);
+        state.screen.pen = pen.clone();
+        state.log.push(SetPenRecord(pen));
+    }
+    fn chpen_ext(&mut self, _cdr: CDriverRef, pen: &TickitPen) -> bool
+    {
+        let mut state = self.state.borrow_mut();
+        state.screen.pen.copy_ext(pen);
+        let pen = state.screen.pen.clone();
+        // part of the same pen change as any SetPen libtickit just logged
+        let merged = match state.log.last_mut()
+        {
+            Some(record) => match *record
+            {
+                SetPenRecord(ref mut last) =>
+                {
+                    last.clone_from(&pen);
+                    true
+                }
+                _ => false,
+            },
+            None => false,
+        };
+        if !merged
+        {
+            state.log.push(SetPenRecord(pen));
+        }
+        true
+    }
+    fn getctl_int(&mut self, _cdr: CDriverRef, ctl: TickitTermCtl) -> Option<int>
+    {
+        match ctl
+        {
+            TICKIT_TERMCTL_COLORS => Some(self.state.borrow().colors),
+            _ => None,
+        }
+    }
+    fn setctl_int(&mut self, _cdr: CDriverRef, ctl: TickitTermCtl, value: int) -> bool
+    {
+        match ctl
+        {
+            TICKIT_TERMCTL_COLORS if value > 0 =>
+            {
+                self.state.borrow_mut().colors = value;
+                true
+            }
+            // nothing to draw, so nothing to do
+            TICKIT_TERMCTL_MOUSE | X_TICKIT_TERMCTL_PASTE | X_TICKIT_TERMCTL_FOCUS => true,
+            _ => false,
+        }
+    }
+    fn setctl_str(&mut self, _cdr: CDriverRef, _ctl: TickitTermCtl, _value: &str) -> bool
+    {
+        false
+    }
+}
+
+pub struct MockTerm
+{
+    pub tt: TickitTerm,
+    state: Rc<RefCell<MockState>>,
+    // entries are moved here from the driver's log so peeklog can lend them
+    log: Vec<LogRecord>,
+}
+
+impl MockTerm
+{
+    pub fn new(lines: int, cols: int) -> Result<MockTerm, TickitError>
+    {
+        if lines < 0 || cols < 0
+        {
+            return Err(InvalidGeometryError(lines, cols));
+        }
+        let state = Rc::new(RefCell::new(MockState{screen: MockScreen::new(lines, cols), log: Vec::new(), colors: 256}));
+        let mut tt = try!(TickitTerm::new_for_driver(MockDriver{state: state.clone()}));
+        tt.set_size(lines, cols);
+        Ok(MockTerm{tt: tt, state: state, log: Vec::new()})
+    }
+}
+
+impl MockTerm
+{
+    fn take_log(&mut self)
+    {
+        let records = std::mem::replace(&mut self.state.borrow_mut().log, Vec::new());
+        for record in records.into_iter()
+        {
+            self.log.push(record);
+        }
+    }
+
+    pub fn resize(&mut self, newlines: uint, newcols: uint)
+    {
+        self.state.borrow_mut().screen.resize(newlines as int, newcols as int);
+        self.tt.set_size(newlines as int, newcols as int);
+    }
+
+    pub fn get_display_text(&mut self, line: uint, col: uint, width: uint) -> String
+    {
+        self.state.borrow().screen.get_display_text(line as int, col as int, width as int)
+    }
+    pub fn get_display_pen(&mut self, line: int, col: int) -> TickitPen
+    {
+        self.state.borrow().screen.get_display_pen(line, col)
+    }
+
+    pub fn loglen(&mut self) -> uint
+    {
+        self.take_log();
+        self.log.len()
+    }
+    pub fn peeklog<'a>(&'a mut self, i: uint) -> LogEntry<'a>
+    {
+        self.take_log();
+        self.log[i].entry()
+    }
+    pub fn clearlog(&mut self)
+    {
+        self.take_log();
+        self.log.clear();
+    }
+
+    pub fn get_position(&mut self) -> (uint, uint)
+    {
+        let state = self.state.borrow();
+        (state.screen.line as uint, state.screen.col as uint)
+    }
+}
@@ -1,15 +1,30 @@
 #![experimental]
 
+#[cfg(not(feature = "rust-mockterm"))]
 use std;
+#[cfg(not(feature = "rust-mockterm"))]
 use collections;
 
+#[cfg(not(feature = "rust-mockterm"))]
 use libc::{c_char,c_int};
 
 use TickitPen;
 use TickitRect;
+#[cfg(not(feature = "rust-mockterm"))]
 use TickitTerm;
+#[cfg(not(feature = "rust-mockterm"))]
+use {TickitError,InvalidGeometryError};
 
 pub mod c;
+#[cfg(feature = "rust-mockterm")]
+mod driver;
+mod screen;
+mod snapshot;
+mod vt;
+
+#[cfg(feature = "rust-mockterm")]
+pub use mock::driver::MockTerm;
+pub use mock::vt::VirtualTerm;
 
 pub enum LogEntry<'a>
 {
@@ -21,6 +36,7 @@ pub enum LogEntry<'a>
     SetPen{pub pen: TickitPen},
 }
 
+#[cfg(not(feature = "rust-mockterm"))]
 impl<'a> LogEntry<'a>
 {
     fn from_c(e: &c::TickitMockTermLogEntry) -> LogEntry<'a>
@@ -56,26 +72,35 @@ impl<'a> LogEntry<'a>
             }
             c::LOG_SETPEN =>
             {
-                let pen: &TickitPen = unsafe { std::mem::transmute(&e.pen) };
-                SetPen{pen: pen.clone()}
+                SetPen{pen: unsafe { TickitPen::clone_c(e.pen as *const _) }}
             }
         }
     }
 }
 
+#[cfg(not(feature = "rust-mockterm"))]
 pub struct MockTerm
 {
     pub tt: TickitTerm,
 }
 
+#[cfg(not(feature = "rust-mockterm"))]
 impl MockTerm
 {
-    pub fn new(lines: int, cols: int) -> MockTerm
+    pub fn new(lines: int, cols: int) -> Result<MockTerm, TickitError>
     {
+        if lines < 0 || cols < 0
+        {
+            return Err(InvalidGeometryError(lines, cols));
+        }
         unsafe
         {
             let tt = c::tickit_mockterm_new(lines as c_int, cols as c_int);
-            MockTerm{tt: TickitTerm{tt: tt, output_hook: std::ptr::null_mut(), output_box: None}}
+            if tt.is_null()
+            {
+                return Err(TickitError::last_os_error());
+            }
+            Ok(MockTerm{tt: TickitTerm::from_c(tt)})
         }
     }
 }
@@ -94,6 +119,7 @@ impl Drop for MockTerm
 }
 */
 
+#[cfg(not(feature = "rust-mockterm"))]
 impl MockTerm
 {
     fn mt(&mut self) -> *mut c::TickitMockTerm
@@ -127,9 +153,7 @@ impl MockTerm
     {
         unsafe
         {
-            let pen = c::tickit_mockterm_get_display_pen(self.mt(), line as c_int, col as c_int);
-            let pen: &TickitPen = std::mem::transmute(&pen);
-            pen.clone()
+            TickitPen::clone_c(c::tickit_mockterm_get_display_pen(self.mt(), line as c_int, col as c_int) as *const _)
         }
     }
 
@@ -0,0 +1,236 @@
+// The screen model behind the pure-Rust MockTerm and VirtualTerm: a grid of
+// cells, each holding the text and pen last drawn there, plus a cursor and
+// current pen.
+
+use StringPos;
+use TickitPen;
+use TickitRect;
+
+#[deriving(Clone)]
+struct Cell
+{
+    // None for the right half of a double-width character
+    text: Option<String>,
+    pen: TickitPen,
+}
+
+impl Cell
+{
+    fn blank(pen: &TickitPen) -> Cell
+    {
+        Cell{text: Some(" ".to_string()), pen: pen.clone()}
+    }
+}
+
+fn blank_line(cols: int, pen: &TickitPen) -> Vec<Cell>
+{
+    Vec::from_fn(cols as uint, |_| Cell::blank(pen))
+}
+
+pub struct MockScreen
+{
+    lines: int,
+    cols: int,
+    cells: Vec<Vec<Cell>>,
+    pub line: int,
+    pub col: int,
+    pub pen: TickitPen,
+}
+
+impl MockScreen
+{
+    pub fn new(lines: int, cols: int) -> MockScreen
+    {
+        let pen = TickitPen::new();
+        MockScreen
+        {
+            lines: lines,
+            cols: cols,
+            cells: Vec::from_fn(lines as uint, |_| blank_line(cols, &pen)),
+            line: 0,
+            col: 0,
+            pen: pen,
+        }
+    }
+
+    pub fn get_size(&self) -> (int, int)
+    {
+        (self.lines, self.cols)
+    }
+
+    /// Change the size, keeping whatever content still fits.
+    pub fn resize(&mut self, lines: int, cols: int)
+    {
+        let blank = TickitPen::new();
+        self.cells.truncate(lines as uint);
+        for row in self.cells.iter_mut()
+        {
+            row.truncate(cols as uint);
+            while (row.len() as int) < cols
+            {
+                row.push(Cell::blank(&blank));
+            }
+        }
+        while (self.cells.len() as int) < lines
+        {
+            self.cells.push(blank_line(cols, &blank));
+        }
+        self.lines = lines;
+        self.cols = cols;
+    }
+
+    fn in_bounds(&self, line: int, col: int) -> bool
+    {
+        line >= 0 && line < self.lines && col >= 0 && col < self.cols
+    }
+
+    fn cell(&self, line: int, col: int) -> &Cell
+    {
+        &self.cells[line as uint][col as uint]
+    }
+    fn cell_mut(&mut self, line: int, col: int) -> &mut Cell
+    {
+        &mut self.cells.as_mut_slice()[line as uint].as_mut_slice()[col as uint]
+    }
+
+    /// Move the cursor; -1 leaves that coordinate unchanged.
+    pub fn goto(&mut self, line: int, col: int)
+    {
+        if line != -1
+        {
+            self.line = line;
+        }
+        if col != -1
+        {
+            self.col = col;
+        }
+    }
+
+    /// Write text at the cursor in the current pen, one grapheme per cell
+    /// (two for double-width ones), and advance the cursor past it.
+    pub fn print(&mut self, text: &str)
+    {
+        let mut pos = StringPos::zero();
+        while pos.bytes < text.len()
+        {
+            let start = pos;
+            StringPos::countmore(text, &mut pos, Some(StringPos::limit_graphemes(start.graphemes + 1)));
+            if pos.bytes == start.bytes
+            {
+                break;
+            }
+            self.put(text.slice(start.bytes, pos.bytes), (pos.columns - start.columns) as int);
+        }
+    }
+
+    fn put(&mut self, grapheme: &str, width: int)
+    {
+        let (line, col) = (self.line, self.col);
+        for i in range(0, width)
+        {
+            if !self.in_bounds(line, col + i)
+            {
+                continue;
+            }
+            let pen = self.pen.clone();
+            let cell = self.cell_mut(line, col + i);
+            cell.text = if i == 0 { Some(grapheme.to_string()) } else { None };
+            cell.pen = pen;
+        }
+        self.col += width;
+    }
+
+    /// Blank `count` cells from (line, col) in the current pen.
+    pub fn erase(&mut self, line: int, col: int, count: int)
+    {
+        for i in range(0, count)
+        {
+            if self.in_bounds(line, col + i)
+            {
+                let blank = Cell::blank(&self.pen);
+                *self.cell_mut(line, col + i) = blank;
+            }
+        }
+    }
+
+    /// Blank `count` cells from the cursor in the current pen.
+    pub fn erasech(&mut self, count: int, moveend: Option<bool>)
+    {
+        let (line, col) = (self.line, self.col);
+        self.erase(line, col, count);
+        if moveend == Some(true)
+        {
+            self.col += count;
+        }
+    }
+
+    pub fn clear(&mut self)
+    {
+        let (lines, cols) = (self.lines, self.cols);
+        let pen = self.pen.clone();
+        self.cells = Vec::from_fn(lines as uint, |_| blank_line(cols, &pen));
+    }
+
+    /// Move the content of `rect` up by `downward` lines and left by
+    /// `rightward` columns (negative values go the other way), blanking
+    /// the cells that are uncovered.
+    pub fn scrollrect(&mut self, rect: &TickitRect, downward: int, rightward: int)
+    {
+        let bounds = TickitRect::init_sized(0, 0, self.lines, self.cols);
+        let rect = match rect.intersect(&bounds)
+        {
+            Some(r) => r,
+            None => return,
+        };
+
+        let old: Vec<Vec<Cell>> = range(rect.top, rect.bottom()).map(
+            |line| range(rect.left, rect.right()).map(|col| self.cell(line, col).clone()).collect()
+        ).collect();
+
+        for line in range(rect.top, rect.bottom())
+        {
+            for col in range(rect.left, rect.right())
+            {
+                let src_line = line + downward;
+                let src_col = col + rightward;
+                let cell = if src_line >= rect.top && src_line < rect.bottom() && src_col >= rect.left && src_col < rect.right()
+                {
+                    old[(src_line - rect.top) as uint][(src_col - rect.left) as uint].clone()
+                }
+                else
+                {
+                    Cell::blank(&self.pen)
+                };
+                *self.cell_mut(line, col) = cell;
+            }
+        }
+    }
+
+    /// The text of `width` columns starting at (line, col).
+    pub fn get_display_text(&self, line: int, col: int, width: int) -> String
+    {
+        let mut out = String::new();
+        for c in range(col, col + width)
+        {
+            if !self.in_bounds(line, c)
+            {
+                continue;
+            }
+            match self.cell(line, c).text
+            {
+                Some(ref text) => out.push_str(text.as_slice()),
+                None => {}
+            }
+        }
+        out
+    }
+
+    pub fn get_display_pen(&self, line: int, col: int) -> TickitPen
+    {
+        if !self.in_bounds(line, col)
+        {
+            return TickitPen::new();
+        }
+        self.cell(line, col).pen.clone()
+    }
+}
@@ -0,0 +1,177 @@
+// Textual dumps of a MockTerm's screen, for golden-file tests.
+//
+// The format is:
+//
+//     size 3x10
+//     |Hello     |
+//     |   world  |
+//     |          |
+//     pen 1:3-7 fg=1 b
+//
+// one '|'-delimited row per line, then one 'pen' entry per run of cells on
+// a line sharing the same non-default pen (columns inclusive).
+
+use std;
+use std::io;
+use std::io::File;
+
+use c::{TICKIT_PENTYPE_BOOL,TICKIT_PENTYPE_INT,TICKIT_PENTYPE_COLOUR};
+use c::TICKIT_PEN_UNDER;
+use TickitPen;
+use PEN_ATTRS;
+use {IndexColour,RGBColour};
+use {NoUnderline,SingleUnderline};
+
+use super::MockTerm;
+
+// Set this in the environment to rewrite golden files instead of comparing.
+static BLESS_VAR: &'static str = "TICKIT_BLESS";
+
+fn pen_desc(pen: &TickitPen) -> String
+{
+    let mut out = Vec::new();
+    for &attr in PEN_ATTRS.iter()
+    {
+        if !pen.nondefault_attr(attr)
+        {
+            continue;
+        }
+        match attr.attrtype()
+        {
+            TICKIT_PENTYPE_BOOL if attr == TICKIT_PEN_UNDER => out.push(match pen.get_under_style()
+            {
+                NoUnderline | SingleUnderline => attr.attrname().to_string(),
+                style => format!("{}={}", attr.attrname(), style.name()),
+            }),
+            TICKIT_PENTYPE_BOOL => out.push(attr.attrname().to_string()),
+            TICKIT_PENTYPE_INT => out.push(format!("{}={}", attr.attrname(), pen.get_int_attr(attr))),
+            TICKIT_PENTYPE_COLOUR => out.push(match pen.get_rgb_attr(attr)
+            {
+                Some(rgb) => format!("{}={}", attr.attrname(), rgb),
+                None => format!("{}={}", attr.attrname(), pen.get_colour_attr(attr)),
+            }),
+        }
+    }
+    match pen.get_under_colour()
+    {
+        Some(IndexColour(idx)) => out.push(format!("uc={}", idx)),
+        Some(RGBColour(rgb)) => out.push(format!("uc={}", rgb)),
+        None => {}
+    }
+    out.connect(" ")
+}
+
+// Line diff via longest common subsequence; screens are small enough.
+fn diff_lines(expect: &[&str], got: &[&str]) -> String
+{
+    let n = expect.len();
+    let m = got.len();
+    let mut lcs = Vec::from_elem((n + 1) * (m + 1), 0u);
+    for i in range(0, n).rev()
+    {
+        for j in range(0, m).rev()
+        {
+            let v = if expect[i] == got[j]
+            {
+                lcs[(i + 1) * (m + 1) + j + 1] + 1
+            }
+            else
+            {
+                std::cmp::max(lcs[(i + 1) * (m + 1) + j], lcs[i * (m + 1) + j + 1])
+            };
+            lcs.as_mut_slice()[i * (m + 1) + j] = v;
+        }
+    }
+
+    let mut out = String::new();
+    let (mut i, mut j) = (0u, 0u);
+    while i < n || j < m
+    {
+        if i < n && j < m && expect[i] == got[j]
+        {
+            out.push_str(format!(" {}\n", expect[i]).as_slice());
+            i += 1;
+            j += 1;
+        }
+        else if j < m && (i == n || lcs[i * (m + 1) + j + 1] >= lcs[(i + 1) * (m + 1) + j])
+        {
+            out.push_str(format!("+{}\n", got[j]).as_slice());
+            j += 1;
+        }
+        else
+        {
+            out.push_str(format!("-{}\n", expect[i]).as_slice());
+            i += 1;
+        }
+    }
+    out
+}
+
+impl MockTerm
+{
+    /// Dump the whole screen, text and pens, in a stable textual format.
+    pub fn snapshot(&mut self) -> String
+    {
+        let (lines, cols) = self.tt.get_size();
+        let mut out = format!("size {}x{}\n", lines, cols);
+
+        for line in range(0, lines)
+        {
+            out.push_str(format!("|{}|\n", self.get_display_text(line, 0, cols)).as_slice());
+        }
+
+        for line in range(0, lines)
+        {
+            let mut col = 0;
+            while col < cols
+            {
+                let pen = self.get_display_pen(line as int, col as int);
+                let mut end = col + 1;
+                while end < cols && self.get_display_pen(line as int, end as int).equiv(&pen)
+                {
+                    end += 1;
+                }
+                let desc = pen_desc(&pen);
+                if !desc.is_empty()
+                {
+                    out.push_str(format!("pen {}:{}-{} {}\n", line, col, end - 1, desc).as_slice());
+                }
+                col = end;
+            }
+        }
+
+        out
+    }
+
+    /// Compare the screen against a golden file, returning a diff (expected
+    /// lines marked '-', actual lines '+') if they differ.
+    ///
+    /// If `TICKIT_BLESS` is set in the environment, the file is (re)written
+    /// instead.
+    pub fn check_snapshot(&mut self, path: &Path) -> Result<(), String>
+    {
+        let got = self.snapshot();
+
+        if std::os::getenv(BLESS_VAR).is_some()
+        {
+            let res = io::fs::mkdir_recursive(&path.dir_path(), io::USER_RWX)
+                .and_then(|_| File::create(path).write_str(got.as_slice()));
+            return res.map_err(|e| format!("cannot write {}: {}", path.display(), e));
+        }
+
+        let expect = match File::open(path).read_to_string()
+        {
+            Ok(s) => s,
+            Err(e) => return Err(format!("cannot read {}: {} (set {}=1 to create it)", path.display(), e, BLESS_VAR)),
+        };
+
+        if expect == got
+        {
+            return Ok(());
+        }
+
+        let expect_lines: Vec<&str> = expect.as_slice().lines().collect();
+        let got_lines: Vec<&str> = got.as_slice().lines().collect();
+        Err(diff_lines(expect_lines.as_slice(), got_lines.as_slice()))
+    }
+}
@@ -0,0 +1,726 @@
+// Replays the bytes a TickitTerm writes to an xterm-like terminal into a
+// virtual screen, so tests can look at the result instead of the encoding.
+//
+// Only the subset of VT100/xterm that libtickit's drivers emit is
+// understood; anything else is consumed and ignored. There is no autowrap:
+// text printed past the right edge is clipped.
+
+use std;
+use std::cmp;
+
+use c::{TickitTermCursorShape,TickitTermMouseMode};
+use c::{TICKIT_TERM_CURSORSHAPE_BLOCK,TICKIT_TERM_CURSORSHAPE_UNDER,TICKIT_TERM_CURSORSHAPE_LEFT_BAR};
+use c::{TICKIT_TERM_MOUSEMODE_OFF,TICKIT_TERM_MOUSEMODE_CLICK,TICKIT_TERM_MOUSEMODE_DRAG,TICKIT_TERM_MOUSEMODE_MOVE};
+use c::{TICKIT_PEN_FG,TICKIT_PEN_BG,TICKIT_PEN_BOLD,TICKIT_PEN_UNDER};
+use c::{TICKIT_PEN_ITALIC,TICKIT_PEN_REVERSE,TICKIT_PEN_STRIKE,TICKIT_PEN_ALTFONT};
+use c::{X_TICKIT_PEN_BLINK,X_TICKIT_PEN_DIM,X_TICKIT_PEN_OVERLINE,X_TICKIT_PEN_CONCEAL};
+use TickitPen;
+use TickitPenRGB;
+use {TickitPenColour,IndexColour,RGBColour};
+use TickitRect;
+use {TickitUnderline,DoubleUnderline};
+use {TickitError,InvalidGeometryError};
+
+use super::screen::MockScreen;
+
+static ESC: u8 = 0x1b;
+
+// A numeric parameter, or -1 if it was left empty
+fn parse_num(digits: &[u8]) -> int
+{
+    if digits.is_empty()
+    {
+        return -1;
+    }
+    digits.iter().fold(0, |n, &b| n * 10 + (b - b'0') as int)
+}
+
+// The n'th parameter, with missing or zero values replaced by 'default'
+fn arg(args: &[Vec<int>], n: uint, default: int) -> int
+{
+    match args.get(n)
+    {
+        Some(a) if a[0] > 0 => a[0],
+        _ => default,
+    }
+}
+
+fn is_channel(v: int) -> bool
+{
+    v >= 0 && v <= 255
+}
+
+// The colour of an SGR 38/48/58 at args[*i]: either 38:5:n in one parameter,
+// or 38;5;n (38;2;r;g;b) across several, which *i is moved past.
+fn sgr_colour(args: &[Vec<int>], i: &mut uint) -> Option<TickitPenColour>
+{
+    let a = args[*i].as_slice();
+    let ext: Vec<int> = if a.len() > 1
+    {
+        a.slice_from(1).to_vec()
+    }
+    else
+    {
+        let count = match args.get(*i + 1).map(|b| b[0]) { Some(2) => 4, _ => 2 };
+        let ext = args.slice(cmp::min(*i + 1, args.len()), cmp::min(*i + 1 + count, args.len()))
+            .iter().map(|b| b[0]).collect();
+        *i += count;
+        ext
+    };
+    match ext.as_slice()
+    {
+        [5, idx] if idx >= 0 => Some(IndexColour(idx)),
+        // 38:2::r:g:b has an (empty) colour space id first
+        [2, r, g, b] | [2, _, r, g, b] if is_channel(r) && is_channel(g) && is_channel(b) =>
+            Some(RGBColour(TickitPenRGB::new(r as u8, g as u8, b as u8))),
+        _ => None,
+    }
+}
+
+// The length of the longest prefix of 'bytes' that doesn't end partway
+// through a UTF-8 sequence.
+fn utf8_complete_len(bytes: &[u8]) -> uint
+{
+    let len = bytes.len();
+    for back in range(1, cmp::min(len, 4) + 1)
+    {
+        let b = bytes[len - back];
+        if b & 0xc0 == 0x80
+        {
+            continue;
+        }
+        let need = if b < 0x80 { 1 } else if b < 0xe0 { 2 } else if b < 0xf0 { 3 } else { 4 };
+        return if back < need { len - back } else { len };
+    }
+    len
+}
+
+pub struct VirtualTerm
+{
+    screen: MockScreen,
+    // the normal screen, while the alternate one is shown
+    primary: Option<MockScreen>,
+    // an incomplete sequence left over from the last feed()
+    pending: Vec<u8>,
+    // scroll region and DECSLRM margins; bottom and right exclusive
+    scroll_top: int,
+    scroll_bottom: int,
+    margin_left: int,
+    margin_right: int,
+    saved_cursor: (int, int),
+    cursor_visible: bool,
+    cursor_blink: bool,
+    cursor_shape: TickitTermCursorShape,
+    mouse_mode: TickitTermMouseMode,
+    keypad_app: bool,
+    title: String,
+    icon: String,
+}
+
+impl VirtualTerm
+{
+    pub fn new(lines: int, cols: int) -> Result<VirtualTerm, TickitError>
+    {
+        if lines < 0 || cols < 0
+        {
+            return Err(InvalidGeometryError(lines, cols));
+        }
+        Ok(VirtualTerm
+        {
+            screen: MockScreen::new(lines, cols),
+            primary: None,
+            pending: Vec::new(),
+            scroll_top: 0,
+            scroll_bottom: lines,
+            margin_left: 0,
+            margin_right: cols,
+            saved_cursor: (0, 0),
+            cursor_visible: true,
+            cursor_blink: false,
+            cursor_shape: TICKIT_TERM_CURSORSHAPE_BLOCK,
+            mouse_mode: TICKIT_TERM_MOUSEMODE_OFF,
+            keypad_app: false,
+            title: String::new(),
+            icon: String::new(),
+        })
+    }
+}
+
+impl VirtualTerm
+{
+    pub fn get_size(&self) -> (uint, uint)
+    {
+        let (lines, cols) = self.screen.get_size();
+        (lines as uint, cols as uint)
+    }
+
+    /// Change the size of both screens; this resets the scroll region.
+    pub fn resize(&mut self, newlines: uint, newcols: uint)
+    {
+        let (lines, cols) = (newlines as int, newcols as int);
+        self.screen.resize(lines, cols);
+        match self.primary
+        {
+            Some(ref mut primary) => primary.resize(lines, cols),
+            None => {}
+        }
+        self.scroll_top = 0;
+        self.scroll_bottom = lines;
+        self.margin_left = 0;
+        self.margin_right = cols;
+        self.clamp_cursor();
+    }
+
+    pub fn get_display_text(&self, line: uint, col: uint, width: uint) -> String
+    {
+        self.screen.get_display_text(line as int, col as int, width as int)
+    }
+    pub fn get_display_pen(&self, line: int, col: int) -> TickitPen
+    {
+        self.screen.get_display_pen(line, col)
+    }
+    pub fn get_position(&self) -> (uint, uint)
+    {
+        (self.screen.line as uint, self.screen.col as uint)
+    }
+
+    pub fn is_altscreen(&self) -> bool
+    {
+        self.primary.is_some()
+    }
+    pub fn is_cursor_visible(&self) -> bool
+    {
+        self.cursor_visible
+    }
+    pub fn is_cursor_blink(&self) -> bool
+    {
+        self.cursor_blink
+    }
+    pub fn get_cursor_shape(&self) -> TickitTermCursorShape
+    {
+        self.cursor_shape
+    }
+    pub fn get_mouse_mode(&self) -> TickitTermMouseMode
+    {
+        self.mouse_mode
+    }
+    pub fn is_keypad_app(&self) -> bool
+    {
+        self.keypad_app
+    }
+    pub fn get_title(&self) -> &str
+    {
+        self.title.as_slice()
+    }
+    pub fn get_icon(&self) -> &str
+    {
+        self.icon.as_slice()
+    }
+}
+
+impl VirtualTerm
+{
+    /// Interpret terminal output. Sequences may be split across calls.
+    pub fn feed(&mut self, bytes: &[u8])
+    {
+        let mut buf = std::mem::replace(&mut self.pending, Vec::new());
+        buf.push_all(bytes);
+
+        let mut i = 0;
+        while i < buf.len()
+        {
+            let rest = buf.slice_from(i);
+            let used = match rest[0]
+            {
+                b if b == ESC => self.escape(rest),
+                b if b < 0x20 || b == 0x7f =>
+                {
+                    self.control(b);
+                    1
+                }
+                _ => self.text(rest),
+            };
+            if used == 0
+            {
+                break;
+            }
+            i += used;
+        }
+        self.pending = buf.slice_from(i).to_vec();
+    }
+
+    fn clamp_cursor(&mut self)
+    {
+        let (lines, cols) = self.screen.get_size();
+        self.screen.line = cmp::max(0, cmp::min(self.screen.line, lines - 1));
+        self.screen.col = cmp::max(0, cmp::min(self.screen.col, cols - 1));
+    }
+
+    fn goto(&mut self, line: int, col: int)
+    {
+        self.screen.line = line;
+        self.screen.col = col;
+        self.clamp_cursor();
+    }
+
+    // The scroll region, limited to the left and right margins
+    fn region(&self) -> TickitRect
+    {
+        TickitRect::init_sized(self.scroll_top, self.margin_left, self.scroll_bottom - self.scroll_top, self.margin_right - self.margin_left)
+    }
+
+    fn index(&mut self)
+    {
+        let (lines, _) = self.screen.get_size();
+        if self.screen.line == self.scroll_bottom - 1
+        {
+            let region = self.region();
+            self.screen.scrollrect(&region, 1, 0);
+        }
+        else if self.screen.line < lines - 1
+        {
+            self.screen.line += 1;
+        }
+    }
+
+    fn reverse_index(&mut self)
+    {
+        if self.screen.line == self.scroll_top
+        {
+            let region = self.region();
+            self.screen.scrollrect(&region, -1, 0);
+        }
+        else if self.screen.line > 0
+        {
+            self.screen.line -= 1;
+        }
+    }
+
+    fn control(&mut self, b: u8)
+    {
+        match b
+        {
+            0x08 =>
+            {
+                self.screen.col = cmp::max(0, self.screen.col - 1);
+            }
+            0x09 =>
+            {
+                let (_, cols) = self.screen.get_size();
+                self.screen.col = cmp::min(cols - 1, (self.screen.col / 8 + 1) * 8);
+            }
+            0x0a | 0x0b | 0x0c =>
+            {
+                self.index();
+            }
+            0x0d =>
+            {
+                self.screen.col = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, bytes: &[u8]) -> uint
+    {
+        let run = bytes.iter().position(|&b| b < 0x20 || b == 0x7f).unwrap_or(bytes.len());
+        // only the end of the buffer can be waiting for more bytes
+        let end = if run == bytes.len() { utf8_complete_len(bytes) } else { run };
+        if end == 0
+        {
+            return 0;
+        }
+        self.screen.print(std::str::from_utf8(bytes.slice_to(end)).unwrap_or("\ufffd"));
+        end
+    }
+
+    fn escape(&mut self, bytes: &[u8]) -> uint
+    {
+        if bytes.len() < 2
+        {
+            return 0;
+        }
+        match bytes[1]
+        {
+            b'[' => self.csi(bytes),
+            b']' => self.osc(bytes),
+            b'P' | b'^' | b'_' =>
+            {
+                // DCS, PM and APC strings; nothing we care about
+                match string_end(bytes)
+                {
+                    Some((_, end)) => end,
+                    None => 0,
+                }
+            }
+            b'(' | b')' | b'*' | b'+' | b'#' =>
+            {
+                if bytes.len() < 3 { 0 } else { 3 }
+            }
+            b'7' =>
+            {
+                self.saved_cursor = (self.screen.line, self.screen.col);
+                2
+            }
+            b'8' =>
+            {
+                let (line, col) = self.saved_cursor;
+                self.goto(line, col);
+                2
+            }
+            b'=' =>
+            {
+                self.keypad_app = true;
+                2
+            }
+            b'>' =>
+            {
+                self.keypad_app = false;
+                2
+            }
+            b'D' =>
+            {
+                self.index();
+                2
+            }
+            b'E' =>
+            {
+                self.index();
+                self.screen.col = 0;
+                2
+            }
+            b'M' =>
+            {
+                self.reverse_index();
+                2
+            }
+            _ => 2,
+        }
+    }
+
+    fn osc(&mut self, bytes: &[u8]) -> uint
+    {
+        let (body_end, end) = match string_end(bytes)
+        {
+            Some(e) => e,
+            None => return 0,
+        };
+        let body = bytes.slice(2, body_end);
+        let semi = match body.iter().position(|&b| b == b';')
+        {
+            Some(p) => p,
+            None => return end,
+        };
+        let text = std::str::from_utf8(body.slice_from(semi + 1)).unwrap_or("").to_string();
+        match parse_num(body.slice_to(semi))
+        {
+            0 =>
+            {
+                self.icon = text.clone();
+                self.title = text;
+            }
+            1 => self.icon = text,
+            2 => self.title = text,
+            _ => {}
+        }
+        end
+    }
+
+    fn csi(&mut self, bytes: &[u8]) -> uint
+    {
+        let mut i = 2;
+        while i < bytes.len() && bytes[i] >= 0x30 && bytes[i] <= 0x3f
+        {
+            i += 1;
+        }
+        let params_end = i;
+        while i < bytes.len() && bytes[i] >= 0x20 && bytes[i] <= 0x2f
+        {
+            i += 1;
+        }
+        if i >= bytes.len()
+        {
+            return 0;
+        }
+
+        let mut params = bytes.slice(2, params_end);
+        let private = match params.head()
+        {
+            Some(&b) if b >= b'<' && b <= b'?' =>
+            {
+                params = params.slice_from(1);
+                Some(b)
+            }
+            _ => None,
+        };
+        let args: Vec<Vec<int>> = params.split(|&b| b == b';').map(
+            |p| p.split(|&b| b == b':').map(parse_num).collect()
+        ).collect();
+
+        self.dispatch(private, bytes.slice(params_end, i), bytes[i], args.as_slice());
+        i + 1
+    }
+
+    fn dispatch(&mut self, private: Option<u8>, inter: &[u8], final_: u8, args: &[Vec<int>])
+    {
+        let (lines, cols) = self.screen.get_size();
+        let (line, col) = (self.screen.line, self.screen.col);
+
+        match (private, inter, final_)
+        {
+            (None, [], b'A') => self.goto(line - arg(args, 0, 1), col),
+            (None, [], b'B') => self.goto(line + arg(args, 0, 1), col),
+            (None, [], b'C') => self.goto(line, col + arg(args, 0, 1)),
+            (None, [], b'D') => self.goto(line, col - arg(args, 0, 1)),
+            (None, [], b'G') | (None, [], b'`') => self.goto(line, arg(args, 0, 1) - 1),
+            (None, [], b'd') => self.goto(arg(args, 0, 1) - 1, col),
+            (None, [], b'H') | (None, [], b'f') => self.goto(arg(args, 0, 1) - 1, arg(args, 1, 1) - 1),
+            (None, [], b'J') =>
+            {
+                let (from, to) = match arg(args, 0, 0)
+                {
+                    0 =>
+                    {
+                        self.screen.erase(line, col, cols - col);
+                        (line + 1, lines)
+                    }
+                    1 =>
+                    {
+                        self.screen.erase(line, 0, col + 1);
+                        (0, line)
+                    }
+                    _ => (0, lines),
+                };
+                for l in range(from, to)
+                {
+                    self.screen.erase(l, 0, cols);
+                }
+            }
+            (None, [], b'K') =>
+            {
+                match arg(args, 0, 0)
+                {
+                    0 => self.screen.erase(line, col, cols - col),
+                    1 => self.screen.erase(line, 0, col + 1),
+                    _ => self.screen.erase(line, 0, cols),
+                }
+            }
+            (None, [], b'X') => self.screen.erase(line, col, arg(args, 0, 1)),
+            (None, [], b'@') | (None, [], b'P') =>
+            {
+                let n = arg(args, 0, 1);
+                let rect = TickitRect::init_sized(line, col, 1, self.margin_right - col);
+                self.screen.scrollrect(&rect, 0, if final_ == b'P' { n } else { -n });
+            }
+            (None, [], b'L') | (None, [], b'M') =>
+            {
+                if line >= self.scroll_top && line < self.scroll_bottom
+                {
+                    let n = arg(args, 0, 1);
+                    let rect = TickitRect::init_sized(line, self.margin_left, self.scroll_bottom - line, self.margin_right - self.margin_left);
+                    self.screen.scrollrect(&rect, if final_ == b'M' { n } else { -n }, 0);
+                }
+            }
+            (None, [], b'S') | (None, [], b'T') =>
+            {
+                let n = arg(args, 0, 1);
+                let region = self.region();
+                self.screen.scrollrect(&region, if final_ == b'S' { n } else { -n }, 0);
+            }
+            (None, [], b'm') => self.sgr(args),
+            (None, [], b'r') =>
+            {
+                let top = arg(args, 0, 1) - 1;
+                let bottom = cmp::min(arg(args, 1, lines), lines);
+                if top < bottom
+                {
+                    self.scroll_top = top;
+                    self.scroll_bottom = bottom;
+                }
+                self.goto(0, 0);
+            }
+            (None, [], b's') =>
+            {
+                // DECSLRM; a bare one is also the old save-cursor
+                if args.len() == 1 && args[0][0] == -1
+                {
+                    self.saved_cursor = (line, col);
+                }
+                let left = arg(args, 0, 1) - 1;
+                let right = cmp::min(arg(args, 1, cols), cols);
+                if left < right
+                {
+                    self.margin_left = left;
+                    self.margin_right = right;
+                }
+            }
+            (None, [], b'u') =>
+            {
+                let (line, col) = self.saved_cursor;
+                self.goto(line, col);
+            }
+            (None, [b'\''], b'}') | (None, [b'\''], b'~') =>
+            {
+                // DECIC / DECDC
+                if line >= self.scroll_top && line < self.scroll_bottom
+                {
+                    let n = arg(args, 0, 1);
+                    let rect = TickitRect::init_sized(self.scroll_top, col, self.scroll_bottom - self.scroll_top, self.margin_right - col);
+                    self.screen.scrollrect(&rect, 0, if final_ == b'~' { n } else { -n });
+                }
+            }
+            (None, [b' '], b'q') =>
+            {
+                // DECSCUSR
+                let n = arg(args, 0, 1);
+                self.cursor_blink = n % 2 == 1;
+                self.cursor_shape = match n
+                {
+                    1 | 2 => TICKIT_TERM_CURSORSHAPE_BLOCK,
+                    3 | 4 => TICKIT_TERM_CURSORSHAPE_UNDER,
+                    _ => TICKIT_TERM_CURSORSHAPE_LEFT_BAR,
+                };
+            }
+            (Some(b'?'), [], b'h') | (Some(b'?'), [], b'l') =>
+            {
+                for a in args.iter()
+                {
+                    self.decmode(a[0], final_ == b'h');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn decmode(&mut self, mode: int, on: bool)
+    {
+        match mode
+        {
+            12 => self.cursor_blink = on,
+            25 => self.cursor_visible = on,
+            69 if !on =>
+            {
+                let (_, cols) = self.screen.get_size();
+                self.margin_left = 0;
+                self.margin_right = cols;
+            }
+            47 | 1047 | 1049 => self.set_altscreen(on),
+            1000 | 1002 | 1003 =>
+            {
+                self.mouse_mode = match (on, mode)
+                {
+                    (false, _) => TICKIT_TERM_MOUSEMODE_OFF,
+                    (true, 1000) => TICKIT_TERM_MOUSEMODE_CLICK,
+                    (true, 1002) => TICKIT_TERM_MOUSEMODE_DRAG,
+                    (true, _) => TICKIT_TERM_MOUSEMODE_MOVE,
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn set_altscreen(&mut self, on: bool)
+    {
+        if on == self.primary.is_some()
+        {
+            return;
+        }
+        if on
+        {
+            let (lines, cols) = self.screen.get_size();
+            let mut alt = MockScreen::new(lines, cols);
+            alt.line = self.screen.line;
+            alt.col = self.screen.col;
+            alt.pen = self.screen.pen.clone();
+            self.primary = Some(std::mem::replace(&mut self.screen, alt));
+        }
+        else
+        {
+            self.screen = self.primary.take().unwrap();
+        }
+    }
+
+    fn sgr(&mut self, args: &[Vec<int>])
+    {
+        let pen = &mut self.screen.pen;
+        let mut i = 0;
+        while i < args.len()
+        {
+            let a = args[i].as_slice();
+            match cmp::max(a[0], 0)
+            {
+                0 => pen.clear(),
+                1 => pen.set_bool_attr(TICKIT_PEN_BOLD, true),
+                2 => pen.set_bool_attr(X_TICKIT_PEN_DIM, true),
+                3 => pen.set_bool_attr(TICKIT_PEN_ITALIC, true),
+                4 if a.len() > 1 => match TickitUnderline::from_sgr(a[1])
+                {
+                    Some(style) => pen.set_under_style(style),
+                    None => {}
+                },
+                4 => pen.set_bool_attr(TICKIT_PEN_UNDER, true),
+                5 | 6 => pen.set_bool_attr(X_TICKIT_PEN_BLINK, true),
+                7 => pen.set_bool_attr(TICKIT_PEN_REVERSE, true),
+                8 => pen.set_bool_attr(X_TICKIT_PEN_CONCEAL, true),
+                9 => pen.set_bool_attr(TICKIT_PEN_STRIKE, true),
+                10 => pen.clear_attr(TICKIT_PEN_ALTFONT),
+                n if n >= 11 && n <= 19 => pen.set_int_attr(TICKIT_PEN_ALTFONT, n - 10),
+                22 =>
+                {
+                    pen.clear_attr(TICKIT_PEN_BOLD);
+                    pen.clear_attr(X_TICKIT_PEN_DIM);
+                }
+                23 => pen.clear_attr(TICKIT_PEN_ITALIC),
+                21 => pen.set_under_style(DoubleUnderline),
+                24 => pen.clear_attr(TICKIT_PEN_UNDER),
+                25 => pen.clear_attr(X_TICKIT_PEN_BLINK),
+                27 => pen.clear_attr(TICKIT_PEN_REVERSE),
+                28 => pen.clear_attr(X_TICKIT_PEN_CONCEAL),
+                29 => pen.clear_attr(TICKIT_PEN_STRIKE),
+                53 => pen.set_bool_attr(X_TICKIT_PEN_OVERLINE, true),
+                55 => pen.clear_attr(X_TICKIT_PEN_OVERLINE),
+                n if n >= 30 && n <= 37 => pen.set_colour_attr(TICKIT_PEN_FG, n - 30),
+                n if n >= 40 && n <= 47 => pen.set_colour_attr(TICKIT_PEN_BG, n - 40),
+                n if n >= 90 && n <= 97 => pen.set_colour_attr(TICKIT_PEN_FG, n - 90 + 8),
+                n if n >= 100 && n <= 107 => pen.set_colour_attr(TICKIT_PEN_BG, n - 100 + 8),
+                39 => pen.clear_attr(TICKIT_PEN_FG),
+                49 => pen.clear_attr(TICKIT_PEN_BG),
+                n if n == 38 || n == 48 =>
+                {
+                    let attr = if n == 38 { TICKIT_PEN_FG } else { TICKIT_PEN_BG };
+                    match sgr_colour(args, &mut i)
+                    {
+                        Some(colour) => pen.set_colour(attr, colour),
+                        None => {}
+                    }
+                }
+                58 => match sgr_colour(args, &mut i)
+                {
+                    Some(colour) => pen.set_under_colour(colour),
+                    None => {}
+                },
+                59 => pen.clear_under_colour(),
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+}
+
+// For a string-type escape (OSC, DCS, ...), the offset of its terminator (BEL
+// or ST) and of the byte after it.
+fn string_end(bytes: &[u8]) -> Option<(uint, uint)>
+{
+    for i in range(2, bytes.len())
+    {
+        if bytes[i] == 0x07
+        {
+            return Some((i, i + 1));
+        }
+        if bytes[i] == ESC && i + 1 < bytes.len() && bytes[i + 1] == b'\\'
+        {
+            return Some((i, i + 2));
+        }
+    }
+    None
+}
@@ -0,0 +1,194 @@
+// Pens as text, for config files: whitespace-separated attributes, each a
+// bare name (a bool turned on) or name:value, e.g.
+//
+//     fg:red bg:#303030 b u:curly uc:208 af:1
+//
+// Names are those of TickitPenAttr::attrname, plus uc for the underline
+// colour. Colours take whatever set_colour_attr_desc does, or "default";
+// bools take 0 or 1, and u an underline style name too. Show writes the
+// same format, which parse reads back.
+
+use std::fmt;
+
+use c;
+use c::TickitPenAttr;
+use c::{TICKIT_PENTYPE_BOOL,TICKIT_PENTYPE_INT,TICKIT_PENTYPE_COLOUR};
+use c::TICKIT_PEN_FG;
+use TickitPen;
+use {BoolValue,IntValue,ColourValue};
+use {TickitPenColour,IndexColour,RGBColour};
+use {TickitUnderline,NoUnderline,SingleUnderline};
+use {TickitError,PenSpecError};
+
+static UNDER_COLOUR_NAME: &'static str = "uc";
+
+// Each whitespace-separated token, with its byte offset.
+fn tokens(spec: &str) -> Vec<(uint, &str)>
+{
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, ch) in spec.char_indices()
+    {
+        match (ch.is_whitespace(), start)
+        {
+            (true, Some(s)) =>
+            {
+                out.push((s, spec.slice(s, i)));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    match start
+    {
+        Some(s) => out.push((s, spec.slice_from(s))),
+        None => {}
+    }
+    out
+}
+
+fn parse_colour(desc: &str) -> Option<TickitPenColour>
+{
+    if desc == "default"
+    {
+        return Some(IndexColour(-1));
+    }
+    let mut tmp = TickitPen::new();
+    if tmp.set_colour_attr_desc(TICKIT_PEN_FG, desc)
+    {
+        tmp.get_colour(TICKIT_PEN_FG)
+    }
+    else
+    {
+        None
+    }
+}
+
+fn parse_bool(value: Option<&str>) -> Option<bool>
+{
+    match value
+    {
+        None | Some("1") => Some(true),
+        Some("0") => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_under(value: Option<&str>) -> Option<TickitUnderline>
+{
+    match parse_bool(value)
+    {
+        Some(true) => Some(SingleUnderline),
+        Some(false) => Some(NoUnderline),
+        None => value.and_then(|v| TickitUnderline::from_name(v)),
+    }
+}
+
+impl TickitPen
+{
+    /// Build a pen from a spec such as `"fg:red bg:blue b u:double"`. The
+    /// error names the first token that doesn't parse.
+    pub fn parse(spec: &str) -> Result<TickitPen, TickitError>
+    {
+        let mut pen = TickitPen::new();
+        for (offset, token) in tokens(spec).into_iter()
+        {
+            let (name, value) = match token.find(':')
+            {
+                Some(i) => (token.slice_to(i), Some(token.slice_from(i + 1))),
+                None => (token, None),
+            };
+            let bad = |why: &'static str| PenSpecError(offset, token.to_string(), why);
+
+            if name == UNDER_COLOUR_NAME
+            {
+                match value.and_then(|v| parse_colour(v))
+                {
+                    Some(colour) => pen.set_under_colour(colour),
+                    None if value.is_none() => return Err(bad("missing value")),
+                    None => return Err(bad("bad colour")),
+                }
+                continue;
+            }
+
+            let attr = match TickitPenAttr::lookup_attr_opt(name)
+            {
+                Some(attr) => attr,
+                None => return Err(bad("unknown attribute")),
+            };
+            match attr.attrtype()
+            {
+                TICKIT_PENTYPE_BOOL if attr == c::TICKIT_PEN_UNDER => match parse_under(value)
+                {
+                    Some(style) => pen.set_under_style(style),
+                    None => return Err(bad("bad underline style")),
+                },
+                TICKIT_PENTYPE_BOOL => match parse_bool(value)
+                {
+                    Some(v) => pen.set_bool_attr(attr, v),
+                    None => return Err(bad("bad boolean")),
+                },
+                TICKIT_PENTYPE_INT => match value.and_then(|v| from_str::<int>(v))
+                {
+                    Some(v) => pen.set_int_attr(attr, v),
+                    None if value.is_none() => return Err(bad("missing value")),
+                    None => return Err(bad("bad number")),
+                },
+                TICKIT_PENTYPE_COLOUR => match value.and_then(|v| parse_colour(v))
+                {
+                    Some(colour) => pen.set_colour(attr, colour),
+                    None if value.is_none() => return Err(bad("missing value")),
+                    None => return Err(bad("bad colour")),
+                },
+            }
+        }
+        Ok(pen)
+    }
+}
+
+fn write_colour(f: &mut fmt::Formatter, colour: TickitPenColour) -> fmt::Result
+{
+    match colour
+    {
+        IndexColour(idx) if idx < 0 => write!(f, ":default"),
+        IndexColour(idx) => write!(f, ":{}", idx),
+        RGBColour(rgb) => write!(f, ":{}", rgb),
+    }
+}
+
+impl fmt::Show for TickitPen
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let mut sep = "";
+        for (attr, value) in self.attrs()
+        {
+            try!(write!(f, "{}{}", sep, attr.attrname()));
+            sep = " ";
+            match value
+            {
+                BoolValue(_) if attr == c::TICKIT_PEN_UNDER => match self.get_under_style()
+                {
+                    NoUnderline => try!(write!(f, ":0")),
+                    SingleUnderline => {}
+                    style => try!(write!(f, ":{}", style.name())),
+                },
+                BoolValue(true) => {}
+                BoolValue(false) => try!(write!(f, ":0")),
+                IntValue(v) => try!(write!(f, ":{}", v)),
+                ColourValue(colour) => try!(write_colour(f, colour)),
+            }
+        }
+        match self.get_under_colour()
+        {
+            Some(colour) =>
+            {
+                try!(write!(f, "{}{}", sep, UNDER_COLOUR_NAME));
+                try!(write_colour(f, colour));
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+// A pen as plain data, for when a TickitPen (a libtickit object behind a
+// pointer) is in the way: as a map key, compared with ==, copied into
+// cells, or sent to another thread. It holds everything a TickitPen does,
+// so converting either way loses nothing.
+
+use c;
+use TickitPen;
+use TickitPenColour;
+use TickitUnderline;
+
+/// Each field is None where the pen leaves the attribute unset.
+#[deriving(PartialEq, Eq, Hash, Clone, Show)]
+pub struct TickitPlainPen
+{
+    pub fg: Option<TickitPenColour>,
+    pub bg: Option<TickitPenColour>,
+    pub bold: Option<bool>,
+    /// TICKIT_PEN_UNDER; NoUnderline is the attribute set false.
+    pub under: Option<TickitUnderline>,
+    pub under_colour: Option<TickitPenColour>,
+    pub italic: Option<bool>,
+    pub reverse: Option<bool>,
+    pub strike: Option<bool>,
+    pub altfont: Option<int>,
+    pub blink: Option<bool>,
+    pub dim: Option<bool>,
+    pub overline: Option<bool>,
+    pub conceal: Option<bool>,
+}
+
+impl TickitPlainPen
+{
+    pub fn new() -> TickitPlainPen
+    {
+        TickitPlainPen{
+            fg: None, bg: None, bold: None, under: None, under_colour: None,
+            italic: None, reverse: None, strike: None, altfont: None,
+            blink: None, dim: None, overline: None, conceal: None,
+        }
+    }
+
+    pub fn from_pen(pen: &TickitPen) -> TickitPlainPen
+    {
+        TickitPlainPen{
+            fg: pen.get_colour(c::TICKIT_PEN_FG),
+            bg: pen.get_colour(c::TICKIT_PEN_BG),
+            bold: pen.maybe_get_bool_attr(c::TICKIT_PEN_BOLD),
+            under: pen.maybe_get_under_style(),
+            under_colour: pen.get_under_colour(),
+            italic: pen.maybe_get_bool_attr(c::TICKIT_PEN_ITALIC),
+            reverse: pen.maybe_get_bool_attr(c::TICKIT_PEN_REVERSE),
+            strike: pen.maybe_get_bool_attr(c::TICKIT_PEN_STRIKE),
+            altfont: pen.maybe_get_int_attr(c::TICKIT_PEN_ALTFONT),
+            blink: pen.maybe_get_bool_attr(c::X_TICKIT_PEN_BLINK),
+            dim: pen.maybe_get_bool_attr(c::X_TICKIT_PEN_DIM),
+            overline: pen.maybe_get_bool_attr(c::X_TICKIT_PEN_OVERLINE),
+            conceal: pen.maybe_get_bool_attr(c::X_TICKIT_PEN_CONCEAL),
+        }
+    }
+
+    pub fn to_pen(&self) -> TickitPen
+    {
+        let mut pen = TickitPen::new();
+        for &(attr, colour) in [(c::TICKIT_PEN_FG, self.fg), (c::TICKIT_PEN_BG, self.bg)].iter()
+        {
+            match colour
+            {
+                Some(colour) => pen.set_colour(attr, colour),
+                None => {}
+            }
+        }
+        let bools = [
+            (c::TICKIT_PEN_BOLD, self.bold),
+            (c::TICKIT_PEN_ITALIC, self.italic),
+            (c::TICKIT_PEN_REVERSE, self.reverse),
+            (c::TICKIT_PEN_STRIKE, self.strike),
+            (c::X_TICKIT_PEN_BLINK, self.blink),
+            (c::X_TICKIT_PEN_DIM, self.dim),
+            (c::X_TICKIT_PEN_OVERLINE, self.overline),
+            (c::X_TICKIT_PEN_CONCEAL, self.conceal),
+        ];
+        for &(attr, value) in bools.iter()
+        {
+            match value
+            {
+                Some(value) => pen.set_bool_attr(attr, value),
+                None => {}
+            }
+        }
+        match self.under
+        {
+            Some(style) => pen.set_under_style(style),
+            None => {}
+        }
+        match self.under_colour
+        {
+            Some(colour) => pen.set_under_colour(colour),
+            None => {}
+        }
+        match self.altfont
+        {
+            Some(font) => pen.set_int_attr(c::TICKIT_PEN_ALTFONT, font),
+            None => {}
+        }
+        pen
+    }
+}
@@ -0,0 +1,886 @@
+// A native implementation of TickitRenderBuffer.
+//
+// This mirrors the cell/span model of libtickit's renderbuffer.c, so that
+// it renders the exact same sequence of terminal operations, but no cell
+// write ever crosses into C.
+
+use std::cmp;
+
+use c::{TickitLineStyle,TickitLineCaps};
+use c::{X_TICKIT_LINE_NONE,TICKIT_LINE_SINGLE,TICKIT_LINE_DOUBLE,TICKIT_LINE_THICK};
+use c::{TICKIT_LINECAP_START,TICKIT_LINECAP_END};
+
+use StringPos;
+use TickitPen;
+use TickitRect;
+use TickitRenderBufferLineMask;
+use TickitRenderBufferSpanInfo;
+use TickitTerm;
+use {TickitError,InvalidGeometryError};
+use {SkipSpan,TextSpan};
+
+static NORTH_SHIFT: uint = 0;
+static EAST_SHIFT: uint = 2;
+static SOUTH_SHIFT: uint = 4;
+static WEST_SHIFT: uint = 6;
+
+// Indexed by a cell's line mask; two bits of TickitLineStyle per direction.
+// Combinations that Unicode has no glyph for degrade toward single lines.
+static LINECHARS: [char, ..256] =
+[
+    '\u0020', '\u2575', '\u2575', '\u2579', '\u2576', '\u2514', '\u2559', '\u2516',
+    '\u2576', '\u2558', '\u255a', '\u2558', '\u257a', '\u2515', '\u2515', '\u2517',
+    '\u2577', '\u2502', '\u2502', '\u257f', '\u250c', '\u251c', '\u251c', '\u251e',
+    '\u2552', '\u255e', '\u255e', '\u255e', '\u250d', '\u251d', '\u251d', '\u2521',
+    '\u2577', '\u2502', '\u2551', '\u257f', '\u2553', '\u251c', '\u255f', '\u251e',
+    '\u2554', '\u255e', '\u2560', '\u255e', '\u2553', '\u251d', '\u255f', '\u2521',
+    '\u257b', '\u257d', '\u257d', '\u2503', '\u250e', '\u251f', '\u251f', '\u2520',
+    '\u250e', '\u251f', '\u251f', '\u2520', '\u250f', '\u2522', '\u2522', '\u2523',
+    '\u2574', '\u2518', '\u255c', '\u251a', '\u2500', '\u2534', '\u2568', '\u2538',
+    '\u2500', '\u2534', '\u2568', '\u2538', '\u257c', '\u2536', '\u2536', '\u253a',
+    '\u2510', '\u2524', '\u2524', '\u2526', '\u252c', '\u253c', '\u253c', '\u2540',
+    '\u252c', '\u253c', '\u253c', '\u2540', '\u252e', '\u253e', '\u253e', '\u2544',
+    '\u2556', '\u2524', '\u2562', '\u2526', '\u2565', '\u253c', '\u256b', '\u2540',
+    '\u2565', '\u253c', '\u256b', '\u2540', '\u2565', '\u253e', '\u256b', '\u2544',
+    '\u2512', '\u2527', '\u2527', '\u2528', '\u2530', '\u2541', '\u2541', '\u2542',
+    '\u2530', '\u2541', '\u2541', '\u2542', '\u2532', '\u2546', '\u2546', '\u254a',
+    '\u2574', '\u255b', '\u255d', '\u255b', '\u2500', '\u2534', '\u2568', '\u2538',
+    '\u2550', '\u2567', '\u2569', '\u2567', '\u257c', '\u2536', '\u2536', '\u253a',
+    '\u2555', '\u2561', '\u2561', '\u2561', '\u252c', '\u253c', '\u253c', '\u2540',
+    '\u2564', '\u256a', '\u256a', '\u256a', '\u252e', '\u253e', '\u253e', '\u2544',
+    '\u2557', '\u2561', '\u2563', '\u2561', '\u2565', '\u253c', '\u256b', '\u2540',
+    '\u2566', '\u256a', '\u256c', '\u256a', '\u2565', '\u253e', '\u256b', '\u2544',
+    '\u2555', '\u2561', '\u2561', '\u2528', '\u2530', '\u2541', '\u2541', '\u2542',
+    '\u2564', '\u256a', '\u256a', '\u256a', '\u2532', '\u2546', '\u2546', '\u254a',
+    '\u2578', '\u2519', '\u2519', '\u251b', '\u257e', '\u2535', '\u2535', '\u2539',
+    '\u257e', '\u2535', '\u2535', '\u2539', '\u2501', '\u2537', '\u2537', '\u253b',
+    '\u2511', '\u2525', '\u2525', '\u2529', '\u252d', '\u253d', '\u253d', '\u2543',
+    '\u252d', '\u253d', '\u253d', '\u2543', '\u252f', '\u253f', '\u253f', '\u2547',
+    '\u2511', '\u2525', '\u2562', '\u2529', '\u252d', '\u253d', '\u256b', '\u2543',
+    '\u252d', '\u253d', '\u256b', '\u2543', '\u252f', '\u253f', '\u253f', '\u2547',
+    '\u2513', '\u252a', '\u252a', '\u252b', '\u2531', '\u2545', '\u2545', '\u2549',
+    '\u2531', '\u2545', '\u2545', '\u2549', '\u2533', '\u2548', '\u2548', '\u254b',
+];
+
+enum CellState
+{
+    SkipCell,
+    TextCell{idx: uint, offs: int},
+    EraseCell,
+    ContCell{startcol: int},
+    LineCell{mask: u8},
+    CharCell{codepoint: char},
+}
+
+struct Cell
+{
+    state: CellState,
+    cols: int,
+    maskdepth: int,
+    pen: Option<TickitPen>,
+}
+
+struct StackEntry
+{
+    vc: Option<(int, int)>,
+    xlate_line: int,
+    xlate_col: int,
+    clip: Option<TickitRect>,
+    pen: TickitPen,
+    pen_only: bool,
+}
+
+pub struct TickitRenderBuffer
+{
+    lines: int,
+    cols: int,
+    cells: Vec<Vec<Cell>>,
+    vc: Option<(int, int)>,
+    xlate_line: int,
+    xlate_col: int,
+    // None once clipped down to nothing
+    clip: Option<TickitRect>,
+    pen: TickitPen,
+    stack: Vec<StackEntry>,
+    texts: Vec<String>,
+}
+
+fn blank_line(cols: int) -> Vec<Cell>
+{
+    Vec::from_fn(cols as uint, |col|
+    {
+        if col == 0
+        {
+            Cell{state: SkipCell, cols: cols, maskdepth: -1, pen: None}
+        }
+        else
+        {
+            Cell{state: ContCell{startcol: 0}, cols: 0, maskdepth: -1, pen: None}
+        }
+    })
+}
+
+fn str_columns(text: &str) -> int
+{
+    let mut pos = StringPos::zero();
+    StringPos::count(text, &mut pos, None);
+    pos.columns as int
+}
+
+// the slice of 'text' covering 'cols' columns, starting 'offs' columns in
+fn slice_columns<'a>(text: &'a str, offs: int, cols: int) -> &'a str
+{
+    let mut start = StringPos::zero();
+    StringPos::count(text, &mut start, Some(StringPos::limit_columns(offs as uint)));
+    let mut end = start;
+    StringPos::countmore(text, &mut end, Some(StringPos::limit_columns((offs + cols) as uint)));
+    text.slice(start.bytes, end.bytes)
+}
+
+fn line_style(bits: u8) -> TickitLineStyle
+{
+    match bits & 0x03
+    {
+        0 => X_TICKIT_LINE_NONE,
+        1 => TICKIT_LINE_SINGLE,
+        2 => TICKIT_LINE_DOUBLE,
+        _ => TICKIT_LINE_THICK,
+    }
+}
+
+impl TickitRenderBuffer
+{
+    pub fn new(lines: int, cols: int) -> Result<TickitRenderBuffer, TickitError>
+    {
+        if lines < 0 || cols < 0
+        {
+            return Err(InvalidGeometryError(lines, cols));
+        }
+        Ok(TickitRenderBuffer
+        {
+            lines: lines,
+            cols: cols,
+            cells: Vec::from_fn(lines as uint, |_| blank_line(cols)),
+            vc: None,
+            xlate_line: 0,
+            xlate_col: 0,
+            clip: Some(TickitRect::init_sized(0, 0, lines, cols)),
+            pen: TickitPen::new(),
+            stack: Vec::new(),
+            texts: Vec::new(),
+        })
+    }
+}
+
+impl TickitRenderBuffer
+{
+    fn cell(&self, line: int, col: int) -> &Cell
+    {
+        &self.cells[line as uint][col as uint]
+    }
+    fn cell_mut(&mut self, line: int, col: int) -> &mut Cell
+    {
+        &mut self.cells.as_mut_slice()[line as uint].as_mut_slice()[col as uint]
+    }
+
+    // Split any spans crossing either edge of [col, col+cols) so that the
+    // range becomes a single span starting at 'col'.
+    fn make_span(&mut self, line: int, col: int, cols: int)
+    {
+        let end = col + cols;
+
+        // If the following cell is a CONT, it needs to become a new start
+        if end < self.cols
+        {
+            let endstate = self.cell(line, end).state;
+            match endstate
+            {
+                ContCell{startcol: spanstart} =>
+                {
+                    let (spanstate, spanend, spanpen) =
+                    {
+                        let spancell = self.cell(line, spanstart);
+                        (spancell.state, spanstart + spancell.cols, spancell.pen.clone())
+                    };
+                    let newstate = match spanstate
+                    {
+                        SkipCell => SkipCell,
+                        TextCell{idx, offs} => TextCell{idx: idx, offs: offs + end - spanstart},
+                        // what is left of a wide character is just blank
+                        _ => EraseCell,
+                    };
+                    {
+                        let endcell = self.cell_mut(line, end);
+                        endcell.state = newstate;
+                        endcell.cols = spanend - end;
+                        endcell.pen = spanpen;
+                    }
+                    // We know these are already CONT cells
+                    for c in range(end + 1, spanend)
+                    {
+                        self.cell_mut(line, c).state = ContCell{startcol: end};
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // If the initial cell is a CONT, shorten its start
+        let startstate = self.cell(line, col).state;
+        match startstate
+        {
+            ContCell{startcol: beforestart} =>
+            {
+                let before = self.cell_mut(line, beforestart);
+                let splittable = match before.state
+                {
+                    SkipCell | TextCell{..} | EraseCell => true,
+                    _ => false,
+                };
+                if !splittable
+                {
+                    before.state = EraseCell;
+                }
+                before.cols = col - beforestart;
+            }
+            _ => {}
+        }
+
+        for c in range(col, end)
+        {
+            let cell = self.cell_mut(line, c);
+            cell.state = ContCell{startcol: col};
+            cell.pen = None;
+        }
+        self.cell_mut(line, col).cols = cols;
+    }
+
+    // Returns (line, col, cols, startcol) in buffer coordinates, where
+    // 'startcol' is how many columns were clipped off the left.
+    fn xlate_and_clip(&self, line: int, col: int, cols: int) -> Option<(int, int, int, int)>
+    {
+        let line = line + self.xlate_line;
+        let mut col = col + self.xlate_col;
+        let mut cols = cols;
+        let mut startcol = 0;
+
+        let clip = match self.clip
+        {
+            Some(clip) => clip,
+            None => return None,
+        };
+
+        if line < clip.top || line >= clip.bottom() || col >= clip.right()
+        {
+            return None;
+        }
+
+        if col < clip.left
+        {
+            cols -= clip.left - col;
+            startcol += clip.left - col;
+            col = clip.left;
+        }
+        if cols <= 0
+        {
+            return None;
+        }
+
+        if cols > clip.right() - col
+        {
+            cols = clip.right() - col;
+        }
+
+        Some((line, col, cols, startcol))
+    }
+
+    fn merge_pen(&self, direct: Option<&TickitPen>) -> TickitPen
+    {
+        match direct
+        {
+            Some(direct) =>
+            {
+                let mut pen = direct.clone();
+                pen.copy(&self.pen, false);
+                pen
+            }
+            None =>
+            {
+                self.pen.clone()
+            }
+        }
+    }
+
+    // Lay spans of 'mk(startcol)' over every unmasked run of the range.
+    fn put_span(&mut self, line: int, col: int, cols: int, pen: Option<TickitPen>, mk: |int| -> CellState)
+    {
+        let (line, mut col, mut cols, mut startcol) = match self.xlate_and_clip(line, col, cols)
+        {
+            Some(v) => v,
+            None => return,
+        };
+
+        while cols > 0
+        {
+            while cols > 0 && self.cell(line, col).maskdepth > -1
+            {
+                col += 1;
+                cols -= 1;
+                startcol += 1;
+            }
+
+            let mut spanlen = 0;
+            while cols > 0 && self.cell(line, col + spanlen).maskdepth == -1
+            {
+                spanlen += 1;
+                cols -= 1;
+            }
+            if spanlen == 0
+            {
+                break;
+            }
+
+            self.make_span(line, col, spanlen);
+            {
+                let cell = self.cell_mut(line, col);
+                cell.state = mk(startcol);
+                cell.pen = pen.clone();
+            }
+
+            col += spanlen;
+            startcol += spanlen;
+        }
+    }
+
+    fn put_text(&mut self, line: int, col: int, text: &str, pen: Option<&TickitPen>) -> int
+    {
+        let cols = str_columns(text);
+        if cols == 0
+        {
+            return 0;
+        }
+
+        let idx = self.texts.len();
+        self.texts.push(text.to_string());
+        let pen = self.merge_pen(pen);
+        self.put_span(line, col, cols, Some(pen), |offs| TextCell{idx: idx, offs: offs});
+        cols
+    }
+
+    fn put_erase(&mut self, line: int, col: int, cols: int, pen: Option<&TickitPen>)
+    {
+        let pen = self.merge_pen(pen);
+        self.put_span(line, col, cols, Some(pen), |_| EraseCell);
+    }
+
+    fn put_char(&mut self, line: int, col: int, codepoint: char, pen: Option<&TickitPen>) -> int
+    {
+        let cols = str_columns(String::from_char(1, codepoint).as_slice());
+        let pen = self.merge_pen(pen);
+        self.put_span(line, col, cols, Some(pen), |_| CharCell{codepoint: codepoint});
+        cols
+    }
+
+    fn linecell(&mut self, line: int, col: int, bits: u8, pen: Option<&TickitPen>)
+    {
+        let (line, col) = match self.xlate_and_clip(line, col, 1)
+        {
+            Some((line, col, _, _)) => (line, col),
+            None => return,
+        };
+        if self.cell(line, col).maskdepth > -1
+        {
+            return;
+        }
+
+        let pen = self.merge_pen(pen);
+        let state = self.cell(line, col).state;
+        let mask = match state
+        {
+            LineCell{mask} => mask,
+            _ =>
+            {
+                self.make_span(line, col, 1);
+                0
+            }
+        };
+
+        let cell = self.cell_mut(line, col);
+        cell.state = LineCell{mask: mask | bits};
+        cell.pen = Some(pen);
+    }
+}
+
+impl TickitRenderBuffer
+{
+    pub fn get_size(&self) -> (int, int)
+    {
+        (self.lines, self.cols)
+    }
+
+    pub fn translate(&mut self, downward: int, rightward: int)
+    {
+        self.xlate_line += downward;
+        self.xlate_col += rightward;
+    }
+    pub fn clip(&mut self, rect: &TickitRect)
+    {
+        let other = TickitRect::init_sized(rect.top + self.xlate_line, rect.left + self.xlate_col, rect.lines, rect.cols);
+        self.clip = match self.clip
+        {
+            Some(clip) => clip.intersect(&other),
+            None => None,
+        };
+    }
+    pub fn mask(&mut self, mask: &TickitRect)
+    {
+        let mut top = mask.top + self.xlate_line;
+        let mut left = mask.left + self.xlate_col;
+        let mut lines = mask.lines;
+        let mut cols = mask.cols;
+
+        if top < 0
+        {
+            lines += top;
+            top = 0;
+        }
+        if left < 0
+        {
+            cols += left;
+            left = 0;
+        }
+
+        let depth = self.stack.len() as int;
+        for line in range(top, cmp::min(top + lines, self.lines))
+        {
+            for col in range(left, cmp::min(left + cols, self.cols))
+            {
+                let cell = self.cell_mut(line, col);
+                if cell.maskdepth == -1
+                {
+                    cell.maskdepth = depth;
+                }
+            }
+        }
+    }
+
+    pub fn get_cursorpos(&self) -> Option<(int, int)>
+    {
+        self.vc
+    }
+    pub fn goto(&mut self, line: int, col: int)
+    {
+        self.vc = Some((line, col));
+    }
+    pub fn ungoto(&mut self)
+    {
+        self.vc = None;
+    }
+
+    pub fn setpen(&mut self, pen: &TickitPen)
+    {
+        // never mutate the pen inplace; make a new one
+        let mut newpen = pen.clone();
+        match self.stack.last()
+        {
+            Some(saved) => newpen.copy(&saved.pen, false),
+            None => {}
+        }
+        self.pen = newpen;
+    }
+
+    pub fn reset(&mut self)
+    {
+        let cols = self.cols;
+        for line in self.cells.iter_mut()
+        {
+            *line = blank_line(cols);
+        }
+
+        self.vc = None;
+        self.xlate_line = 0;
+        self.xlate_col = 0;
+        self.clip = Some(TickitRect::init_sized(0, 0, self.lines, self.cols));
+        self.pen = TickitPen::new();
+        self.stack.clear();
+        self.texts.clear();
+    }
+
+    pub fn save(&mut self)
+    {
+        let entry = StackEntry{vc: self.vc, xlate_line: self.xlate_line, xlate_col: self.xlate_col, clip: self.clip, pen: self.pen.clone(), pen_only: false};
+        self.stack.push(entry);
+    }
+    pub fn savepen(&mut self)
+    {
+        let entry = StackEntry{vc: self.vc, xlate_line: self.xlate_line, xlate_col: self.xlate_col, clip: self.clip, pen: self.pen.clone(), pen_only: true};
+        self.stack.push(entry);
+    }
+    pub fn restore(&mut self)
+    {
+        let entry = match self.stack.pop()
+        {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        if !entry.pen_only
+        {
+            self.vc = entry.vc;
+            self.xlate_line = entry.xlate_line;
+            self.xlate_col = entry.xlate_col;
+            self.clip = entry.clip;
+        }
+        self.pen = entry.pen;
+
+        // unmask everything masked since the save
+        let depth = self.stack.len() as int;
+        for line in self.cells.iter_mut()
+        {
+            for cell in line.iter_mut()
+            {
+                if cell.maskdepth > depth
+                {
+                    cell.maskdepth = -1;
+                }
+            }
+        }
+    }
+
+    pub fn skip_at(&mut self, line: int, col: int, len: int)
+    {
+        self.put_span(line, col, len, None, |_| SkipCell);
+    }
+    pub fn skip(&mut self, len: int)
+    {
+        match self.vc
+        {
+            Some((line, col)) =>
+            {
+                self.skip_at(line, col, len);
+                self.vc = Some((line, col + len));
+            }
+            None => {}
+        }
+    }
+    pub fn skip_to(&mut self, col: int)
+    {
+        match self.vc
+        {
+            Some((_, vc_col)) if vc_col < col => self.skip(col - vc_col),
+            _ => {}
+        }
+    }
+    pub fn text_at(&mut self, line: int, col: int, text: &str, pen: Option<&TickitPen>) -> int
+    {
+        self.put_text(line, col, text, pen)
+    }
+    pub fn text(&mut self, text: &str, pen: Option<&TickitPen>) -> int
+    {
+        match self.vc
+        {
+            Some((line, col)) =>
+            {
+                let cols = self.put_text(line, col, text, pen);
+                self.vc = Some((line, col + cols));
+                cols
+            }
+            None => -1,
+        }
+    }
+    pub fn erase_at(&mut self, line: int, col: int, len: int, pen: Option<&TickitPen>)
+    {
+        self.put_erase(line, col, len, pen);
+    }
+    pub fn erase(&mut self, len: int, pen: Option<&TickitPen>)
+    {
+        match self.vc
+        {
+            Some((line, col)) =>
+            {
+                self.put_erase(line, col, len, pen);
+                self.vc = Some((line, col + len));
+            }
+            None => {}
+        }
+    }
+    pub fn erase_to(&mut self, col: int, pen: Option<&TickitPen>)
+    {
+        match self.vc
+        {
+            Some((_, vc_col)) if vc_col < col => self.erase(col - vc_col, pen),
+            _ => {}
+        }
+    }
+    pub fn eraserect(&mut self, rect: &TickitRect, pen: Option<&TickitPen>)
+    {
+        for line in range(rect.top, rect.bottom())
+        {
+            self.put_erase(line, rect.left, rect.cols, pen);
+        }
+    }
+    pub fn clear(&mut self, pen: Option<&TickitPen>)
+    {
+        let all = TickitRect::init_sized(0, 0, self.lines, self.cols);
+        self.eraserect(&all, pen);
+    }
+    pub fn char_at(&mut self, line: int, col: int, codepoint: char, pen: Option<&TickitPen>)
+    {
+        self.put_char(line, col, codepoint, pen);
+    }
+    pub fn char(&mut self, codepoint: char, pen: Option<&TickitPen>)
+    {
+        match self.vc
+        {
+            Some((line, col)) =>
+            {
+                let cols = self.put_char(line, col, codepoint, pen);
+                self.vc = Some((line, col + cols));
+            }
+            None => {}
+        }
+    }
+}
+
+impl TickitRenderBuffer
+{
+    pub fn hline_at(&mut self, line: int, startcol: int, endcol: int, style: TickitLineStyle, pen: Option<&TickitPen>, caps: TickitLineCaps)
+    {
+        let east = (style as u8) << EAST_SHIFT;
+        let west = (style as u8) << WEST_SHIFT;
+
+        let start = if caps.contains(TICKIT_LINECAP_START) { west } else { 0 };
+        self.linecell(line, startcol, east | start, pen);
+        for col in range(startcol + 1, endcol)
+        {
+            self.linecell(line, col, east | west, pen);
+        }
+        let end = if caps.contains(TICKIT_LINECAP_END) { east } else { 0 };
+        self.linecell(line, endcol, end | west, pen);
+    }
+    pub fn vline_at(&mut self, startline: int, endline: int, col: int, style: TickitLineStyle, pen: Option<&TickitPen>, caps: TickitLineCaps)
+    {
+        let north = (style as u8) << NORTH_SHIFT;
+        let south = (style as u8) << SOUTH_SHIFT;
+
+        let start = if caps.contains(TICKIT_LINECAP_START) { north } else { 0 };
+        self.linecell(startline, col, south | start, pen);
+        for line in range(startline + 1, endline)
+        {
+            self.linecell(line, col, south | north, pen);
+        }
+        let end = if caps.contains(TICKIT_LINECAP_END) { south } else { 0 };
+        self.linecell(endline, col, end | north, pen);
+    }
+
+    pub fn flush_to_term(&mut self, tt: &mut TickitTerm)
+    {
+        for line in range(0, self.lines)
+        {
+            // column where the terminal cursor physically is
+            let mut phycol = -1;
+            let mut col = 0;
+
+            while col < self.cols
+            {
+                let (state, cols) =
+                {
+                    let cell = self.cell(line, col);
+                    (cell.state, cell.cols)
+                };
+
+                match state
+                {
+                    SkipCell =>
+                    {
+                        col += cols;
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                if phycol < col
+                {
+                    tt.goto(line, col);
+                }
+                phycol = col;
+
+                match state
+                {
+                    TextCell{idx, offs} =>
+                    {
+                        tt.setpen(self.cell(line, col).pen.as_ref().unwrap());
+                        tt.print(slice_columns(self.texts[idx].as_slice(), offs, cols));
+                        phycol += cols;
+                    }
+                    EraseCell =>
+                    {
+                        tt.setpen(self.cell(line, col).pen.as_ref().unwrap());
+                        tt.erasech(cols, None);
+                        phycol += cols;
+                    }
+                    LineCell{..} =>
+                    {
+                        let pen = self.cell(line, col).pen.clone().unwrap();
+                        let mut buf = String::new();
+                        while col < self.cols
+                        {
+                            let cell = self.cell(line, col);
+                            let mask = match cell.state
+                            {
+                                LineCell{mask} => mask,
+                                _ => break,
+                            };
+                            if !cell.pen.as_ref().unwrap().equiv(&pen)
+                            {
+                                break;
+                            }
+                            buf.push(LINECHARS[mask as uint]);
+                            col += 1;
+                            phycol += 1;
+                        }
+                        tt.setpen(&pen);
+                        tt.print(buf.as_slice());
+                        // col already updated
+                        continue;
+                    }
+                    CharCell{codepoint} =>
+                    {
+                        tt.setpen(self.cell(line, col).pen.as_ref().unwrap());
+                        tt.print(String::from_char(1, codepoint).as_slice());
+                        phycol += cols;
+                    }
+                    SkipCell | ContCell{..} =>
+                    {
+                        fail!("renderbuffer span corruption at {},{}", line, col);
+                    }
+                }
+
+                col += cols;
+            }
+        }
+
+        self.reset();
+    }
+}
+
+#[experimental]
+impl TickitRenderBuffer
+{
+    // Returns (span start column, offset into span), or None if outside
+    fn find_span(&self, line: int, col: int) -> Option<(int, int)>
+    {
+        if line < 0 || line >= self.lines || col < 0 || col >= self.cols
+        {
+            return None;
+        }
+        match self.cell(line, col).state
+        {
+            ContCell{startcol} => Some((startcol, col - startcol)),
+            _ => Some((col, 0)),
+        }
+    }
+
+    fn span_text(&self, line: int, startcol: int, offset: int, cols: int) -> String
+    {
+        match self.cell(line, startcol).state
+        {
+            TextCell{idx, offs} =>
+            {
+                slice_columns(self.texts[idx].as_slice(), offs + offset, cols).to_string()
+            }
+            LineCell{mask} =>
+            {
+                String::from_char(1, LINECHARS[mask as uint])
+            }
+            CharCell{codepoint} =>
+            {
+                String::from_char(1, codepoint)
+            }
+            _ =>
+            {
+                "".to_string()
+            }
+        }
+    }
+
+    pub fn get_cell_active(&mut self, line: int, col: int) -> bool
+    {
+        match self.find_span(line, col)
+        {
+            Some((startcol, _)) =>
+            {
+                match self.cell(line, startcol).state
+                {
+                    SkipCell => false,
+                    _ => true,
+                }
+            }
+            None => false,
+        }
+    }
+    pub fn get_cell_text(&mut self, line: int, col: int) -> String
+    {
+        match self.find_span(line, col)
+        {
+            Some((startcol, offset)) => self.span_text(line, startcol, offset, 1),
+            None => "".to_string(),
+        }
+    }
+    pub fn get_cell_linemask(&mut self, line: int, col: int) -> TickitRenderBufferLineMask
+    {
+        let mask = match self.find_span(line, col)
+        {
+            Some((startcol, _)) =>
+            {
+                match self.cell(line, startcol).state
+                {
+                    LineCell{mask} => mask,
+                    _ => 0,
+                }
+            }
+            None => 0,
+        };
+        TickitRenderBufferLineMask
+        {
+            north: line_style(mask >> NORTH_SHIFT),
+            south: line_style(mask >> SOUTH_SHIFT),
+            east: line_style(mask >> EAST_SHIFT),
+            west: line_style(mask >> WEST_SHIFT),
+        }
+    }
+
+    pub fn get_cell_pen(&mut self, line: int, col: int) -> TickitPen
+    {
+        let pen = match self.find_span(line, col)
+        {
+            Some((startcol, _)) => self.cell(line, startcol).pen.clone(),
+            None => None,
+        };
+        pen.unwrap_or_else(|| TickitPen::new())
+    }
+
+    /// The span covering `startcol` from there on, or None outside the
+    /// buffer.
+    pub fn get_span(&mut self, line: int, startcol: int) -> Option<TickitRenderBufferSpanInfo>
+    {
+        let (spancol, offset) = match self.find_span(line, startcol)
+        {
+            Some(v) => v,
+            None => return None,
+        };
+        let cell = self.cell(line, spancol);
+        let n_columns = cell.cols - offset;
+        Some(match cell.state
+        {
+            SkipCell =>
+            {
+                SkipSpan{n_columns: n_columns}
+            }
+            _ =>
+            {
+                TextSpan{pen: cell.pen.clone().unwrap(), text: self.span_text(line, spancol, offset, n_columns), n_columns: n_columns}
+            }
+        })
+    }
+}
@@ -0,0 +1,397 @@
+use std;
+use std::collections::HashSet;
+
+use libc;
+use libc::{c_int,c_void,nfds_t,pollfd,sighandler_t,size_t};
+use time;
+
+use TickitError;
+use TickitTerm;
+
+pub type TickitCallback = Box<FnMut(&mut Tickit) + 'static>;
+
+#[deriving(PartialEq, Eq, Show)]
+pub struct TickitWatchId(uint);
+
+// Write end of the self-pipe, for the signal handler.
+static mut SIGNAL_FD: c_int = -1;
+
+extern "C" fn on_signal(signum: c_int)
+{
+    unsafe
+    {
+        let b = signum as u8;
+        libc::write(SIGNAL_FD, &b as *const u8 as *const c_void, 1);
+    }
+}
+
+fn is_ready(pfd: &pollfd) -> bool
+{
+    pfd.revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0
+}
+
+// A pipe the signal handler can't block on, and children don't inherit.
+unsafe fn signal_pipe(fds: &mut [c_int, ..2]) -> Result<(), TickitError>
+{
+    if libc::pipe(fds.as_mut_ptr()) != 0
+    {
+        return Err(TickitError::last_os_error());
+    }
+    for &fd in fds.iter()
+    {
+        let flags = libc::fcntl(fd, libc::F_GETFL);
+        if libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) != 0
+            || libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) != 0
+        {
+            let err = TickitError::last_os_error();
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+struct Timer
+{
+    id: uint,
+    // from time::precise_time_ns
+    deadline: u64,
+    interval_ms: Option<uint>,
+    cb: TickitCallback,
+}
+
+struct Watch
+{
+    id: uint,
+    fd: c_int,
+    cb: TickitCallback,
+}
+
+/// A run-loop owning a terminal, multiplexing its input with user file
+/// descriptors, timers and idle callbacks.
+///
+/// SIGWINCH refreshes the terminal size and SIGINT stops the loop.
+pub struct Tickit
+{
+    pub tt: TickitTerm,
+    running: bool,
+    next_id: uint,
+    timers: Vec<Timer>,
+    watches: Vec<Watch>,
+    laters: Vec<(uint, TickitCallback)>,
+    idles: Vec<(uint, TickitCallback)>,
+    // ids cancelled during this iteration, whose callbacks may be out of
+    // their lists waiting to run, or running
+    cancelled: HashSet<uint>,
+    sigpipe: [c_int, ..2],
+    // what SIGINT and SIGWINCH did, and where on_signal wrote, before us
+    old_handlers: [sighandler_t, ..2],
+    old_signal_fd: c_int,
+}
+
+impl Tickit
+{
+    /// Create a terminal on stdin/stdout and a loop driving it.
+    pub fn new() -> Result<Tickit, TickitError>
+    {
+        let mut tt = try!(TickitTerm::new());
+        tt.set_input_fd(libc::STDIN_FILENO);
+        tt.set_output_fd(libc::STDOUT_FILENO);
+        let await = libc::timeval{ tv_sec: 0, tv_usec: 50000 };
+        tt.await_started(Some(await));
+        Tickit::new_for_term(tt)
+    }
+
+    pub fn new_for_term(tt: TickitTerm) -> Result<Tickit, TickitError>
+    {
+        let mut sigpipe: [c_int, ..2] = [-1, -1];
+        let old_handlers;
+        let old_signal_fd;
+        unsafe
+        {
+            try!(signal_pipe(&mut sigpipe));
+            old_signal_fd = SIGNAL_FD;
+            SIGNAL_FD = sigpipe[1];
+            let handler = on_signal as sighandler_t;
+            old_handlers = [libc::signal(libc::SIGINT, handler), libc::signal(libc::SIGWINCH, handler)];
+        }
+
+        Ok(Tickit
+        {
+            tt: tt,
+            running: false,
+            next_id: 0,
+            timers: Vec::new(),
+            watches: Vec::new(),
+            laters: Vec::new(),
+            idles: Vec::new(),
+            cancelled: HashSet::new(),
+            sigpipe: sigpipe,
+            old_handlers: old_handlers,
+            old_signal_fd: old_signal_fd,
+        })
+    }
+}
+
+impl Drop for Tickit
+{
+    fn drop(&mut self)
+    {
+        unsafe
+        {
+            if SIGNAL_FD == self.sigpipe[1]
+            {
+                libc::signal(libc::SIGINT, self.old_handlers[0]);
+                libc::signal(libc::SIGWINCH, self.old_handlers[1]);
+                SIGNAL_FD = self.old_signal_fd;
+            }
+            libc::close(self.sigpipe[0]);
+            libc::close(self.sigpipe[1]);
+        }
+    }
+}
+
+impl Tickit
+{
+    fn alloc_id(&mut self) -> uint
+    {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn add_timer(&mut self, ms: uint, interval_ms: Option<uint>, cb: TickitCallback) -> TickitWatchId
+    {
+        let id = self.alloc_id();
+        let deadline = time::precise_time_ns() + ms as u64 * 1000000;
+        self.timers.push(Timer{id: id, deadline: deadline, interval_ms: interval_ms, cb: cb});
+        TickitWatchId(id)
+    }
+
+    /// Call `cb` once, after `ms` milliseconds.
+    pub fn timer_after(&mut self, ms: uint, cb: TickitCallback) -> TickitWatchId
+    {
+        self.add_timer(ms, None, cb)
+    }
+    /// Call `cb` every `ms` milliseconds, until cancelled; 0 is taken as
+    /// 1, so that the loop still waits between calls.
+    pub fn timer_every(&mut self, ms: uint, cb: TickitCallback) -> TickitWatchId
+    {
+        let ms = std::cmp::max(ms, 1);
+        self.add_timer(ms, Some(ms), cb)
+    }
+    /// Call `cb` once, at the start of the next iteration.
+    pub fn later(&mut self, cb: TickitCallback) -> TickitWatchId
+    {
+        let id = self.alloc_id();
+        self.laters.push((id, cb));
+        TickitWatchId(id)
+    }
+    /// Call `cb` on every iteration, just before waiting, until cancelled.
+    pub fn idle(&mut self, cb: TickitCallback) -> TickitWatchId
+    {
+        let id = self.alloc_id();
+        self.idles.push((id, cb));
+        TickitWatchId(id)
+    }
+    /// Call `cb` whenever `fd` is readable, until cancelled.
+    pub fn watch_io(&mut self, fd: c_int, cb: TickitCallback) -> TickitWatchId
+    {
+        let id = self.alloc_id();
+        self.watches.push(Watch{id: id, fd: fd, cb: cb});
+        TickitWatchId(id)
+    }
+
+    pub fn cancel(&mut self, id: TickitWatchId)
+    {
+        let TickitWatchId(id) = id;
+        self.cancelled.insert(id);
+        self.timers.retain(|t| t.id != id);
+        self.watches.retain(|w| w.id != id);
+        self.laters.retain(|&(i, _)| i != id);
+        self.idles.retain(|&(i, _)| i != id);
+    }
+
+    pub fn is_running(&self) -> bool
+    {
+        self.running
+    }
+    /// Make `run` return after the current iteration.
+    pub fn stop(&mut self)
+    {
+        self.running = false;
+    }
+
+    /// Run until stopped, or until waiting fails.
+    pub fn run(&mut self) -> Result<(), TickitError>
+    {
+        self.running = true;
+        while self.running
+        {
+            try!(self.tick(true));
+        }
+        Ok(())
+    }
+}
+
+impl Tickit
+{
+    // Run 'cb' as the repeating callback 'id', unless an earlier callback
+    // cancelled it; returns whether to keep it.
+    fn call_repeating(&mut self, id: uint, cb: &mut TickitCallback) -> bool
+    {
+        if self.cancelled.contains(&id)
+        {
+            return false;
+        }
+        cb.call_mut((self,));
+        !self.cancelled.contains(&id)
+    }
+
+    fn next_timeout(&mut self) -> c_int
+    {
+        if !self.laters.is_empty()
+        {
+            return 0;
+        }
+
+        let mut timeout: Option<u64> = self.tt.input_check_timeout().map(|ms| ms as u64);
+        let now = time::precise_time_ns();
+        for timer in self.timers.iter()
+        {
+            let ms = if timer.deadline > now { (timer.deadline - now + 999999) / 1000000 } else { 0 };
+            timeout = Some(match timeout { Some(t) if t < ms => t, _ => ms });
+        }
+        match timeout
+        {
+            Some(ms) => ms as c_int,
+            None => -1,
+        }
+    }
+
+    fn handle_signals(&mut self)
+    {
+        let mut buf = [0u8, ..16];
+        let n = unsafe { libc::read(self.sigpipe[0], buf.as_mut_ptr() as *mut c_void, buf.len() as size_t) };
+        for &b in buf.slice_to(if n > 0 { n as uint } else { 0 }).iter()
+        {
+            let signum = b as c_int;
+            if signum == libc::SIGWINCH
+            {
+                self.tt.refresh_size();
+            }
+            else if signum == libc::SIGINT
+            {
+                self.stop();
+            }
+        }
+    }
+
+    fn fire_timers(&mut self)
+    {
+        let now = time::precise_time_ns();
+        let (due, pending) = std::mem::replace(&mut self.timers, Vec::new()).partition(|t| t.deadline <= now);
+        self.timers = pending;
+
+        for mut timer in due.into_iter()
+        {
+            let keep = self.call_repeating(timer.id, &mut timer.cb);
+            match timer.interval_ms
+            {
+                Some(ms) if keep =>
+                {
+                    timer.deadline = timer.deadline + ms as u64 * 1000000;
+                    self.timers.push(timer);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Run one iteration of the loop, optionally waiting for something to
+    /// happen. An error is from waiting; a signal cutting it short isn't one.
+    pub fn tick(&mut self, block: bool) -> Result<(), TickitError>
+    {
+        let laters = std::mem::replace(&mut self.laters, Vec::new());
+        for (id, mut cb) in laters.into_iter()
+        {
+            if !self.cancelled.contains(&id)
+            {
+                cb.call_mut((self,));
+            }
+        }
+
+        let idles = std::mem::replace(&mut self.idles, Vec::new());
+        for (id, mut cb) in idles.into_iter()
+        {
+            if self.call_repeating(id, &mut cb)
+            {
+                self.idles.push((id, cb));
+            }
+        }
+
+        self.tt.flush();
+
+        let timeout = if block { self.next_timeout() } else { 0 };
+
+        let input_fd = self.tt.get_input_fd();
+        let mut fds = vec![pollfd{fd: self.sigpipe[0], events: libc::POLLIN, revents: 0}];
+        fds.push(pollfd{fd: input_fd, events: libc::POLLIN, revents: 0});
+        for watch in self.watches.iter()
+        {
+            fds.push(pollfd{fd: watch.fd, events: libc::POLLIN, revents: 0});
+        }
+
+        // poll ignores negative fds, such as an unset input fd
+        let n = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as nfds_t, timeout) };
+        if n < 0
+        {
+            if std::os::errno() as c_int != libc::EINTR
+            {
+                return Err(TickitError::last_os_error());
+            }
+            return Ok(());
+        }
+
+        if is_ready(&fds[0])
+        {
+            self.handle_signals();
+        }
+
+        if input_fd >= 0 && is_ready(&fds[1])
+        {
+            self.tt.input_readable();
+        }
+        else
+        {
+            self.tt.input_check_timeout();
+        }
+
+        let ready_ids: Vec<uint> = self.watches.iter().zip(fds.slice_from(2).iter())
+            .filter(|&(_, pfd)| is_ready(pfd))
+            .map(|(w, _)| w.id)
+            .collect();
+        for &id in ready_ids.iter()
+        {
+            // an earlier callback may have cancelled this one
+            let idx = match self.watches.iter().position(|w| w.id == id)
+            {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let mut watch = self.watches.remove(idx).unwrap();
+            if self.call_repeating(id, &mut watch.cb)
+            {
+                self.watches.insert(idx, watch);
+            }
+        }
+
+        self.fire_timers();
+
+        // every callback taken out to run is back in its list, or gone
+        self.cancelled.clear();
+
+        self.tt.flush();
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 #![feature(macro_rules)]
 #![feature(unsafe_destructor)]
 #![feature(struct_variant)]
+#![feature(unboxed_closures)]
 #![allow(unused_variable)]
 #![allow(unused_mut)]
 
@@ -9,6 +10,7 @@ extern crate libc;
 extern crate tickit;
 
 use tickit::TickitRect;
+use tickit::window::TickitWindow;
 
 use taplib::Show2;
 
@@ -179,6 +181,28 @@ mod taplib
 
     impl Tap
     {
+        // Compare against tests/snapshots/<file>.snap under the crate root,
+        // wherever the tests are run from; see MockTerm::check_snapshot
+        pub fn is_snapshot(&mut self, mt: &mut ::tickit::mock::MockTerm, file: &str, name: &str)
+        {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/snapshots").join(format!("{}.snap", file));
+            match mt.check_snapshot(&path)
+            {
+                Ok(()) =>
+                {
+                    self.pass(name);
+                }
+                Err(diff) =>
+                {
+                    self.fail(name);
+                    for line in diff.as_slice().lines()
+                    {
+                        diag!("{}", line);
+                    }
+                }
+            }
+        }
+
         pub fn is_display_text(&mut self, mt: &mut ::tickit::mock::MockTerm, name: &str, expects: &[&str])
         {
             let (lines, cols) = mt.tt.get_size();
@@ -205,6 +229,32 @@ mod taplib
             self.pass(name);
         }
 
+        pub fn is_vt_text(&mut self, vt: &mut ::tickit::mock::VirtualTerm, name: &str, expects: &[&str])
+        {
+            let (lines, cols) = vt.get_size();
+
+            assert!(lines == expects.len())
+
+            for line in range(0, lines)
+            {
+                let expect = expects[line];
+                let got = vt.get_display_text(line, 0, cols);
+
+                if expect == got.as_slice()
+                {
+                    continue;
+                }
+
+                self.fail(name);
+                diag!("Got line {:2} |{}|", line, got);
+                diag!("Expected    |{}|", expect);
+
+                return;
+            }
+
+            self.pass(name);
+        }
+
         pub fn is_termlog<'a>(&mut self, mt: &mut ::tickit::mock::MockTerm, name: &str, expects: &[::LogExpectation<'a>])
         {
             let loglen = mt.loglen();
@@ -1369,7 +1419,7 @@ fn test_12term_modes()
         let _alive = tt.set_output_lively(output);
 
         (*buffer.lock()).clear();
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1).unwrap();
 
         tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1049h", "buffer after set_mode_altscreen on");
 
@@ -1377,27 +1427,27 @@ fn test_12term_modes()
         tap.is_int(value, 1, "get_mode_altscreen returns value");
 
         (*buffer.lock()).clear();
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_ALTSCREEN, 1).unwrap();
 
         tap.is_str_escape(uslice(&(*buffer.lock())), "", "set_mode_altscreen a second time is idempotent");
 
         (*buffer.lock()).clear();
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_CURSORVIS, 0).unwrap();
 
         tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?25l", "buffer after set_mode_cursorvis off");
 
         (*buffer.lock()).clear();
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_CLICK as int);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_CLICK as int).unwrap();
 
         tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1000h\x1b[?1006h", "buffer after set_mode_mouse to click");
 
         (*buffer.lock()).clear();
-        tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_DRAG as int);
+        tt.setctl_int(tickit::c::TICKIT_TERMCTL_MOUSE, tickit::c::TICKIT_TERM_MOUSEMODE_DRAG as int).unwrap();
 
         tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1002h\x1b[?1006h", "buffer after set_mode_mouse to drag");
 
         (*buffer.lock()).clear();
-        tt.setctl_str(tickit::c::TICKIT_TERMCTL_TITLE_TEXT, "title here");
+        tt.setctl_str(tickit::c::TICKIT_TERMCTL_TITLE_TEXT, "title here").unwrap();
 
         tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b]2;title here\x1b\\", "buffer after set title");
 
@@ -2026,7 +2076,7 @@ fn test_19term_driver()
 
     let ttd = TestVtable;
 
-    let mut tt = TickitTerm::new_for_driver(ttd);
+    let mut tt = TickitTerm::new_for_driver(ttd).unwrap();
 
     tap.pass("tickit_term_new_for_driver");
 
@@ -2052,6 +2102,12 @@ struct PenLog
     rv: Option<bool>,
     strike: Option<bool>,
     af: Option<int>,
+    ul: Option<tickit::TickitUnderline>,
+    uc: Option<tickit::TickitPenColour>,
+    blink: Option<bool>,
+    dim: Option<bool>,
+    overline: Option<bool>,
+    conceal: Option<bool>,
 }
 
 #[allow(dead_code)]
@@ -2065,10 +2121,16 @@ impl PenLog
     fn rv(self, v: bool) -> PenLog { PenLog{rv: Some(v), ..self} }
     fn strike(self, v: bool) -> PenLog { PenLog{strike: Some(v), ..self} }
     fn af(self, v: int) -> PenLog { PenLog{af: Some(v), ..self} }
+    fn ul(self, v: tickit::TickitUnderline) -> PenLog { PenLog{u: Some(v != tickit::NoUnderline), ul: Some(v), ..self} }
+    fn uc(self, v: tickit::TickitPenColour) -> PenLog { PenLog{uc: Some(v), ..self} }
+    fn blink(self, v: bool) -> PenLog { PenLog{blink: Some(v), ..self} }
+    fn dim(self, v: bool) -> PenLog { PenLog{dim: Some(v), ..self} }
+    fn overline(self, v: bool) -> PenLog { PenLog{overline: Some(v), ..self} }
+    fn conceal(self, v: bool) -> PenLog { PenLog{conceal: Some(v), ..self} }
 }
 
 #[allow(non_uppercase_statics)]
-static pen_log: PenLog = PenLog{fg: None, bg: None, b: None, u: None, i: None, rv: None, strike: None, af: None};
+static pen_log: PenLog = PenLog{fg: None, bg: None, b: None, u: None, i: None, rv: None, strike: None, af: None, ul: None, uc: None, blink: None, dim: None, overline: None, conceal: None};
 
 enum LogExpectation<'a>
 {
@@ -2143,6 +2205,12 @@ impl<'a> LogExpectation<'a>
                         && pl.rv.unwrap_or(false) == pen.get_bool_attr(tickit::c::TICKIT_PEN_REVERSE)
                         && pl.strike.unwrap_or(false) == pen.get_bool_attr(tickit::c::TICKIT_PEN_STRIKE)
                         && pl.af.unwrap_or(-1) == pen.get_int_attr(tickit::c::TICKIT_PEN_ALTFONT)
+                        && pl.ul.unwrap_or(if pl.u.unwrap_or(false) { tickit::SingleUnderline } else { tickit::NoUnderline }) == pen.get_under_style()
+                        && pl.uc == pen.get_under_colour()
+                        && pl.blink.unwrap_or(false) == pen.get_bool_attr(tickit::c::X_TICKIT_PEN_BLINK)
+                        && pl.dim.unwrap_or(false) == pen.get_bool_attr(tickit::c::X_TICKIT_PEN_DIM)
+                        && pl.overline.unwrap_or(false) == pen.get_bool_attr(tickit::c::X_TICKIT_PEN_OVERLINE)
+                        && pl.conceal.unwrap_or(false) == pen.get_bool_attr(tickit::c::X_TICKIT_PEN_CONCEAL)
                     }
                     _ => { false }
                 }
@@ -2165,7 +2233,33 @@ fn fillterm(tt: &mut tickit::TickitTerm)
 
 fn make_term(lines: int, cols: int) -> tickit::mock::MockTerm
 {
-    tickit::mock::MockTerm::new(lines, cols)
+    tickit::mock::MockTerm::new(lines, cols).unwrap()
+}
+
+// An xterm whose output collects in 'buffer'; declare the buffer first, so
+// that it outlives the term.
+fn make_xterm(buffer: &std::sync::Mutex<Vec<u8>>) -> tickit::TickitTerm
+{
+    fn output(tt: &mut tickit::TickitTerm, bytes: &[u8], buffer: &mut &std::sync::Mutex<Vec<u8>>)
+    {
+        (*buffer.lock()).push_all(bytes);
+    }
+
+    let mut tt = tickit::TickitTerm::new_for_termtype("xterm").unwrap();
+    tt.set_output_func(output, buffer);
+    tt
+}
+
+// Run checks that only the Rust MockTerm ("rust-mockterm") passes, on a
+// fresh one; libtickit's mock term lacks what they look at.
+#[cfg(feature = "rust-mockterm")]
+fn with_rust_mockterm(lines: int, cols: int, f: |tickit::mock::MockTerm|)
+{
+    f(make_term(lines, cols));
+}
+#[cfg(not(feature = "rust-mockterm"))]
+fn with_rust_mockterm(_lines: int, _cols: int, _f: |tickit::mock::MockTerm|)
+{
 }
 
 
@@ -2384,6 +2478,13 @@ fn test_20mockterm()
             "ABCDE   FG",
             "ABC     IJ",
         ]);
+
+    with_rust_mockterm(25, 80, |mut mt|
+    {
+        tap.ok(mt.tt.getctl_int(tickit::c::TICKIT_TERMCTL_COLORS) == Ok(256), "mockterm has 256 colours");
+        tap.ok(mt.tt.setctl_int(tickit::c::TICKIT_TERMCTL_COLORS, 8).is_ok(), "mockterm takes a colour count");
+        tap.ok(mt.tt.getctl_int(tickit::c::TICKIT_TERMCTL_COLORS) == Ok(8), "mockterm colour count after set");
+    });
 }
 
 #[test]
@@ -2393,7 +2494,7 @@ fn test_30renderbuffer_span()
 
     let mut mt = make_term(25, 80);
 
-    let mut rb = tickit::TickitRenderBuffer::new(10, 20);
+    let mut rb = tickit::TickitRenderBuffer::new(10, 20).unwrap();
 
     tap.pass("tickit_renderbuffer_new");
 
@@ -2623,7 +2724,7 @@ fn test_31renderbuffer_line()
 
     let mut mt = make_term(25, 80);
 
-    let mut rb = tickit::TickitRenderBuffer::new(30, 30);
+    let mut rb = tickit::TickitRenderBuffer::new(30, 30).unwrap();
 
     // Simple lines, explicit pen
     {
@@ -2710,7 +2811,7 @@ fn test_32renderbuffer_char()
 
     let mut mt = make_term(25, 80);
 
-    let mut rb = tickit::TickitRenderBuffer::new(10, 20);
+    let mut rb = tickit::TickitRenderBuffer::new(10, 20).unwrap();
 
     // Absolute characters
     {
@@ -2799,7 +2900,7 @@ fn test_33renderbuffer_clip()
 
     let mut mt = make_term(25, 80);
 
-    let mut rb = tickit::TickitRenderBuffer::new(10, 20);
+    let mut rb = tickit::TickitRenderBuffer::new(10, 20).unwrap();
 
     // Clipping to edge
     {
@@ -2906,7 +3007,7 @@ fn test_34renderbuffer_save()
 
     let mut mt = make_term(25, 80);
 
-    let mut rb = tickit::TickitRenderBuffer::new(10, 20);
+    let mut rb = tickit::TickitRenderBuffer::new(10, 20).unwrap();
 
     // Position
     {
@@ -3051,7 +3152,7 @@ fn test_35renderbuffer_mask()
 
     let mut mt = make_term(25, 80);
 
-    let mut rb = tickit::TickitRenderBuffer::new(10, 20);
+    let mut rb = tickit::TickitRenderBuffer::new(10, 20).unwrap();
 
     let mask = TickitRect{top: 3, left: 5, lines: 4, cols: 6};
 
@@ -3183,3 +3284,1863 @@ fn test_35renderbuffer_mask()
         rb.restore();
     }
 }
+
+#[test]
+fn test_36renderbuffer_get_span()
+{
+    let mut tap = taplib::Tap::new();
+
+    let mut rb = tickit::TickitRenderBuffer::new(3, 20).unwrap();
+    rb.text_at(0, 2, "Hello", None);
+    rb.erase_at(1, 4, 6, None);
+
+    match rb.get_span(0, 2)
+    {
+        Some(tickit::TextSpan{text, n_columns, ..}) =>
+        {
+            tap.is_str(text, "Hello", "get_span text of text span");
+            tap.is_int(n_columns, 5, "get_span n_columns of text span");
+        }
+        _ => tap.ok(false, "get_span text span"),
+    }
+    match rb.get_span(0, 4)
+    {
+        Some(tickit::TextSpan{text, n_columns, ..}) =>
+        {
+            tap.is_str(text, "llo", "get_span text from inside a span");
+            tap.is_int(n_columns, 3, "get_span n_columns from inside a span");
+        }
+        _ => tap.ok(false, "get_span inside a text span"),
+    }
+    match rb.get_span(1, 4)
+    {
+        Some(tickit::TextSpan{text, n_columns, ..}) =>
+        {
+            tap.is_str(text, "", "get_span text of erase span");
+            tap.is_int(n_columns, 6, "get_span n_columns of erase span");
+        }
+        _ => tap.ok(false, "get_span erase span"),
+    }
+    match rb.get_span(0, 0)
+    {
+        Some(tickit::SkipSpan{n_columns}) => tap.is_int(n_columns, 2, "get_span n_columns of skip span"),
+        _ => tap.ok(false, "get_span skip span"),
+    }
+
+    tap.ok(rb.get_span(3, 0).is_none(), "get_span below the buffer");
+    tap.ok(rb.get_span(-1, 0).is_none(), "get_span above the buffer");
+    tap.ok(rb.get_span(0, 20).is_none(), "get_span right of the buffer");
+}
+
+// The Rust renderbuffer against libtickit's, drawn on the same way
+#[cfg(feature = "rust-renderbuffer")]
+#[test]
+fn test_37renderbuffer_native()
+{
+    use std::c_str::ToCStr;
+    use libc::{c_char,c_int,c_long,size_t};
+    use tickit::c;
+
+    fn c_cell_text(crb: *mut c::TickitRenderBuffer, line: int, col: int) -> String
+    {
+        let mut buf = [0u8, ..16];
+        let n = unsafe { c::tickit_renderbuffer_get_cell_text(crb, line as c_int, col as c_int, buf.as_mut_ptr() as *mut c_char, buf.len() as size_t) };
+        String::from_utf8_lossy(buf.slice_to(n as uint)).into_string()
+    }
+
+    // whether the span is active, and its columns
+    fn c_span(crb: *mut c::TickitRenderBuffer, line: int, col: int) -> Option<(bool, int)>
+    {
+        unsafe
+        {
+            let mut info: c::TickitRenderBufferSpanInfo = std::mem::zeroed();
+            let n = c::tickit_renderbuffer_get_span(crb, line as c_int, col as c_int, &mut info, std::ptr::null_mut(), 0);
+            if n == -1 { None } else { Some((info.is_active != 0, info.n_columns as int)) }
+        }
+    }
+
+    let mut tap = taplib::Tap::new();
+
+    let mut rb = tickit::TickitRenderBuffer::new(6, 20).unwrap();
+    let crb = unsafe { c::tickit_renderbuffer_new(6, 20) };
+
+    rb.text_at(0, 2, "Hello, world", None);
+    "Hello, world".with_c_str(|s| unsafe { c::tickit_renderbuffer_text_at(crb, 0, 2, s, std::ptr::null()) });
+    rb.skip_at(0, 4, 2);
+    unsafe { c::tickit_renderbuffer_skip_at(crb, 0, 4, 2) };
+    rb.erase_at(1, 0, 5, None);
+    unsafe { c::tickit_renderbuffer_erase_at(crb, 1, 0, 5, std::ptr::null()) };
+    rb.char_at(2, 3, 'x', None);
+    unsafe { c::tickit_renderbuffer_char_at(crb, 2, 3, 'x' as c_long, std::ptr::null()) };
+    rb.text_at(3, 0, "wide \u4e00\u4e8c!", None);
+    "wide \u4e00\u4e8c!".with_c_str(|s| unsafe { c::tickit_renderbuffer_text_at(crb, 3, 0, s, std::ptr::null()) });
+    rb.hline_at(4, 1, 6, c::TICKIT_LINE_SINGLE, None, c::TICKIT_LINECAP_BOTH);
+    unsafe { c::tickit_renderbuffer_hline_at(crb, 4, 1, 6, c::TICKIT_LINE_SINGLE, std::ptr::null(), c::TICKIT_LINECAP_BOTH) };
+    rb.clip(&TickitRect{top: 0, left: 0, lines: 6, cols: 15});
+    unsafe { c::tickit_renderbuffer_clip(crb, &c::TickitRect{top: 0, left: 0, lines: 6, cols: 15}) };
+    rb.text_at(5, 10, "clipped text", None);
+    "clipped text".with_c_str(|s| unsafe { c::tickit_renderbuffer_text_at(crb, 5, 10, s, std::ptr::null()) });
+
+    let mut cells_ok = true;
+    let mut spans_ok = true;
+    for line in range(0i, 6)
+    {
+        for col in range(0i, 20)
+        {
+            let active = unsafe { c::tickit_renderbuffer_get_cell_active(crb, line as c_int, col as c_int) != 0 };
+            if rb.get_cell_active(line, col) != active || rb.get_cell_text(line, col) != c_cell_text(crb, line, col)
+            {
+                diag!("cell {},{} differs", line, col);
+                cells_ok = false;
+            }
+            let span = match rb.get_span(line, col)
+            {
+                Some(tickit::SkipSpan{n_columns}) => Some((false, n_columns)),
+                Some(tickit::TextSpan{n_columns, ..}) => Some((true, n_columns)),
+                None => None,
+            };
+            if span != c_span(crb, line, col)
+            {
+                diag!("span at {},{} differs", line, col);
+                spans_ok = false;
+            }
+        }
+    }
+    tap.ok(cells_ok, "cells match libtickit's renderbuffer");
+    tap.ok(spans_ok, "spans match libtickit's renderbuffer");
+
+    tap.ok(rb.get_span(6, 0).is_none() && c_span(crb, 6, 0).is_none(), "get_span below the buffer matches");
+    tap.ok(rb.get_span(0, -1).is_none() && c_span(crb, 0, -1).is_none(), "get_span left of the buffer matches");
+
+    unsafe { c::tickit_renderbuffer_destroy(crb) };
+}
+
+#[test]
+fn test_40window()
+{
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let mut tap = taplib::Tap::new();
+
+    let mut mt = make_term(25, 80);
+
+    let root = TickitWindow::new_root(&mt.tt);
+    let win = root.make_sub(3, 10, 4, 20);
+
+    tap.is_int(win.get_abs_geometry().top, 3, "win abs top");
+    tap.is_int(win.get_abs_geometry().left, 10, "win abs left");
+
+    // Exposure is translated and clipped to the window
+    {
+        win.bind_expose(box move |&mut: rb: &mut tickit::TickitRenderBuffer, rect: &TickitRect|
+        {
+            rb.text_at(0, 0, "Hello", None);
+            rb.text_at(1, 18, "world", None);
+        });
+
+        root.flush(&mut mt.tt).unwrap();
+        mt.clearlog();
+
+        win.expose(None);
+        root.flush(&mut mt.tt).unwrap();
+        tap.is_termlog(&mut mt, "window expose",
+            [
+                GOTO(3,10), SETPEN(pen_log), PRINT("Hello"),
+                GOTO(4,28), SETPEN(pen_log), PRINT("wo"),
+            ]);
+    }
+
+    // Front-most sibling obscures those behind it
+    {
+        let front = root.make_sub(3, 12, 1, 2);
+        front.bind_expose(box move |&mut: rb: &mut tickit::TickitRenderBuffer, rect: &TickitRect|
+        {
+            rb.text_at(0, 0, "XY", None);
+        });
+
+        win.expose(Some(&TickitRect{top: 0, left: 0, lines: 1, cols: 5}));
+        root.flush(&mut mt.tt).unwrap();
+        tap.is_termlog(&mut mt, "window expose with sibling in front",
+            [
+                GOTO(3,10), SETPEN(pen_log), PRINT("He"), SETPEN(pen_log), PRINT("XY"), SETPEN(pen_log), PRINT("o"),
+            ]);
+
+        front.lower_to_back();
+        root.flush(&mut mt.tt).unwrap();
+        tap.is_termlog(&mut mt, "window expose after lower_to_back",
+            [
+                GOTO(3,12), SETPEN(pen_log), PRINT("ll"),
+            ]);
+
+        front.close();
+        root.flush(&mut mt.tt).unwrap();
+        mt.clearlog();
+    }
+
+    // Mouse events are translated and bubble up
+    {
+        let win_mouse = Rc::new(Cell::new((0i, 0i)));
+        let root_mouse = Rc::new(Cell::new(0u));
+
+        let w = win_mouse.clone();
+        win.bind_mouse(box move |&mut: _win: &TickitWindow, ev: &tickit::TickitMouseEvent|
+        {
+            match *ev
+            {
+                tickit::MousePressEvent{line, col, ..} => w.set((line, col)),
+                _ => {}
+            }
+            line_is_zero(ev)
+        });
+        let r = root_mouse.clone();
+        root.bind_mouse(box move |&mut: _win: &TickitWindow, ev: &tickit::TickitMouseEvent|
+        {
+            r.set(r.get() + 1);
+            true
+        });
+
+        fn line_is_zero(ev: &tickit::TickitMouseEvent) -> bool
+        {
+            match *ev
+            {
+                tickit::MousePressEvent{line, ..} => line == 0,
+                _ => false,
+            }
+        }
+
+        let mod_ = tickit::c::X_Tickit_Mod::empty();
+        root.handle_event(&tickit::MouseEvent(tickit::MousePressEvent{button: 1, line: 3, col: 15, mod_: mod_}));
+        tap.ok(win_mouse.get() == (0, 5), "window receives translated mouse event");
+        tap.is_int(root_mouse.get() as int, 0, "handled mouse event does not bubble");
+
+        root.handle_event(&tickit::MouseEvent(tickit::MousePressEvent{button: 1, line: 5, col: 12, mod_: mod_}));
+        tap.ok(win_mouse.get() == (2, 2), "window receives second mouse event");
+        tap.is_int(root_mouse.get() as int, 1, "unhandled mouse event bubbles to root");
+
+        root.handle_event(&tickit::MouseEvent(tickit::MousePressEvent{button: 1, line: 20, col: 70, mod_: mod_}));
+        tap.is_int(root_mouse.get() as int, 2, "mouse event outside window goes to root");
+    }
+
+    // Focus and key events
+    {
+        let focus = Rc::new(Cell::new(false));
+        let keys = Rc::new(Cell::new(0u));
+
+        let f = focus.clone();
+        win.bind_focus(box move |&mut: _win: &TickitWindow, focused: bool|
+        {
+            f.set(focused);
+        });
+        let k = keys.clone();
+        win.bind_key(box move |&mut: _win: &TickitWindow, ev: &tickit::TickitKeyEvent|
+        {
+            k.set(k.get() + 1);
+            true
+        });
+
+        let mod_ = tickit::c::X_Tickit_Mod::empty();
+        root.handle_event(&tickit::KeyEvent(tickit::KeyTextEvent{text: "A", mod_: mod_}));
+        tap.is_int(keys.get() as int, 0, "unfocused window receives no keys");
+
+        win.take_focus();
+        tap.ok(win.is_focused(), "window is focused after take_focus");
+        tap.ok(focus.get(), "focus handler invoked");
+
+        root.handle_event(&tickit::KeyEvent(tickit::KeyTextEvent{text: "A", mod_: mod_}));
+        tap.is_int(keys.get() as int, 1, "focused window receives keys");
+
+        root.take_focus();
+        tap.ok(!win.is_focused(), "window is unfocused after root takes focus");
+        tap.ok(!focus.get(), "focus handler invoked on unfocus");
+
+        let child = win.make_sub(0, 0, 1, 1);
+        let child_focus = Rc::new(Cell::new(false));
+        let f = child_focus.clone();
+        child.bind_focus(box move |&mut: _win: &TickitWindow, focused: bool|
+        {
+            f.set(focused);
+        });
+        child.take_focus();
+        child.close();
+        tap.ok(!child.is_focused(), "closed window is unfocused");
+        tap.ok(!child_focus.get(), "focus handler invoked on close");
+    }
+
+    // Hiding exposes the area underneath
+    {
+        win.hide();
+        tap.ok(!win.is_visible(), "window hidden");
+        root.flush(&mut mt.tt).unwrap();
+        tap.is_termlog(&mut mt, "hidden window is not drawn", []);
+    }
+}
+
+#[test]
+fn test_41expose()
+{
+    let mut tap = taplib::Tap::new();
+
+    let mut mt = make_term(25, 80);
+
+    let mut sched = tickit::TickitExposeScheduler::new(25, 80);
+
+    tap.ok(!sched.is_damaged(), "scheduler initially undamaged");
+    tap.ok(sched.flush(&mut mt.tt, |rb, rect| {}) == Ok(false), "flush with no damage does nothing");
+
+    sched.damage(&TickitRect{top: 2, left: 5, lines: 1, cols: 10});
+    sched.damage(&TickitRect{top: 2, left: 10, lines: 1, cols: 10});
+    sched.damage(&TickitRect{top: 30, left: 0, lines: 1, cols: 10});
+
+    {
+        let rects = sched.get_rects();
+        tap.is_int(rects.len(), 1, "overlapping damage is coalesced");
+        tap.is_rect(rects[0], "5,2+15,1", "rects[0] after damage");
+    }
+
+    tap.ok(sched.flush(&mut mt.tt, |rb, rect|
+        {
+            rb.text_at(2, 0, "ABCDEFGHIJKLMNOPQRSTUVWXYZ", None);
+            rb.text_at(3, 0, "not damaged", None);
+        }) == Ok(true), "flush with damage renders");
+    tap.is_termlog(&mut mt, "flush renders only damaged region",
+        [
+            GOTO(2, 5), SETPEN(pen_log), PRINT("FGHIJKLMNOPQRST"),
+        ]);
+    tap.ok(!sched.is_damaged(), "scheduler undamaged after flush");
+
+    sched.resize(2, 4);
+    {
+        let rects = sched.get_rects();
+        tap.is_int(rects.len(), 1, "resize damages everything");
+        tap.is_rect(rects[0], "0,0+4,2", "rects[0] after resize");
+    }
+
+    // What an expose handler exposes while a window tree renders is drawn
+    // on the next flush
+    {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let root = TickitWindow::new_root(&mt.tt);
+        let other = root.make_sub(10, 0, 1, 5);
+        other.bind_expose(box move |&mut: rb: &mut tickit::TickitRenderBuffer, rect: &TickitRect|
+        {
+            rb.text_at(0, 0, "Other", None);
+        });
+        let armed = Rc::new(Cell::new(false));
+        let trigger = root.make_sub(12, 0, 1, 5);
+        {
+            let armed = armed.clone();
+            let other = other.clone();
+            trigger.bind_expose(box move |&mut: rb: &mut tickit::TickitRenderBuffer, rect: &TickitRect|
+            {
+                if armed.get()
+                {
+                    armed.set(false);
+                    other.expose(None);
+                }
+            });
+        }
+        root.flush(&mut mt.tt).unwrap();
+        mt.clearlog();
+
+        armed.set(true);
+        trigger.expose(None);
+        root.flush(&mut mt.tt).unwrap();
+        mt.clearlog();
+        root.flush(&mut mt.tt).unwrap();
+        tap.is_termlog(&mut mt, "window expose from an expose handler",
+            [
+                GOTO(10,0), SETPEN(pen_log), PRINT("Other"),
+            ]);
+
+        other.close();
+        trigger.close();
+        root.flush(&mut mt.tt).unwrap();
+        mt.clearlog();
+    }
+}
+
+#[test]
+fn test_42runloop()
+{
+    use std::cell::{Cell,RefCell};
+    use std::rc::Rc;
+
+    let mut tap = taplib::Tap::new();
+
+    let tt = tickit::TickitTerm::new_for_termtype("screen").unwrap();
+    let mut t = tickit::Tickit::new_for_term(tt).unwrap();
+
+    let log = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let l = log.clone();
+    t.timer_after(20, box move |&mut: t: &mut tickit::Tickit|
+    {
+        l.borrow_mut().push("after".to_string());
+        t.stop();
+    });
+    let l = log.clone();
+    let mut ticks = 0u;
+    t.timer_every(5, box move |&mut: t: &mut tickit::Tickit|
+    {
+        ticks += 1;
+        if ticks == 2
+        {
+            l.borrow_mut().push("every".to_string());
+        }
+    });
+    let l = log.clone();
+    t.later(box move |&mut: t: &mut tickit::Tickit|
+    {
+        l.borrow_mut().push("later".to_string());
+    });
+    let l = log.clone();
+    let idle = t.idle(box move |&mut: t: &mut tickit::Tickit|
+    {
+        l.borrow_mut().push("idle".to_string());
+    });
+
+    t.tick(false).unwrap();
+    t.cancel(idle);
+
+    tap.ok(t.run().is_ok(), "run returns Ok after stop");
+    tap.ok(!t.is_running(), "run returns after stop");
+
+    let log = log.borrow();
+    tap.is_int(log.len(), 4, "callbacks invoked");
+    tap.is_str(log[0].as_slice(), "later", "later runs first");
+    tap.is_str(log[1].as_slice(), "idle", "idle runs before waiting");
+    tap.is_str(log[2].as_slice(), "every", "repeating timer fires more than once");
+    tap.is_str(log[3].as_slice(), "after", "one-shot timer fires");
+
+    // A callback cancelled by an earlier one in the same iteration doesn't run
+    {
+        let fired = Rc::new(Cell::new(0u));
+        let later = Rc::new(Cell::new(None::<tickit::TickitWatchId>));
+        let idle = Rc::new(Cell::new(None::<tickit::TickitWatchId>));
+        let timer = Rc::new(Cell::new(None::<tickit::TickitWatchId>));
+
+        let v = later.clone();
+        t.later(box move |&mut: t: &mut tickit::Tickit| t.cancel(v.get().unwrap()));
+        let f = fired.clone();
+        later.set(Some(t.later(box move |&mut: t: &mut tickit::Tickit| f.set(f.get() + 1))));
+
+        let v = idle.clone();
+        let idle_canceller = t.idle(box move |&mut: t: &mut tickit::Tickit| t.cancel(v.get().unwrap()));
+        let f = fired.clone();
+        idle.set(Some(t.idle(box move |&mut: t: &mut tickit::Tickit| f.set(f.get() + 1))));
+
+        let v = timer.clone();
+        t.timer_after(0, box move |&mut: t: &mut tickit::Tickit| t.cancel(v.get().unwrap()));
+        let f = fired.clone();
+        timer.set(Some(t.timer_after(0, box move |&mut: t: &mut tickit::Tickit| f.set(f.get() + 1))));
+
+        tap.ok(t.tick(false).is_ok(), "tick without waiting");
+        t.cancel(idle_canceller);
+        tap.is_int(fired.get() as int, 0, "cancelled later, idle and timer don't run");
+    }
+}
+
+#[test]
+fn test_43bind()
+{
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    let mut tap = taplib::Tap::new();
+
+    let size = Rc::new(Cell::new((0i, 0i)));
+    let guard;
+
+    {
+        let mut mt = make_term(25, 80);
+
+        let s = size.clone();
+        guard = mt.tt.bind(tickit::c::TICKIT_EV_RESIZE, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+        {
+            match *ev
+            {
+                tickit::ResizeEvent{lines, cols} => s.set((lines, cols)),
+                _ => {}
+            }
+        });
+        tap.ok(guard.is_bound(), "guard is bound after bind");
+
+        mt.tt.set_size(30, 100);
+        tap.ok(size.get() == (30, 100), "closure invoked on resize");
+
+        let calls = Rc::new(Cell::new(0u));
+        {
+            let c = calls.clone();
+            let _scoped = mt.tt.bind(tickit::c::TICKIT_EV_RESIZE, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+            {
+                c.set(c.get() + 1);
+            });
+
+            mt.tt.set_size(35, 110);
+            tap.is_int(calls.get() as int, 1, "second closure invoked on resize");
+        }
+
+        mt.tt.set_size(40, 120);
+        tap.is_int(calls.get() as int, 1, "closure not invoked after guard dropped");
+        tap.ok(size.get() == (40, 120), "first closure still bound");
+    }
+
+    tap.ok(!guard.is_bound(), "guard unbound after term destroyed");
+    drop(guard);
+    tap.pass("dropping guard after term destroyed");
+
+    {
+        let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+        let mut tt = make_xterm(&buffer);
+        let pastes = Rc::new(Cell::new(0u));
+
+        let p = pastes.clone();
+        let _resize = tt.bind(tickit::c::TICKIT_EV_RESIZE, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+        {
+            let p = p.clone();
+            let _ = tt.set_bracketed_paste(true);
+            tt.bind(tickit::c::X_TICKIT_EV_PASTE, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+            {
+                p.set(p.get() + 1);
+            }).forget();
+        });
+
+        tt.set_size(30, 100);
+        tap.ok(tt.get_bracketed_paste() == Ok(true), "term keeps a mode set on it from a callback");
+        tt.input_push_bytes("\x1b[200~x\x1b[201~".as_bytes());
+        tap.is_int(pastes.get() as int, 1, "binding made from a callback is fired");
+    }
+
+    {
+        let mut pen = tickit::TickitPen::new();
+        let changes = Rc::new(Cell::new(0u));
+
+        let c = changes.clone();
+        let pen_guard = pen.bind(tickit::c::TICKIT_EV_CHANGE, box move |&mut: pen: &mut tickit::TickitPen, ev: &tickit::TickitEvent|
+        {
+            c.set(c.get() + 1);
+        });
+
+        pen.set_bool_attr(tickit::c::TICKIT_PEN_BOLD, true);
+        tap.is_int(changes.get() as int, 1, "pen closure invoked on change");
+
+        drop(pen_guard);
+        pen.set_bool_attr(tickit::c::TICKIT_PEN_BOLD, false);
+        tap.is_int(changes.get() as int, 1, "pen closure not invoked after guard dropped");
+    }
+}
+
+#[test]
+fn test_44term_ctl_typed()
+{
+    let mut tap = taplib::Tap::new();
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let mut tt = make_xterm(&buffer);
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_altscreen(true).is_ok(), "set_altscreen succeeds");
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1049h", "buffer after set_altscreen true");
+    tap.ok(tt.get_altscreen() == Ok(true), "get_altscreen returns true");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_mouse_mode(tickit::c::TICKIT_TERM_MOUSEMODE_DRAG).is_ok(), "set_mouse_mode succeeds");
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1002h\x1b[?1006h", "buffer after set_mouse_mode drag");
+    tap.ok(tt.get_mouse_mode() == Ok(tickit::c::TICKIT_TERM_MOUSEMODE_DRAG), "get_mouse_mode returns drag");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_cursor_shape(tickit::c::TICKIT_TERM_CURSORSHAPE_UNDER).is_ok(), "set_cursor_shape succeeds");
+    tap.ok(tt.get_cursor_shape() == Ok(tickit::c::TICKIT_TERM_CURSORSHAPE_UNDER), "get_cursor_shape returns under");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_title_text("title here").is_ok(), "set_title_text succeeds");
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b]2;title here\x1b\\", "buffer after set_title_text");
+
+    tap.ok(tt.get_colors().is_ok(), "get_colors succeeds");
+}
+
+#[test]
+fn test_45errors()
+{
+    let mut tap = taplib::Tap::new();
+
+    match tickit::TickitTerm::new_for_termtype("no-such-terminal")
+    {
+        Err(tickit::UnknownTermTypeError(name)) =>
+        {
+            tap.is_str(name.as_slice(), "no-such-terminal", "unknown terminal type error names the type");
+        }
+        _ =>
+        {
+            tap.fail("new_for_termtype with unknown terminal type");
+        }
+    }
+
+    tap.ok(tickit::TickitRenderBuffer::new(-1, 20).err() == Some(tickit::InvalidGeometryError(-1, 20)), "TickitRenderBuffer::new rejects negative lines");
+    tap.ok(tickit::mock::MockTerm::new(25, -1).err() == Some(tickit::InvalidGeometryError(25, -1)), "MockTerm::new rejects negative cols");
+
+    let mut tt = tickit::TickitTerm::new_for_termtype("xterm").unwrap();
+    tap.ok(tt.setctl_int(tickit::c::TICKIT_TERMCTL_COLORS, 16) == Err(tickit::UnsupportedCtlError(tickit::c::TICKIT_TERMCTL_COLORS)), "setting read-only control fails");
+
+    tap.is_str(format!("{}", tickit::InvalidGeometryError(-1, 20)).as_slice(), "invalid geometry -1x20", "TickitError display");
+    tap.is_str(format!("{}", tickit::UnsupportedCtlError(tickit::c::TICKIT_TERMCTL_COLORS)).as_slice(),
+        "terminal control TICKIT_TERMCTL_COLORS not supported", "TickitError display for control");
+}
+
+#[test]
+fn test_46snapshot()
+{
+    let mut tap = taplib::Tap::new();
+
+    let mut mt = make_term(3, 10);
+
+    mt.tt.goto(0, 0);
+    mt.tt.print("Hello");
+
+    let pen = tickit::TickitPen::new()
+        .with_colour_attr(tickit::c::TICKIT_PEN_FG, 1)
+        .with_bool_attr(tickit::c::TICKIT_PEN_BOLD, true);
+    mt.tt.setpen(&pen);
+    mt.tt.goto(1, 3);
+    mt.tt.print("world");
+
+    tap.is_snapshot(&mut mt, "46snapshot_basic", "snapshot of text and pens");
+
+    tap.is_str(mt.snapshot(), "size 3x10\n|Hello     |\n|   world  |\n|          |\npen 1:3-7 fg=1 b\n", "snapshot format");
+}
+
+#[test]
+fn test_47virtualterm()
+{
+    let mut tap = taplib::Tap::new();
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let mut vt = tickit::mock::VirtualTerm::new(4, 10).unwrap();
+
+    {
+        let mut tt = make_xterm(&buffer);
+        tt.set_size(4, 10);
+
+        tt.goto(1, 2);
+        tt.print("Hello");
+        vt.feed((*buffer.lock()).as_slice());
+        (*buffer.lock()).clear();
+
+        tap.is_vt_text(&mut vt, "Display after goto+print",
+            [
+                "          ",
+                "  Hello   ",
+                "          ",
+                "          ",
+            ]);
+        tap.is_int(vt.get_position(), (1, 7), "position after print");
+
+        tt.setpen(&tickit::TickitPen::new().with_colour_attr(tickit::c::TICKIT_PEN_FG, 1).with_bool_attr(tickit::c::TICKIT_PEN_BOLD, true));
+        tt.goto(2, 0);
+        tt.print("ab");
+        tt.setpen(&tickit::TickitPen::new());
+        tt.print("c");
+        vt.feed((*buffer.lock()).as_slice());
+        (*buffer.lock()).clear();
+
+        let pen = vt.get_display_pen(2, 1);
+        tap.is_int(pen.get_colour_attr(tickit::c::TICKIT_PEN_FG), 1, "pen fg from SGR");
+        tap.ok(pen.get_bool_attr(tickit::c::TICKIT_PEN_BOLD), "pen bold from SGR");
+        tap.ok(!vt.get_display_pen(2, 2).is_nondefault(), "pen reset by SGR");
+
+        tt.scrollrect(TickitRect::init_sized(0, 0, 4, 10), 1, 0);
+        vt.feed((*buffer.lock()).as_slice());
+        (*buffer.lock()).clear();
+
+        tap.is_vt_text(&mut vt, "Display after scrollrect down",
+            [
+                "  Hello   ",
+                "abc       ",
+                "          ",
+                "          ",
+            ]);
+
+        tt.scrollrect(TickitRect::init_sized(0, 0, 1, 10), 0, 2);
+        tt.goto(1, 1);
+        tt.erasech(1, None);
+        vt.feed((*buffer.lock()).as_slice());
+        (*buffer.lock()).clear();
+
+        tap.is_vt_text(&mut vt, "Display after DCH and erasech",
+            [
+                "Hello     ",
+                "a c       ",
+                "          ",
+                "          ",
+            ]);
+
+        tt.set_altscreen(true).unwrap();
+        tt.set_cursor_visible(false).unwrap();
+        tt.set_mouse_mode(tickit::c::TICKIT_TERM_MOUSEMODE_DRAG).unwrap();
+        tt.set_title_text("title here").unwrap();
+        vt.feed((*buffer.lock()).as_slice());
+        (*buffer.lock()).clear();
+
+        tap.ok(vt.is_altscreen(), "altscreen on");
+        tap.ok(!vt.is_cursor_visible(), "cursor hidden");
+        tap.is_int(vt.get_mouse_mode(), tickit::c::TICKIT_TERM_MOUSEMODE_DRAG, "mouse mode drag");
+        tap.is_str(vt.get_title(), "title here", "title from OSC 2");
+        tap.is_str(vt.get_display_text(0, 0, 10), "          ", "altscreen starts blank");
+
+        tt.clear();
+        vt.feed((*buffer.lock()).as_slice());
+        (*buffer.lock()).clear();
+    }
+
+    vt.feed((*buffer.lock()).as_slice());
+    tap.ok(!vt.is_altscreen(), "altscreen off after term is destroyed");
+    tap.is_str(vt.get_display_text(0, 0, 10), "Hello     ", "primary screen restored");
+
+    vt.feed(b"\x1b[2;3H\x1b[2@");
+    tap.is_str(vt.get_display_text(1, 0, 10), "a   c     ", "ICH inserts blanks");
+    vt.feed(b"\x1b[");
+    vt.feed(b"1P\xe2\x82");
+    tap.is_str(vt.get_display_text(1, 0, 10), "a  c      ", "split CSI is buffered");
+    vt.feed(b"\xac");
+    tap.is_str(vt.get_display_text(1, 0, 10), "a \u20acc      ", "split UTF-8 is buffered");
+}
+
+// A driver for a terminal of so many colours, which prints text as it is and
+// writes foreground colour changes and erases as FG(n) and ERASE(n); with
+// 'ext' it takes RGB foregrounds too, as FG(#rrggbb).
+struct PaletteVtable
+{
+    colors: int,
+    ext: bool,
+}
+
+impl tickit::drv::TickitTermDriverImpl for PaletteVtable
+{
+    fn print(&mut self, cdr: tickit::drv::CDriverRef, str_: &str)
+    {
+        cdr.write_str(str_);
+    }
+    fn goto_abs(&mut self, cdr: tickit::drv::CDriverRef, line: int, col: int) -> bool { true }
+    fn move_rel(&mut self, cdr: tickit::drv::CDriverRef, downward: int, rightward: int) {}
+    fn scrollrect(&mut self, cdr: tickit::drv::CDriverRef, rect: &TickitRect, downward: int, rightward: int) -> bool { false }
+    fn erasech(&mut self, cdr: tickit::drv::CDriverRef, count: int, moveend: Option<bool>)
+    {
+        cdr.write_str(format!("ERASE({})", count).as_slice());
+    }
+    fn clear(&mut self, cdr: tickit::drv::CDriverRef) {}
+    fn chpen(&mut self, cdr: tickit::drv::CDriverRef, delta: &tickit::TickitPen, final_: &tickit::TickitPen)
+    {
+        match delta.maybe_get_colour_attr(tickit::c::TICKIT_PEN_FG)
+        {
+            Some(idx) => cdr.write_str(format!("FG({})", idx).as_slice()),
+            None => {}
+        }
+    }
+    fn chpen_ext(&mut self, cdr: tickit::drv::CDriverRef, pen: &tickit::TickitPen) -> bool
+    {
+        if !self.ext
+        {
+            return false;
+        }
+        match pen.get_rgb_attr(tickit::c::TICKIT_PEN_FG)
+        {
+            Some(rgb) => cdr.write_str(format!("FG({})", rgb).as_slice()),
+            None => {}
+        }
+        true
+    }
+    fn getctl_int(&mut self, cdr: tickit::drv::CDriverRef, ctl: tickit::c::TickitTermCtl) -> Option<int>
+    {
+        match ctl
+        {
+            tickit::c::TICKIT_TERMCTL_COLORS => Some(self.colors),
+            _ => None,
+        }
+    }
+    fn setctl_int(&mut self, cdr: tickit::drv::CDriverRef, ctl: tickit::c::TickitTermCtl, value: int) -> bool
+    {
+        false
+    }
+    fn setctl_str(&mut self, cdr: tickit::drv::CDriverRef, ctl: tickit::c::TickitTermCtl, value: &str) -> bool
+    {
+        false
+    }
+}
+
+#[test]
+fn test_48truecolour()
+{
+    use std::sync::Mutex;
+
+    use tickit::{TickitPen,TickitPenRGB,TickitRenderBuffer,TickitTerm};
+
+    let mut tap = taplib::Tap::new();
+
+    let orange = TickitPenRGB::new(255, 135, 0);
+
+    tap.ok(TickitPenRGB::from_index(208) == orange, "TickitPenRGB::from_index cube");
+    tap.ok(TickitPenRGB::from_index(232) == TickitPenRGB::new(8, 8, 8), "TickitPenRGB::from_index greyscale");
+    tap.is_int(orange.nearest_index(256), 208, "nearest_index 256");
+    tap.is_int(orange.nearest_index(16), 3, "nearest_index 16");
+    tap.ok(TickitPenRGB::from_hex("#ff8700") == Some(orange), "TickitPenRGB::from_hex");
+    tap.ok(TickitPenRGB::from_hex("ff8700").is_none(), "TickitPenRGB::from_hex needs #");
+    tap.is_str(format!("{}", orange), "#ff8700", "TickitPenRGB display");
+
+    let mut pen = TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, orange);
+    tap.ok(pen.get_rgb_attr(tickit::c::TICKIT_PEN_FG) == Some(orange), "get_rgb_attr");
+    tap.is_int(pen.get_colour_attr(tickit::c::TICKIT_PEN_FG), 208, "get_colour_attr gives nearest index");
+    tap.ok(pen.get_colour(tickit::c::TICKIT_PEN_FG) == Some(tickit::RGBColour(orange)), "get_colour RGB");
+    tap.ok(pen.clone().get_rgb_attr(tickit::c::TICKIT_PEN_FG) == Some(orange), "clone keeps RGB");
+    tap.ok(!pen.equiv(&TickitPen::new().with_colour_attr(tickit::c::TICKIT_PEN_FG, 208)), "RGB pen not equiv to its index");
+
+    pen.set_colour_attr(tickit::c::TICKIT_PEN_FG, 208);
+    tap.ok(pen.get_colour(tickit::c::TICKIT_PEN_FG) == Some(tickit::IndexColour(208)), "set_colour_attr replaces RGB");
+
+    tap.ok(pen.set_colour_attr_desc(tickit::c::TICKIT_PEN_BG, "#ff8700"), "set_colour_attr_desc #rrggbb");
+    tap.ok(pen.get_rgb_attr(tickit::c::TICKIT_PEN_BG) == Some(orange), "set_colour_attr_desc sets RGB");
+    pen.clear_attr(tickit::c::TICKIT_PEN_BG);
+    tap.ok(pen.get_colour(tickit::c::TICKIT_PEN_BG).is_none(), "clear_attr removes RGB");
+
+    let buffer = Mutex::new(Vec::<u8>::new());
+
+    fn output(tt: &mut tickit::TickitTerm, bytes: &[u8], buffer: &mut &Mutex<Vec<u8>>)
+    {
+        (*buffer.lock()).push_all(bytes);
+    }
+
+    let mut tt = TickitTerm::new_for_driver(PaletteVtable{colors: tickit::TRUECOLOR_COLORS, ext: false}).unwrap();
+    tt.set_output_func(output, &buffer);
+    tt.set_output_buffer(4096);
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, orange));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(208)\x1b[38;2;255;135;0m", "truecolour setpen sends index then RGB");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, TickitPenRGB::new(250, 135, 0)));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[38;2;250;135;0m", "RGB change within the same index");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_colour_attr(tickit::c::TICKIT_PEN_FG, 208));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[38;5;208m", "RGB back to its own index");
+
+    tt.setpen(&TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, orange));
+    let mut rb = TickitRenderBuffer::new(1, 10).unwrap();
+    rb.text_at(0, 0, "x", Some(&TickitPen::new().with_colour_attr(tickit::c::TICKIT_PEN_FG, 208)));
+    (*buffer.lock()).clear();
+    rb.flush_to_term(&mut tt);
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[38;5;208mx", "flush_to_term undoes RGB that libtickit doesn't know of");
+
+    let mut tt = TickitTerm::new_for_driver(PaletteVtable{colors: tickit::TRUECOLOR_COLORS, ext: true}).unwrap();
+    tt.set_output_func(output, &buffer);
+    tt.set_output_buffer(4096);
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, orange));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(208)FG(#ff8700)", "Rust driver takes RGB through chpen_ext");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_colour_attr(tickit::c::TICKIT_PEN_FG, 208));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "", "Rust driver sees RGB go without SGR");
+
+    let mut tt = make_xterm(&buffer);
+
+    // whatever xterm's terminfo claims, it's not RGB
+    let colors = tt.get_colors().unwrap() as int;
+    let idx = orange.nearest_index(colors);
+    let expect = if idx < 8
+    {
+        format!("\x1b[{}m", 30 + idx)
+    }
+    else if idx < 16
+    {
+        format!("\x1b[{}m", 90 + idx - 8)
+    }
+    else
+    {
+        format!("\x1b[38;5;{}m", idx)
+    };
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, orange));
+    tap.is_str_escape(uslice(&(*buffer.lock())), expect, "RGB degrades to the nearest palette colour");
+}
+
+#[test]
+fn test_49colour_downsample()
+{
+    use std::sync::Mutex;
+
+    use tickit::{TickitPen,TickitPenRGB,TickitRect,TickitRenderBuffer,TickitTerm};
+
+    let mut tap = taplib::Tap::new();
+
+    let buffer = Mutex::new(Vec::<u8>::new());
+
+    fn output(tt: &mut tickit::TickitTerm, bytes: &[u8], buffer: &mut &Mutex<Vec<u8>>)
+    {
+        (*buffer.lock()).push_all(bytes);
+    }
+
+    let fg = |idx: int| TickitPen::new().with_colour_attr(tickit::c::TICKIT_PEN_FG, idx);
+
+    let mut tt = TickitTerm::new_for_driver(PaletteVtable{colors: 16, ext: false}).unwrap();
+    tt.set_output_func(output, &buffer);
+    tt.set_output_buffer(4096);
+
+    tap.is_int(tt.palette_index(tickit::IndexColour(3), 16), 3, "palette_index in range");
+    tap.is_int(tt.palette_index(tickit::IndexColour(208), 16), 3, "palette_index 208 on 16");
+    tap.is_int(tt.palette_index(tickit::IndexColour(12), 8), 4, "palette_index 12 on 8");
+    tap.is_int(tt.palette_index(tickit::IndexColour(196), 8), 1, "palette_index 196 on 8");
+    tap.is_int(tt.palette_index(tickit::RGBColour(TickitPenRGB::new(255, 0, 0)), 16), 9, "palette_index RGB on 16");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&fg(208));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(3)", "setpen 208 on 16 colours");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&fg(196));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(9)", "chpen 196 on 16 colours");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&fg(5));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(5)", "palette colours are untouched");
+
+    let mut map = Vec::from_elem(256, -1i);
+    for idx in range(0i, 256)
+    {
+        map.as_mut_slice()[idx as uint] = if idx < 16 { idx } else { 7 };
+    }
+    tt.set_colour_map(Some(map));
+
+    (*buffer.lock()).clear();
+    tt.setpen(&fg(208));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(7)", "colour map overrides nearest colour");
+
+    tt.setpen(&fg(1));
+    tt.flush();
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_rgb_attr(tickit::c::TICKIT_PEN_FG, TickitPenRGB::new(250, 135, 0)));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(7)", "colour map applies to RGB colours");
+
+    tt.set_colour_map(None);
+
+    tt.setpen(&TickitPen::new());
+    tt.flush();
+    (*buffer.lock()).clear();
+    let mut rb = TickitRenderBuffer::new(1, 10).unwrap();
+    rb.text_at(0, 0, "orange", Some(&fg(208)));
+    rb.flush_to_term(&mut tt);
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(3)orange", "flush_to_term downsamples pens");
+
+    (*buffer.lock()).clear();
+    let mut rb = TickitRenderBuffer::new(1, 10).unwrap();
+    rb.eraserect(&TickitRect{top: 0, left: 2, lines: 1, cols: 4}, Some(&fg(196)));
+    rb.flush_to_term(&mut tt);
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(9)ERASE(4)", "flush_to_term erases with downsampled pens");
+
+    let mut tt = TickitTerm::new_for_driver(PaletteVtable{colors: 8, ext: false}).unwrap();
+    tt.set_output_func(output, &buffer);
+    tt.set_output_buffer(4096);
+
+    (*buffer.lock()).clear();
+    tt.setpen(&fg(12));
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "FG(4)", "setpen 12 on 8 colours");
+}
+
+#[test]
+fn test_50underline()
+{
+    use tickit::{TickitPen,IndexColour,RGBColour,TickitPenRGB};
+    use tickit::{NoUnderline,SingleUnderline,DoubleUnderline,CurlyUnderline,DottedUnderline};
+
+    let mut tap = taplib::Tap::new();
+
+    let under = tickit::c::TICKIT_PEN_UNDER;
+
+    let mut pen = TickitPen::new();
+    tap.ok(pen.get_under_style() == NoUnderline, "get_under_style default");
+    tap.ok(pen.maybe_get_under_style().is_none(), "maybe_get_under_style default");
+
+    pen.set_under_style(CurlyUnderline);
+    tap.ok(pen.get_bool_attr(under), "set_under_style sets TICKIT_PEN_UNDER");
+    tap.ok(pen.get_under_style() == CurlyUnderline, "get_under_style curly");
+    tap.ok(pen.clone().get_under_style() == CurlyUnderline, "clone keeps underline style");
+    tap.ok(!pen.equiv_attr(&TickitPen::new().with_bool_attr(under, true), under), "equiv_attr compares underline style");
+    tap.ok(pen.equiv_attr(&TickitPen::new().with_under_style(CurlyUnderline), under), "equiv_attr same underline style");
+
+    pen.set_bool_attr(under, true);
+    tap.ok(pen.get_under_style() == SingleUnderline, "set_bool_attr gives a single underline");
+
+    pen.set_under_colour(IndexColour(1));
+    tap.ok(pen.get_under_colour() == Some(IndexColour(1)), "get_under_colour");
+    tap.ok(!pen.equiv_attr(&TickitPen::new().with_bool_attr(under, true), under), "equiv_attr compares underline colour");
+    let mut copy = TickitPen::new();
+    copy.copy_attr(&pen, under);
+    tap.ok(copy.equiv(&pen), "copy_attr brings underline colour");
+
+    pen.clear();
+    tap.ok(pen.get_under_colour().is_none(), "clear removes underline colour");
+
+    tap.ok(tickit::TickitUnderline::from_sgr(3) == Some(CurlyUnderline), "TickitUnderline::from_sgr");
+    tap.ok(tickit::TickitUnderline::from_sgr(9).is_none(), "TickitUnderline::from_sgr out of range");
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let mut tt = make_xterm(&buffer);
+
+    tt.setpen(&TickitPen::new());
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_under_style(CurlyUnderline));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[4m", "curly underline is plain without ext_underline");
+
+    tt.setpen(&TickitPen::new());
+    tt.set_ext_underline(true);
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_under_style(CurlyUnderline));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[4m\x1b[4:3m", "setpen curly underline");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&TickitPen::new().with_under_style(DottedUnderline));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[4:4m", "chpen dotted underline");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&TickitPen::new().with_bool_attr(tickit::c::TICKIT_PEN_BOLD, true));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[1m", "chpen keeps underline style");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_under_style(SingleUnderline).with_under_colour(IndexColour(1)));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[22m\x1b[4:1;58;5;1m", "setpen single underline with colour");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new());
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[m", "setpen reset clears underline colour");
+
+    with_rust_mockterm(5, 10, |mut mt|
+    {
+        mt.tt.setpen(&TickitPen::new().with_under_style(DoubleUnderline).with_under_colour(IndexColour(2)));
+        mt.tt.print("x");
+        tap.is_termlog(&mut mt, "mockterm logs underline style and colour",
+            [SETPEN(pen_log.ul(DoubleUnderline).uc(IndexColour(2))), PRINT("x")]);
+        tap.ok(mt.get_display_pen(0, 0).get_under_style() == DoubleUnderline, "mockterm displays underline style");
+
+        mt.tt.chpen(&TickitPen::new().with_bool_attr(tickit::c::TICKIT_PEN_BOLD, true));
+        tap.is_termlog(&mut mt, "mockterm keeps underline style across chpen",
+            [SETPEN(pen_log.b(true).ul(DoubleUnderline).uc(IndexColour(2)))]);
+    });
+
+    let mut vt = tickit::mock::VirtualTerm::new(2, 10).unwrap();
+    vt.feed(b"\x1b[4:3;58:2::255:135:0mx\x1b[21;59my\x1b[24mz");
+    let x = vt.get_display_pen(0, 0);
+    tap.ok(x.get_under_style() == CurlyUnderline, "VirtualTerm 4:3");
+    tap.ok(x.get_under_colour() == Some(RGBColour(TickitPenRGB::new(255, 135, 0))), "VirtualTerm 58:2");
+    let y = vt.get_display_pen(0, 1);
+    tap.ok(y.get_under_style() == DoubleUnderline, "VirtualTerm 21");
+    tap.ok(y.get_under_colour().is_none(), "VirtualTerm 59");
+    tap.ok(vt.get_display_pen(0, 2).get_under_style() == NoUnderline, "VirtualTerm 24");
+}
+
+#[test]
+fn test_51pen_x_attrs()
+{
+    use tickit::TickitPen;
+    use tickit::c::{X_TICKIT_PEN_BLINK,X_TICKIT_PEN_DIM,X_TICKIT_PEN_OVERLINE,X_TICKIT_PEN_CONCEAL};
+    use tickit::c::{TICKIT_PEN_BOLD,TICKIT_PEN_ITALIC};
+
+    let mut tap = taplib::Tap::new();
+
+    tap.is_str(X_TICKIT_PEN_DIM.attrname(), "dim", "attrname dim");
+    tap.is_int(tickit::c::TickitPenAttr::lookup_attr("blink"), X_TICKIT_PEN_BLINK, "lookup_attr \"blink\"");
+    tap.is_int(tickit::c::TickitPenAttr::lookup_attr("conceal"), X_TICKIT_PEN_CONCEAL, "lookup_attr \"conceal\"");
+    tap.is_int(X_TICKIT_PEN_OVERLINE.attrtype(), tickit::c::TICKIT_PENTYPE_BOOL, "overline is a boolean attribute");
+
+    let mut pen = TickitPen::new();
+    tap.ok(!pen.has_attr(X_TICKIT_PEN_DIM), "pen lacks dim initially");
+    tap.ok(!pen.is_nonempty(), "pen initially empty");
+
+    pen.set_bool_attr(X_TICKIT_PEN_DIM, true);
+    tap.ok(pen.has_attr(X_TICKIT_PEN_DIM), "pen has dim after set");
+    tap.ok(pen.get_bool_attr(X_TICKIT_PEN_DIM), "get_bool_attr dim");
+    tap.ok(pen.is_nondefault(), "pen with dim is non-default");
+    tap.ok(pen.clone().get_bool_attr(X_TICKIT_PEN_DIM), "clone keeps dim");
+    tap.ok(!pen.equiv(&TickitPen::new()), "dim pen not equiv to an empty one");
+    tap.ok(!pen.equiv_attr(&TickitPen::new(), X_TICKIT_PEN_DIM), "equiv_attr dim");
+
+    let mut copy = TickitPen::new().with_bool_attr(X_TICKIT_PEN_DIM, false);
+    copy.copy(&pen, false);
+    tap.ok(!copy.get_bool_attr(X_TICKIT_PEN_DIM), "copy without overwrite keeps dim");
+    copy.copy_attr(&pen, X_TICKIT_PEN_DIM);
+    tap.ok(copy.get_bool_attr(X_TICKIT_PEN_DIM), "copy_attr dim");
+
+    pen.clear_attr(X_TICKIT_PEN_DIM);
+    tap.ok(!pen.has_attr(X_TICKIT_PEN_DIM), "pen lacks dim after clear");
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let mut tt = make_xterm(&buffer);
+
+    tt.setpen(&TickitPen::new());
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new().with_bool_attr(X_TICKIT_PEN_DIM, true));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[2m", "setpen dim");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&TickitPen::new().with_bool_attr(TICKIT_PEN_BOLD, true));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[1m", "chpen bold keeps dim");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&TickitPen::new().with_bool_attr(TICKIT_PEN_BOLD, false));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[m\x1b[2m", "dim is restored after a reset");
+
+    tt.chpen(&TickitPen::new().with_bool_attr(TICKIT_PEN_BOLD, true));
+
+    (*buffer.lock()).clear();
+    tt.chpen(&TickitPen::new().with_bool_attr(X_TICKIT_PEN_DIM, false));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[22;1m", "undimming keeps bold");
+
+    let pen = TickitPen::new()
+        .with_bool_attr(X_TICKIT_PEN_BLINK, true)
+        .with_bool_attr(X_TICKIT_PEN_OVERLINE, true)
+        .with_bool_attr(X_TICKIT_PEN_CONCEAL, true);
+
+    (*buffer.lock()).clear();
+    tt.setpen(&pen);
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[m\x1b[5;53;8m", "setpen blink, overline and conceal");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&pen);
+    tap.is_str_escape(uslice(&(*buffer.lock())), "", "chpen again is a no-op");
+
+    (*buffer.lock()).clear();
+    tt.setpen(&TickitPen::new()
+        .with_bool_attr(TICKIT_PEN_BOLD, true)
+        .with_bool_attr(TICKIT_PEN_ITALIC, true)
+        .with_bool_attr(X_TICKIT_PEN_DIM, true));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[1;3m\x1b[25;2;55;28m", "setpen swaps attributes");
+
+    (*buffer.lock()).clear();
+    tt.chpen(&TickitPen::new().with_bool_attr(TICKIT_PEN_BOLD, false));
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[22m\x1b[2m", "dim is restored after unbolding");
+
+    tt.setpen(&TickitPen::new().with_bool_attr(X_TICKIT_PEN_BLINK, true));
+    let mut rb = tickit::TickitRenderBuffer::new(1, 10).unwrap();
+    rb.text_at(0, 0, "x", None);
+    (*buffer.lock()).clear();
+    rb.flush_to_term(&mut tt);
+    tap.ok(uslice(&(*buffer.lock())).ends_with("\x1b[25mx"), "flush_to_term ends blink that libtickit doesn't know of");
+
+    with_rust_mockterm(5, 10, |mut mt|
+    {
+        mt.tt.setpen(&TickitPen::new().with_bool_attr(X_TICKIT_PEN_BLINK, true));
+        mt.tt.print("!");
+        tap.is_termlog(&mut mt, "mockterm logs blink",
+            [SETPEN(pen_log.blink(true)), PRINT("!")]);
+        tap.ok(mt.get_display_pen(0, 0).get_bool_attr(X_TICKIT_PEN_BLINK), "mockterm displays blink");
+
+        mt.tt.setpen(&TickitPen::new().with_bool_attr(X_TICKIT_PEN_BLINK, true).with_bool_attr(TICKIT_PEN_BOLD, true));
+        mt.tt.setpen(&TickitPen::new());
+        mt.tt.print("?");
+        tap.ok(!mt.get_display_pen(0, 1).get_bool_attr(X_TICKIT_PEN_BLINK), "mockterm setpen reset clears blink");
+    });
+
+    let mut vt = tickit::mock::VirtualTerm::new(2, 10).unwrap();
+    vt.feed(b"\x1b[1;2;5;53;8mx\x1b[22;25;55;28my");
+    let x = vt.get_display_pen(0, 0);
+    tap.ok(x.get_bool_attr(X_TICKIT_PEN_DIM) && x.get_bool_attr(X_TICKIT_PEN_BLINK), "VirtualTerm SGR 2 and 5");
+    tap.ok(x.get_bool_attr(X_TICKIT_PEN_OVERLINE) && x.get_bool_attr(X_TICKIT_PEN_CONCEAL), "VirtualTerm SGR 53 and 8");
+    tap.ok(!vt.get_display_pen(0, 1).is_nondefault(), "VirtualTerm SGR 22, 25, 55 and 28");
+}
+
+#[test]
+fn test_52pen_spec()
+{
+    use tickit::{TickitPen,PenSpecError};
+
+    let mut tap = taplib::Tap::new();
+
+    let pen = TickitPen::parse("fg:red bg:blue b u:double").unwrap();
+    tap.is_int(pen.get_colour_attr(tickit::c::TICKIT_PEN_FG), 1, "parse fg:red");
+    tap.is_int(pen.get_colour_attr(tickit::c::TICKIT_PEN_BG), 4, "parse bg:blue");
+    tap.ok(pen.get_bool_attr(tickit::c::TICKIT_PEN_BOLD), "parse b");
+    tap.ok(pen.get_under_style() == tickit::DoubleUnderline, "parse u:double");
+    tap.is_str(format!("{}", pen), "fg:1 bg:4 b u:double", "pen display");
+    tap.ok(TickitPen::parse(format!("{}", pen).as_slice()).unwrap().equiv(&pen), "pen display round-trips");
+
+    let spec = "fg:#ff8700 bg:default b:0 u:0 af:2 blink uc:208";
+    let pen = TickitPen::parse(spec).unwrap();
+    tap.is_str(format!("{}", pen), spec, "pen display of every kind of value");
+    tap.ok(TickitPen::parse(spec).unwrap().equiv(&pen), "full spec round-trips");
+
+    let pen = TickitPen::parse("  \t").unwrap();
+    tap.ok(!pen.is_nonempty(), "blank spec gives an empty pen");
+    tap.is_str(format!("{}", pen), "", "empty pen display");
+
+    tap.ok(tickit::c::TickitPenAttr::lookup_attr_opt("xyz").is_none(), "lookup_attr_opt unknown name");
+    tap.ok(tickit::c::TickitPenAttr::lookup_attr_opt("af") == Some(tickit::c::TICKIT_PEN_ALTFONT), "lookup_attr_opt libtickit's name");
+    tap.ok(tickit::c::TickitPenAttr::lookup_attr_opt("overline") == Some(tickit::c::X_TICKIT_PEN_OVERLINE), "lookup_attr_opt our own name");
+    tap.ok(TickitPen::parse("fg:red xyz b").err() == Some(PenSpecError(7, "xyz".to_string(), "unknown attribute")), "parse unknown attribute");
+    tap.ok(TickitPen::parse("fg:nosuch").err() == Some(PenSpecError(0, "fg:nosuch".to_string(), "bad colour")), "parse bad colour");
+    tap.ok(TickitPen::parse("b  af").err() == Some(PenSpecError(3, "af".to_string(), "missing value")), "parse missing value");
+    tap.ok(TickitPen::parse("u:wavy").err() == Some(PenSpecError(0, "u:wavy".to_string(), "bad underline style")), "parse bad underline style");
+    tap.ok(TickitPen::parse("i:yes").err() == Some(PenSpecError(0, "i:yes".to_string(), "bad boolean")), "parse bad boolean");
+    tap.is_str(format!("{}", TickitPen::parse("fg:red xyz").err().unwrap()).as_slice(), "bad pen spec at byte 7: unknown attribute 'xyz'", "PenSpecError display");
+}
+
+#[test]
+fn test_53theme()
+{
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use tickit::{Theme,TickitPen,ThemeLineError};
+
+    let mut tap = taplib::Tap::new();
+
+    let mut theme = Theme::parse("# comment\n\nborder: fg:blue b\nborder.focused: fg:red\nborder.focused.inner: i\n").unwrap();
+    tap.is_str(format!("{}", theme.get("border.focused").unwrap()), "fg:1", "theme get");
+    tap.ok(theme.get("border.inner").is_none(), "theme get without fallback");
+    tap.is_str(format!("{}", theme.pen("border.focused")), "fg:1 b", "theme pen falls back to parent");
+    tap.is_str(format!("{}", theme.pen("border.focused.inner")), "fg:1 b i", "theme pen falls back through two levels");
+    tap.is_str(format!("{}", theme.pen("border.other")), "fg:4 b", "theme pen of unset child");
+    tap.ok(!theme.pen("title").is_nonempty(), "theme pen of unset name is empty");
+
+    let focused = theme.derive("border.focused");
+    let title = theme.derive("title");
+    let changes = Rc::new(Cell::new(0u));
+    let c = changes.clone();
+    let guard = title.borrow_mut().bind(tickit::c::TICKIT_EV_CHANGE, box move |&mut: pen: &mut tickit::TickitPen, ev: &tickit::TickitEvent|
+    {
+        c.set(c.get() + 1);
+    });
+
+    theme.replace(Theme::parse("border: fg:green\nborder.focused: u\n").unwrap());
+    tap.is_str(format!("{}", *focused.borrow()), "fg:2 u", "derived pen updated on replace");
+    tap.is_int(changes.get() as int, 0, "unchanged derived pen not changed");
+
+    theme.set("title", TickitPen::parse("b").unwrap());
+    tap.is_str(format!("{}", *title.borrow()), "b", "derived pen updated on set");
+    tap.is_int(changes.get() as int, 1, "derived pen fires one change");
+    theme.set("title", TickitPen::parse("b u").unwrap());
+    tap.is_str(format!("{}", *title.borrow()), "b u", "derived pen updated in place");
+    tap.is_int(changes.get() as int, 2, "derived pen fires one change when updated in place");
+    theme.set("title", TickitPen::parse("b u:double").unwrap());
+    tap.is_str(format!("{}", *title.borrow()), "b u:double", "derived pen takes underline style");
+    tap.is_int(changes.get() as int, 3, "derived pen fires change for underline style alone");
+    theme.set("title", TickitPen::new());
+    tap.ok(!title.borrow().is_nonempty(), "derived pen emptied");
+    tap.is_int(changes.get() as int, 4, "derived pen fires one change when emptied");
+    drop(guard);
+    theme.set("title", TickitPen::parse("i").unwrap());
+    tap.is_int(changes.get() as int, 4, "no change fired through a dropped guard");
+
+    tap.ok(Theme::parse("border: fg:blue\nborder.focused fg:red\n").err() == Some(ThemeLineError(2, "expected 'name: pen spec'".to_string())), "theme line without colon");
+    tap.ok(Theme::parse("a b: fg:red").err() == Some(ThemeLineError(1, "bad style name 'a b'".to_string())), "theme bad style name");
+    tap.is_str(format!("{}", Theme::parse("\nx: fg:nosuch").err().unwrap()).as_slice(),
+        "theme line 2: bad pen spec at byte 3: bad colour 'fg:nosuch'", "theme bad pen spec");
+    tap.is_str(format!("{}", Theme::parse("  x:fg:red zz").err().unwrap()).as_slice(),
+        "theme line 1: bad pen spec at byte 11: unknown attribute 'zz'", "theme bad pen spec offset counts from line start");
+
+    match theme.reload(&Path::new("/nonexistent/tickit.theme"))
+    {
+        Err(tickit::FileError(path, _)) => tap.is_str(path, "/nonexistent/tickit.theme", "theme reload of missing file"),
+        _ => tap.ok(false, "theme reload of missing file"),
+    }
+    tap.is_str(format!("{}", *focused.borrow()), "fg:2 u", "failed reload leaves theme as it was");
+}
+
+#[test]
+fn test_54pen_attrs()
+{
+    use tickit::{TickitPen,BoolValue,IntValue,ColourValue,IndexColour,RGBColour,TickitPenRGB};
+    use tickit::c::{TICKIT_PEN_FG,TICKIT_PEN_BG,TICKIT_PEN_BOLD,TICKIT_PEN_UNDER,TICKIT_PEN_ALTFONT,X_TICKIT_PEN_DIM};
+
+    let mut tap = taplib::Tap::new();
+
+    let pen = TickitPen::parse("fg:#ff8700 bg:4 b:0 af:2 dim").unwrap();
+    let attrs: Vec<(tickit::c::TickitPenAttr, tickit::TickitPenValue)> = pen.attrs().collect();
+    tap.is_int(attrs.len() as int, 5, "attrs yields each set attribute");
+    tap.ok(attrs[0] == (TICKIT_PEN_FG, ColourValue(RGBColour(TickitPenRGB{r: 255, g: 135, b: 0}))), "attrs yields RGB colour");
+    tap.ok(attrs[1] == (TICKIT_PEN_BG, ColourValue(IndexColour(4))), "attrs yields index colour");
+    tap.ok(attrs[2] == (TICKIT_PEN_BOLD, BoolValue(false)), "attrs yields false bool");
+    tap.ok(attrs[3] == (TICKIT_PEN_ALTFONT, IntValue(2)), "attrs yields int");
+    tap.ok(attrs[4] == (X_TICKIT_PEN_DIM, BoolValue(true)), "attrs yields X attribute");
+    tap.ok(TickitPen::new().attrs().next().is_none(), "attrs of empty pen");
+
+    tap.ok(pen.get_value(TICKIT_PEN_UNDER).is_none(), "get_value of unset attribute");
+    let mut copy = TickitPen::new();
+    for (attr, value) in pen.attrs()
+    {
+        copy.set_value(attr, value);
+    }
+    tap.ok(copy.equiv(&pen), "set_value of every attribute rebuilds the pen");
+
+    let old = TickitPen::parse("fg:1 bg:4 b u").unwrap();
+    let new = TickitPen::parse("fg:1 bg:5 b u:curly i uc:2").unwrap();
+    tap.is_str(format!("{}", old.diff(&new)), "bg:5 u:curly i uc:2", "diff holds only what changed");
+    tap.is_str(format!("{}", new.diff(&old)), "bg:4 u", "diff leaves out attributes the new pen doesn't set");
+    tap.ok(!old.diff(&old).is_nonempty(), "diff of equal pens is empty");
+    tap.is_str(format!("{}", TickitPen::new().diff(&TickitPen::parse("b:0").unwrap())), "b:0", "diff includes attributes the old pen doesn't set");
+
+    let mut applied = old.clone();
+    applied.copy(&old.diff(&new), true);
+    tap.ok(applied.equiv(&new), "copying the diff gives the new pen");
+}
+
+#[test]
+fn test_55plain_pen()
+{
+    use std::hash::hash;
+    use tickit::{TickitPen,TickitPlainPen,IndexColour};
+
+    let mut tap = taplib::Tap::new();
+
+    let spec = "fg:#ff8700 bg:default b:0 u:curly i af:2 blink conceal:0 uc:208";
+    let pen = TickitPen::parse(spec).unwrap();
+    let plain = TickitPlainPen::from_pen(&pen);
+    tap.ok(plain.bg == Some(IndexColour(-1)), "plain pen holds default colour");
+    tap.ok(plain.under == Some(tickit::CurlyUnderline), "plain pen holds underline style");
+    tap.ok(plain.reverse.is_none(), "plain pen leaves unset attribute None");
+    tap.is_str(format!("{}", plain.to_pen()), spec, "plain pen converts back losslessly");
+
+    let copy = plain;
+    tap.ok(copy == plain, "plain pen copies compare equal");
+    tap.ok(TickitPlainPen::from_pen(&TickitPen::parse(spec).unwrap()) == plain, "plain pens of equal pens compare equal");
+    tap.ok(hash(&TickitPlainPen::from_pen(&TickitPen::parse(spec).unwrap())) == hash(&plain), "plain pens of equal pens hash equal");
+    tap.ok(TickitPlainPen::from_pen(&TickitPen::parse("b:0").unwrap()) != TickitPlainPen::from_pen(&TickitPen::new()), "plain pen tells false from unset");
+
+    tap.ok(TickitPlainPen::new() == TickitPlainPen::from_pen(&TickitPen::new()), "plain pen of empty pen");
+    tap.ok(!TickitPlainPen::new().to_pen().is_nonempty(), "empty plain pen converts to empty pen");
+}
+
+#[test]
+fn test_56unicode()
+{
+    use tickit::StringPos;
+
+    let mut tap = taplib::Tap::new();
+
+    let mut pos = StringPos::zero();
+
+    tap.is_int(StringPos::count("\U0001f600", &mut pos, None), 4, "count emoji");
+    tap.is_int(pos.graphemes, 1, "count emoji graphemes");
+    tap.is_int(pos.columns,   2, "count emoji columns");
+
+    StringPos::count("\u2764\ufe0f", &mut pos, None);
+    tap.is_int(pos.graphemes, 1, "count emoji presentation sequence graphemes");
+    tap.is_int(pos.columns,   2, "count emoji presentation sequence columns");
+    tap.is_int(tickit::mbswidth("\u2764"), 1, "mbswidth text-default emoji alone");
+    tap.is_int(tickit::mbswidth("\u231a\ufe0e"), 1, "mbswidth text presentation sequence");
+
+    StringPos::count("\U0001f468\u200d\U0001f469\u200d\U0001f467", &mut pos, None);
+    tap.is_int(pos.codepoints, 5, "count ZWJ sequence codepoints");
+    tap.is_int(pos.graphemes,  1, "count ZWJ sequence graphemes");
+    tap.is_int(pos.columns,    2, "count ZWJ sequence columns");
+
+    StringPos::count("\U0001f44d\U0001f3fd", &mut pos, None);
+    tap.is_int(pos.graphemes, 1, "count emoji modifier sequence graphemes");
+    tap.is_int(pos.columns,   2, "count emoji modifier sequence columns");
+
+    StringPos::count("\U0001f1ec\U0001f1e7\U0001f1eb\U0001f1f7", &mut pos, None);
+    tap.is_int(pos.graphemes, 2, "count flags graphemes");
+    tap.is_int(pos.columns,   4, "count flags columns");
+
+    StringPos::count("\u1100\u1161\u11a8", &mut pos, None);
+    tap.is_int(pos.graphemes, 1, "count Hangul jamo graphemes");
+    tap.is_int(pos.columns,   2, "count Hangul jamo columns");
+
+    tap.is_int(StringPos::count("\U0001f468\u200d\U0001f469", &mut pos, Some(StringPos::limit_columns(1))), 0, "column limit never chops ZWJ sequences");
+
+    let mut wide = StringPos::zero();
+    tap.ok(StringPos::countmore_with("\u253b\u2501\u253b", &mut wide, None, true) == Some(9), "countmore_with gives the bytes counted");
+    tap.is_int(wide.columns, 6, "countmore_with ambiguous wide");
+    let mut ctrl = StringPos::zero();
+    tap.ok(StringPos::countmore_with("a\u0007", &mut ctrl, None, false).is_none(), "countmore_with None for a control character");
+    tap.is_int(ctrl.bytes, 1, "countmore_with stops before a control character");
+    tap.is_int(tickit::mbswidth("\u253b\u2501\u253b"), 3, "ambiguous narrow by default");
+
+    tap.is_int(StringPos::count("a\u0000b", &mut pos, None), -1, "count -1 for embedded NUL");
+    tap.is_int(tickit::mbswidth("a\u0000b"), 1, "mbswidth stops at embedded NUL");
+    tap.is_int(StringPos::x_bcount(b"ab\xff", &mut pos, Some(StringPos::limit_bytes(2))), 2, "count stops at limit before invalid UTF-8");
+
+    let clusters: Vec<&str> = tickit::graphemes("cafe\u0301\U0001f44d\U0001f3fd!").collect();
+    tap.ok(clusters == vec!["c", "a", "f", "e\u0301", "\U0001f44d\U0001f3fd", "!"], "graphemes splits into clusters");
+}
+
+#[test]
+fn test_57layout()
+{
+    use tickit::layout::{wrap,truncate,align,AlignLeft,AlignCentre,AlignRight};
+
+    let mut tap = taplib::Tap::new();
+
+    let text = "the quick brown fox";
+    let lines = wrap(text, 10);
+    tap.is_int(lines.len(), 2, "wrap at spaces gives 2 lines");
+    tap.is_str(lines[0].text(text), "the quick", "wrap line 0");
+    tap.is_str(lines[1].text(text), "brown fox", "wrap line 1");
+    tap.is_int(lines[1].start.bytes, 10, "wrap line 1 start bytes");
+    tap.is_int(lines[1].start.columns, 10, "wrap line 1 start columns");
+    tap.is_int(lines[1].columns(), 9, "wrap line 1 columns");
+
+    let text = "abcdefgh";
+    let lines = wrap(text, 3);
+    tap.ok(lines.iter().map(|l| l.text(text)).collect::<Vec<&str>>() == vec!["abc", "def", "gh"], "wrap breaks long words");
+
+    let text = "one  \ntwo";
+    let lines = wrap(text, 10);
+    tap.is_str(lines[0].text(text), "one", "wrap at newline drops trailing spaces");
+    tap.is_str(lines[1].text(text), "two", "wrap after newline");
+    tap.is_int(lines[1].start.codepoints, 6, "wrap counts newline as a codepoint");
+
+    let text = "\u5f61\u5f61\u5f61";
+    let lines = wrap(text, 5);
+    tap.ok(lines.iter().map(|l| l.text(text)).collect::<Vec<&str>>() == vec!["\u5f61\u5f61", "\u5f61"], "wrap never splits wide characters");
+    tap.is_int(wrap(text, 1).len(), 3, "wrap gives graphemes wider than the line one each");
+
+    let text = "a   b";
+    let lines = wrap(text, 1);
+    tap.ok(lines.iter().map(|l| l.text(text)).collect::<Vec<&str>>() == vec!["a", "b"], "wrap drops spaces at the wrap");
+    tap.is_int(wrap("", 5).len(), 1, "wrap of empty string gives one empty line");
+
+    tap.is_str(truncate("hello world", 8, "..."), "hello...", "truncate with ellipsis");
+    tap.is_str(truncate("hello", 8, "..."), "hello", "truncate of short text");
+    tap.is_str(truncate("\u5f61\u5f61\u5f61", 5, "\u2026"), "\u5f61\u5f61\u2026", "truncate wide text");
+    tap.is_str(truncate("\u5f61\u5f61\u5f61", 4, "\u2026"), "\u5f61\u2026", "truncate never splits wide characters");
+    tap.is_str(truncate("hello", 2, "..."), "..", "truncate to less than the ellipsis");
+
+    tap.is_str(align("ab", 6, AlignLeft), "ab    ", "align left");
+    tap.is_str(align("ab", 6, AlignCentre), "  ab  ", "align centre");
+    tap.is_str(align("ab", 5, AlignCentre), " ab  ", "align centre with odd padding");
+    tap.is_str(align("ab", 6, AlignRight), "    ab", "align right");
+    tap.is_str(align("\u5f61", 4, AlignRight), "  \u5f61", "align wide text");
+    tap.is_str(align("abc", 2, AlignLeft), "abc", "align of wide text leaves it");
+}
+
+#[test]
+fn test_58paste()
+{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::duration::Duration;
+
+    let mut tap = taplib::Tap::new();
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let events = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let mut tt = make_xterm(&buffer);
+    tt.set_utf8(true);
+
+    tap.ok(tt.get_bracketed_paste() == Ok(false), "bracketed paste off initially");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_bracketed_paste(true).is_ok(), "set_bracketed_paste true");
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?2004h", "buffer after set_bracketed_paste true");
+    tap.ok(tt.get_bracketed_paste() == Ok(true), "bracketed paste on after set");
+
+    let e = events.clone();
+    let _guard = tt.bind(tickit::c::X_TICKIT_EV_PASTE | tickit::c::TICKIT_EV_KEY, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+    {
+        match *ev
+        {
+            tickit::PasteEvent{text} => e.borrow_mut().push(format!("paste {}", text)),
+            tickit::KeyEvent(tickit::KeyTextEvent{text, ..}) => e.borrow_mut().push(format!("text {}", text)),
+            _ => {}
+        }
+    });
+
+    tt.input_push_bytes("a\x1b[200~x\ry".as_bytes());
+    tap.ok(*events.borrow() == vec!["text a".to_string()], "keys before the paste arrive at once");
+
+    tt.input_push_bytes("\x1b[20".as_bytes());
+    tap.is_int(events.borrow().len(), 1, "no paste event before the end marker");
+
+    tt.input_push_bytes("1~b".as_bytes());
+    tap.ok(*events.borrow() == vec!["text a".to_string(), "paste x\ny".to_string(), "text b".to_string()],
+        "paste delivered whole, with newlines, across reads");
+
+    tt.input_push_bytes("\x1b[20".as_bytes());
+    tap.is_int(events.borrow().len(), 3, "possible marker held back");
+    tap.ok(tt.input_check_timeout().is_some(), "term has a timeout while holding input");
+
+    tt.input_push_bytes("0~z".as_bytes());
+    tap.ok(tt.input_check_timeout().map_or(false, |t| t > 50), "an open paste waits longer for its end");
+    tt.input_push_bytes("\x1b[201~".as_bytes());
+    tap.is_str(events.borrow()[3].as_slice(), "paste z", "paste after a held start marker");
+
+    tt.input_push_bytes("\x1b[200~cut".as_bytes());
+    let timeout_msec = tt.input_check_timeout().unwrap();
+    std::io::timer::sleep(Duration::milliseconds(timeout_msec as i64));
+    tt.input_check_timeout();
+    tap.is_str(events.borrow().last().unwrap().as_slice(), "paste cut", "open paste cut short after waiting the timeout it gave");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_bracketed_paste(false).is_ok(), "set_bracketed_paste false");
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?2004l", "buffer after set_bracketed_paste false");
+
+    events.borrow_mut().clear();
+    tt.input_push_bytes("\x1b[200~x\x1b[201~".as_bytes());
+    tap.ok(!events.borrow().iter().any(|e| e.as_slice().starts_with("paste")), "no paste event with bracketed paste off");
+
+    with_rust_mockterm(25, 80, |mut mt|
+    {
+        tap.ok(mt.tt.set_bracketed_paste(true).is_ok(), "mockterm takes bracketed paste");
+        tap.ok(mt.tt.get_bracketed_paste() == Ok(true), "mockterm bracketed paste on");
+    });
+}
+
+#[test]
+fn test_59focus()
+{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut tap = taplib::Tap::new();
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let events = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let mut tt = make_xterm(&buffer);
+    tt.set_utf8(true);
+
+    tap.ok(tt.get_focus_reports() == Ok(false), "focus reports off initially");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_focus_reports(true).is_ok(), "set_focus_reports true");
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1004h", "buffer after set_focus_reports true");
+    tap.ok(tt.get_focus_reports() == Ok(true), "focus reports on after set");
+
+    let e = events.clone();
+    let _guard = tt.bind(tickit::c::X_TICKIT_EV_FOCUS | tickit::c::TICKIT_EV_KEY, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+    {
+        match *ev
+        {
+            tickit::FocusEvent{focused} => e.borrow_mut().push(format!("focus {}", focused)),
+            tickit::KeyEvent(tickit::KeyTextEvent{text, ..}) => e.borrow_mut().push(format!("text {}", text)),
+            _ => {}
+        }
+    });
+
+    tt.input_push_bytes("\x1b[O".as_bytes());
+    tap.ok(*events.borrow() == vec!["focus false".to_string()], "focus out");
+
+    tt.input_push_bytes("a\x1b[".as_bytes());
+    tt.input_push_bytes("Ib".as_bytes());
+    tap.ok(*events.borrow() == vec!["focus false".to_string(), "text a".to_string(), "focus true".to_string(), "text b".to_string()],
+        "focus in between keys, across reads");
+
+    tt.set_bracketed_paste(true).unwrap();
+    events.borrow_mut().clear();
+    tt.input_push_bytes("\x1b[200~\x1b[O\x1b[201~".as_bytes());
+    tap.ok(events.borrow().is_empty(), "no focus event inside a paste");
+
+    (*buffer.lock()).clear();
+    tap.ok(tt.set_focus_reports(false).is_ok(), "set_focus_reports false");
+    tt.flush();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1004l", "buffer after set_focus_reports false");
+
+    tt.input_push_bytes("\x1b[O".as_bytes());
+    tap.ok(!events.borrow().iter().any(|e| e.as_slice().starts_with("focus")), "no focus event with focus reports off");
+}
+
+#[test]
+fn test_60sgr_mouse()
+{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    let mut tap = taplib::Tap::new();
+
+    let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+
+    let events = Rc::new(RefCell::new(Vec::<String>::new()));
+
+    let mut tt = make_xterm(&buffer);
+    tt.set_utf8(true);
+
+    (*buffer.lock()).clear();
+    tt.set_mouse_mode(tickit::c::TICKIT_TERM_MOUSEMODE_MOVE).unwrap();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1003h\x1b[?1006h", "mouse mode asks for SGR reports");
+
+    let e = events.clone();
+    let _guard = tt.bind(tickit::c::TICKIT_EV_MOUSE, box move |&mut: tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent|
+    {
+        let s = match *ev
+        {
+            tickit::MouseEvent(tickit::MousePressEvent{button, line, col, mod_}) => format!("press {} {},{} {}", button, line, col, mods(mod_)),
+            tickit::MouseEvent(tickit::MouseDragEvent{button, line, col, mod_}) => format!("drag {} {},{} {}", button, line, col, mods(mod_)),
+            tickit::MouseEvent(tickit::MouseReleaseEvent{button, line, col, mod_}) => format!("release {} {},{} {}", button, line, col, mods(mod_)),
+            tickit::MouseEvent(tickit::MouseWheelEvent{dir, line, col, mod_}) => format!("wheel {} {},{} {}", dir, line, col, mods(mod_)),
+            tickit::MouseEvent(tickit::MouseMoveEvent{line, col, mod_}) => format!("move {},{} {}", line, col, mods(mod_)),
+            _ => "other".to_string(),
+        };
+        e.borrow_mut().push(s);
+    });
+
+    fn mods(mod_: tickit::c::X_Tickit_Mod) -> String
+    {
+        let mut s = String::new();
+        for &(m, name) in [(tickit::c::TICKIT_MOD_SHIFT, "S"), (tickit::c::TICKIT_MOD_ALT, "A"), (tickit::c::TICKIT_MOD_CTRL, "C")].iter()
+        {
+            if mod_.contains(m)
+            {
+                s.push_str(name);
+            }
+        }
+        if s.is_empty() { "-".to_string() } else { s }
+    }
+
+    // the last event pushing 'bytes' makes
+    fn next(tt: &mut tickit::TickitTerm, events: &Rc<RefCell<Vec<String>>>, bytes: &str) -> String
+    {
+        tt.input_push_bytes(bytes.as_bytes());
+        let s = events.borrow_mut().pop().unwrap_or("none".to_string());
+        events.borrow_mut().clear();
+        s
+    }
+
+    tap.is_str(next(&mut tt, &events, "\x1b[<0;300;5M"), "press 1 4,299 -", "SGR press beyond column 223");
+    tap.is_str(next(&mut tt, &events, "\x1b[<32;301;5M"), "drag 1 4,300 -", "SGR drag");
+    tap.is_str(next(&mut tt, &events, "\x1b[<0;301;5m"), "release 1 4,300 -", "SGR release reports its button");
+    tap.is_str(next(&mut tt, &events, "\x1b[<2;1;1m"), "release 3 0,0 -", "SGR release of button 3");
+    tap.is_str(next(&mut tt, &events, "\x1b[<35;7;8M"), "move 7,6 -", "SGR motion with no button");
+    tap.is_str(next(&mut tt, &events, "\x1b[<65;2;3M"), "wheel TICKIT_MOUSEWHEEL_DOWN 2,1 -", "SGR wheel down");
+    tap.is_str(next(&mut tt, &events, "\x1b[<65;2;3m"), "none", "SGR wheel release makes no event");
+    tap.is_str(next(&mut tt, &events, "\x1b[<0;0;3M"), "none", "SGR report at column 0 makes no event");
+    tap.is_str(next(&mut tt, &events, "\x1b[<0;2;0M"), "none", "SGR report at line 0 makes no event");
+    tap.is_str(next(&mut tt, &events, "\x1b[<20;1;1M"), "press 1 0,0 SC", "SGR press with shift and ctrl");
+
+    tt.input_push_bytes("\x1b[<0;12".as_bytes());
+    tap.ok(events.borrow().is_empty(), "no event for a partial SGR report");
+    tap.is_str(next(&mut tt, &events, ";3M"), "press 1 2,11 -", "SGR press across reads");
+
+    tap.is_str(next(&mut tt, &events, "\x1b[M !!"), "press 1 0,0 -", "X10 press still arrives from libtickit");
+
+    (*buffer.lock()).clear();
+    tt.set_mouse_mode(tickit::c::TICKIT_TERM_MOUSEMODE_OFF).unwrap();
+    tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1003l\x1b[?1006l", "mouse mode off stops SGR reports");
+
+    with_rust_mockterm(25, 80, |mut mt|
+    {
+        fn output(tt: &mut tickit::TickitTerm, bytes: &[u8], buffer: &mut &std::sync::Mutex<Vec<u8>>)
+        {
+            (*buffer.lock()).push_all(bytes);
+        }
+
+        let buffer = std::sync::Mutex::new(Vec::<u8>::new());
+        mt.tt.set_output_func(output, &buffer);
+
+        mt.tt.set_mouse_mode(tickit::c::TICKIT_TERM_MOUSEMODE_CLICK).unwrap();
+        tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1006h", "Rust driver's mouse mode asks for SGR reports");
+
+        (*buffer.lock()).clear();
+        drop(mt);
+        tap.is_str_escape(uslice(&(*buffer.lock())), "\x1b[?1006l", "SGR reports stop when the term goes");
+    });
+}
+
+#[test]
+fn test_61mouse_buttons()
+{
+    use std::sync::Mutex;
+    use tickit::c::{TICKIT_MOUSE_BUTTON_LEFT,TICKIT_MOUSE_BUTTON_WHEEL_LEFT,TICKIT_MOUSE_BUTTON_BACK,TICKIT_MOUSE_BUTTON_FORWARD,TICKIT_MOUSE_BUTTON_11};
+
+    let mut tap = taplib::Tap::new();
+
+    type MouseEventData = tickit::TickitMouseEvent;
+
+    let mouse_event: Mutex<Option<MouseEventData>> = Mutex::new(None);
+
+    fn on_mouse(tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent, data: &mut &Mutex<Option<MouseEventData>>)
+    {
+        let mouse = match *ev { tickit::MouseEvent(m) => { m } _ => { fail!(); } };
+        *data.lock() = Some(mouse);
+    }
+
+    let mut mt = make_term(25, 80);
+    mt.tt.set_mouse_mode(tickit::c::TICKIT_TERM_MOUSEMODE_DRAG).unwrap();
+
+    mt.tt.bind_event(tickit::c::TICKIT_EV_MOUSE, on_mouse, &mouse_event);
+
+    mt.tt.input_push_bytes("\x1b[<66;3;2M".as_bytes());
+
+    let ev = (*mouse_event.lock()).take().unwrap();
+    match ev
+    {
+        tickit::MouseWheelEvent{dir, line, col, mod_} =>
+        {
+            tap.pass("mousetype after wheel left");
+            tap.is_int(dir, tickit::c::TICKIT_MOUSEWHEEL_LEFT, "mousewheel after wheel left");
+            tap.is_int(line,   1,                    "mouseline after wheel left");
+            tap.is_int(col,    2,                    "mousecol after wheel left");
+            tap.ok(mod_.is_empty(),                    "mousemod after wheel left");
+            tap.ok(ev.button() == Some(TICKIT_MOUSE_BUTTON_WHEEL_LEFT), "mouse button after wheel left");
+        }
+        _ =>
+        {
+            tap.bypass(6, "mousetype after wheel left");
+        }
+    }
+
+    mt.tt.input_push_bytes("\x1b[<71;1;1M".as_bytes());
+
+    let ev = (*mouse_event.lock()).take().unwrap();
+    match ev
+    {
+        tickit::MouseWheelEvent{dir, line, col, mod_} =>
+        {
+            tap.pass("mousetype after shift-wheel right");
+            tap.is_int(dir, tickit::c::TICKIT_MOUSEWHEEL_RIGHT, "mousewheel after shift-wheel right");
+            tap.ok(mod_ == tickit::c::TICKIT_MOD_SHIFT, "mousemod after shift-wheel right");
+        }
+        _ =>
+        {
+            tap.bypass(3, "mousetype after shift-wheel right");
+        }
+    }
+
+    mt.tt.input_push_bytes("\x1b[<128;5;6M".as_bytes());
+
+    let ev = (*mouse_event.lock()).take().unwrap();
+    match ev
+    {
+        tickit::MousePressEvent{button, line, col, mod_} =>
+        {
+            tap.pass("mousetype after back button press");
+            tap.is_int(button, 8,                    "mousebutton after back button press");
+            tap.is_int(line,   5,                    "mouseline after back button press");
+            tap.is_int(col,    4,                    "mousecol after back button press");
+            tap.ok(ev.button() == Some(TICKIT_MOUSE_BUTTON_BACK), "named mouse button after back button press");
+        }
+        _ =>
+        {
+            tap.bypass(5, "mousetype after back button press");
+        }
+    }
+
+    mt.tt.input_push_bytes("\x1b[<129;5;6m".as_bytes());
+
+    let ev = (*mouse_event.lock()).take().unwrap();
+    match ev
+    {
+        tickit::MouseReleaseEvent{button, ..} =>
+        {
+            tap.pass("mousetype after forward button release");
+            tap.is_int(button, 9,                    "mousebutton after forward button release");
+            tap.ok(ev.button() == Some(TICKIT_MOUSE_BUTTON_FORWARD), "named mouse button after forward button release");
+        }
+        _ =>
+        {
+            tap.bypass(3, "mousetype after forward button release");
+        }
+    }
+
+    mt.tt.input_push_bytes("\x1b[<131;1;1M".as_bytes());
+
+    let ev = (*mouse_event.lock()).take().unwrap();
+    match ev
+    {
+        tickit::MousePressEvent{button, ..} =>
+        {
+            tap.pass("mousetype after button 11 press");
+            tap.is_int(button, 11,                   "mousebutton after button 11 press");
+            tap.ok(ev.button() == Some(TICKIT_MOUSE_BUTTON_11), "named mouse button after button 11 press");
+        }
+        _ =>
+        {
+            tap.bypass(3, "mousetype after button 11 press");
+        }
+    }
+
+    mt.tt.input_push_bytes("\x1b[<0;1;1M".as_bytes());
+
+    tap.ok((*mouse_event.lock()).take().unwrap().button() == Some(TICKIT_MOUSE_BUTTON_LEFT), "named mouse button after left press");
+}
+
+#[test]
+fn test_62key()
+{
+    use std::sync::Mutex;
+    use tickit::key::{Key,KeyPress,Char,Function,Up,Enter,Keypad5,KeypadEnter};
+    use tickit::c::{TICKIT_MOD_ALT,TICKIT_MOD_CTRL,TICKIT_MOD_SHIFT,X_Tickit_Mod};
+
+    let mut tap = taplib::Tap::new();
+
+    tap.ok(from_str::<Key>("a") == Some(Char('a')), "from_str a");
+    tap.ok(from_str::<Key>("Space") == Some(Char(' ')), "from_str Space");
+    tap.ok(from_str::<Key>("Enter") == Some(Enter), "from_str Enter");
+    tap.ok(from_str::<Key>("F1") == Some(Function(1)), "from_str F1");
+    tap.ok(from_str::<Key>("F35") == Some(Function(35)), "from_str F35");
+    tap.ok(from_str::<Key>("F36").is_none(), "from_str F36 fails");
+    tap.ok(from_str::<Key>("KP5") == Some(Keypad5), "from_str KP5");
+    tap.ok(from_str::<Key>("Entre").is_none(), "from_str of a typo fails");
+    tap.ok(from_str::<Key>("").is_none(), "from_str of nothing fails");
+    tap.ok(from_str::<Key>("\u00e9") == Some(Char('\u00e9')), "from_str non-ASCII character");
+
+    tap.ok(from_str::<KeyPress>("C-a") == Some(KeyPress::new(Char('a'), TICKIT_MOD_CTRL)), "from_str C-a");
+    tap.ok(from_str::<KeyPress>("S-M-Up") == Some(KeyPress::new(Up, TICKIT_MOD_ALT | TICKIT_MOD_SHIFT)), "from_str takes modifiers in any order");
+    tap.ok(from_str::<KeyPress>("C--") == Some(KeyPress::new(Char('-'), TICKIT_MOD_CTRL)), "from_str C--");
+    tap.ok(from_str::<KeyPress>("M-").is_none(), "from_str of a bare modifier fails");
+
+    tap.is_str(format!("{}", Char(' ')), "Space", "show Space");
+    tap.is_str(format!("{}", Function(12)), "F12", "show F12");
+    tap.is_str(format!("{}", KeypadEnter), "KPEnter", "show KPEnter");
+    tap.is_str(format!("{}", KeyPress::new(Up, TICKIT_MOD_SHIFT | TICKIT_MOD_CTRL | TICKIT_MOD_ALT)), "M-C-S-Up", "show writes modifiers in libtermkey's order");
+    for s in ["a", "C-a", "M-S-Up", "F12", "KP5", "Space", "C--", "DEL"].iter()
+    {
+        tap.is_str(format!("{}", from_str::<KeyPress>(*s).unwrap()), *s, format!("{} round-trips", s).as_slice());
+    }
+
+    type KeyEventData = Option<KeyPress>;
+
+    let key_event: Mutex<Option<KeyEventData>> = Mutex::new(None);
+
+    fn on_key(tt: &mut tickit::TickitTerm, ev: &tickit::TickitEvent, data: &mut &Mutex<Option<KeyEventData>>)
+    {
+        let key = match *ev { tickit::KeyEvent(k) => { k } _ => { fail!(); } };
+        *data.lock() = Some(key.key());
+    }
+
+    let mut tt = tickit::TickitTerm::new_for_termtype("xterm").unwrap();
+    tt.set_utf8(true);
+
+    tt.bind_event(tickit::c::TICKIT_EV_KEY, on_key, &key_event);
+
+    tt.input_push_bytes("A".as_bytes());
+    tap.ok((*key_event.lock()).take().unwrap() == Some(KeyPress::new(Char('A'), X_Tickit_Mod::empty())), "key after push_bytes A");
+
+    tt.input_push_bytes("\x1b[A".as_bytes());
+    tap.ok((*key_event.lock()).take().unwrap() == Some(KeyPress::new(Up, X_Tickit_Mod::empty())), "key after push_bytes Up");
+
+    tt.input_push_bytes("\x01".as_bytes());
+    tap.ok((*key_event.lock()).take().unwrap() == Some(KeyPress::new(Char('a'), TICKIT_MOD_CTRL)), "key after push_bytes C-a");
+
+    tt.input_push_bytes("\x1b[1;5A".as_bytes());
+    tap.ok((*key_event.lock()).take().unwrap() == Some(KeyPress::new(Up, TICKIT_MOD_CTRL)), "key after push_bytes C-Up");
+}
@@ -0,0 +1,160 @@
+// Named pens for the parts of an application ("error", "selection",
+// "border.focused"), so that its look is set in one place. A dotted name
+// falls back attribute by attribute to its parents: "border.focused" takes
+// whatever it doesn't set itself from "border".
+//
+// Theme files hold one style per line, as `name: pen spec` (see penspec.rs);
+// blank lines and lines starting with '#' are skipped.
+
+use std::cell::RefCell;
+use std::io::File;
+use std::rc::{Rc,Weak};
+
+use TickitPen;
+use {TickitError,FileError,PenSpecError,ThemeLineError};
+
+pub struct Theme
+{
+    // in the order they were first set
+    styles: Vec<(String, TickitPen)>,
+    derived: Vec<(String, Weak<RefCell<TickitPen>>)>,
+}
+
+impl Theme
+{
+    pub fn new() -> Theme
+    {
+        Theme{styles: Vec::new(), derived: Vec::new()}
+    }
+
+    pub fn parse(text: &str) -> Result<Theme, TickitError>
+    {
+        let mut theme = Theme::new();
+        for (i, raw) in text.lines().enumerate()
+        {
+            let line = raw.trim();
+            if line.is_empty() || line.starts_with("#")
+            {
+                continue;
+            }
+            let (name, spec) = match line.find(':')
+            {
+                Some(colon) => (line.slice_to(colon).trim(), line.slice_from(colon + 1)),
+                None => return Err(ThemeLineError(i + 1, "expected 'name: pen spec'".to_string())),
+            };
+            if name.is_empty() || name.chars().any(|c| c.is_whitespace())
+            {
+                return Err(ThemeLineError(i + 1, format!("bad style name '{}'", name)));
+            }
+            match TickitPen::parse(spec)
+            {
+                Ok(pen) => theme.put(name, pen),
+                // from the start of the line, rather than of the spec
+                Err(PenSpecError(offset, token, why)) =>
+                {
+                    let start = raw.len() - raw.trim_left().len() + line.len() - spec.len();
+                    let e = PenSpecError(start + offset, token, why);
+                    return Err(ThemeLineError(i + 1, e.to_string()));
+                }
+                Err(e) => return Err(ThemeLineError(i + 1, e.to_string())),
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, TickitError>
+    {
+        match File::open(path).read_to_string()
+        {
+            Ok(text) => Theme::parse(text.as_slice()),
+            Err(e) => Err(FileError(path.display().to_string(), e.to_string())),
+        }
+    }
+
+    /// Replace the styles with those of `path`, updating derived pens. On
+    /// error the theme is left as it was.
+    pub fn reload(&mut self, path: &Path) -> Result<(), TickitError>
+    {
+        let theme = try!(Theme::load(path));
+        self.replace(theme);
+        Ok(())
+    }
+
+    /// Take the styles of `other`, updating derived pens.
+    pub fn replace(&mut self, other: Theme)
+    {
+        self.styles = other.styles;
+        self.refresh();
+    }
+
+    pub fn set(&mut self, name: &str, pen: TickitPen)
+    {
+        self.put(name, pen);
+        self.refresh();
+    }
+
+    /// The style set for exactly `name`, without fallback.
+    pub fn get(&self, name: &str) -> Option<&TickitPen>
+    {
+        self.styles.iter().find(|&&(ref n, _)| n.as_slice() == name).map(|&(_, ref pen)| pen)
+    }
+
+    /// The pen for `name`, with anything it doesn't set taken from its
+    /// parents; empty if neither it nor any parent is set.
+    pub fn pen(&self, name: &str) -> TickitPen
+    {
+        let mut pen = TickitPen::new();
+        let mut name = name;
+        loop
+        {
+            match self.get(name)
+            {
+                Some(style) => pen.copy(style, false),
+                None => {}
+            }
+            match name.rfind('.')
+            {
+                Some(dot) => name = name.slice_to(dot),
+                None => break,
+            }
+        }
+        pen
+    }
+
+    /// A pen for `name` that the theme keeps up to date as it is changed
+    /// or reloaded, in place, with one TICKIT_EV_CHANGE each time it
+    /// changes. Don't hold it borrowed across those calls.
+    pub fn derive(&mut self, name: &str) -> Rc<RefCell<TickitPen>>
+    {
+        let pen = Rc::new(RefCell::new(self.pen(name)));
+        self.derived.push((name.to_string(), pen.downgrade()));
+        pen
+    }
+
+    fn put(&mut self, name: &str, pen: TickitPen)
+    {
+        for &(ref n, ref mut style) in self.styles.iter_mut()
+        {
+            if n.as_slice() == name
+            {
+                *style = pen;
+                return;
+            }
+        }
+        self.styles.push((name.to_string(), pen));
+    }
+
+    fn refresh(&mut self)
+    {
+        self.derived.retain(|&(_, ref weak)| weak.upgrade().is_some());
+        for &(ref name, ref weak) in self.derived.iter()
+        {
+            let new = self.pen(name.as_slice());
+            match weak.upgrade()
+            {
+                Some(rc) => rc.borrow_mut().update(&new),
+                None => {}
+            }
+        }
+    }
+}
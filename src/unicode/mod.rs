@@ -0,0 +1,295 @@
+// Grapheme clusters and their widths in terminal columns, in Rust rather
+// than through libtickit, so that strings with NULs work and emoji come out
+// as wide as terminals draw them.
+//
+// Clusters follow UAX #29's extended grapheme clusters, except that Prepend
+// characters start a cluster of their own. A cluster is as wide as its first
+// character, unless it is an emoji made wide by U+FE0F (or narrow by U+FE0E)
+// or a pair of regional indicators making a flag; ZWJ sequences and skin
+// tone modifiers join the cluster and add nothing.
+
+use std::sync::atomic::{AtomicBool,INIT_ATOMIC_BOOL,SeqCst};
+
+mod tables;
+
+static AMBIGUOUS_WIDE: AtomicBool = INIT_ATOMIC_BOOL;
+
+static ZWJ: char = '\u200d';
+static TEXT_PRESENTATION: char = '\ufe0e';
+static EMOJI_PRESENTATION: char = '\ufe0f';
+
+// Extended_Pictographic, which Python's unicodedata doesn't have
+static PICTOGRAPHIC: &'static [(char, char)] = &[
+    ('\u00a9', '\u00a9'), ('\u00ae', '\u00ae'), ('\u203c', '\u203c'), ('\u2049', '\u2049'),
+    ('\u2122', '\u2122'), ('\u2139', '\u2139'), ('\u2194', '\u2199'), ('\u21a9', '\u21aa'),
+    ('\u231a', '\u231b'), ('\u2328', '\u2328'), ('\u2388', '\u2388'), ('\u23cf', '\u23cf'),
+    ('\u23e9', '\u23f3'), ('\u23f8', '\u23fa'), ('\u24c2', '\u24c2'), ('\u25aa', '\u25ab'),
+    ('\u25b6', '\u25b6'), ('\u25c0', '\u25c0'), ('\u25fb', '\u25fe'), ('\u2600', '\u2605'),
+    ('\u2607', '\u2612'), ('\u2614', '\u2685'), ('\u2690', '\u2705'), ('\u2708', '\u2712'),
+    ('\u2714', '\u2714'), ('\u2716', '\u2716'), ('\u271d', '\u271d'), ('\u2721', '\u2721'),
+    ('\u2728', '\u2728'), ('\u2733', '\u2734'), ('\u2744', '\u2744'), ('\u2747', '\u2747'),
+    ('\u274c', '\u274c'), ('\u274e', '\u274e'), ('\u2753', '\u2755'), ('\u2757', '\u2757'),
+    ('\u2763', '\u2767'), ('\u2795', '\u2797'), ('\u27a1', '\u27a1'), ('\u27b0', '\u27b0'),
+    ('\u27bf', '\u27bf'), ('\u2934', '\u2935'), ('\u2b05', '\u2b07'), ('\u2b1b', '\u2b1c'),
+    ('\u2b50', '\u2b50'), ('\u2b55', '\u2b55'), ('\u3030', '\u3030'), ('\u303d', '\u303d'),
+    ('\u3297', '\u3297'), ('\u3299', '\u3299'),
+    ('\U0001f000', '\U0001f0ff'), ('\U0001f10d', '\U0001f10f'), ('\U0001f12f', '\U0001f12f'),
+    ('\U0001f16c', '\U0001f171'), ('\U0001f17e', '\U0001f17f'), ('\U0001f18e', '\U0001f18e'),
+    ('\U0001f191', '\U0001f19a'), ('\U0001f1ad', '\U0001f1e5'), ('\U0001f201', '\U0001f20f'),
+    ('\U0001f21a', '\U0001f21a'), ('\U0001f22f', '\U0001f22f'), ('\U0001f232', '\U0001f23a'),
+    ('\U0001f23c', '\U0001f23f'), ('\U0001f249', '\U0001f3fa'), ('\U0001f400', '\U0001f53d'),
+    ('\U0001f546', '\U0001f64f'), ('\U0001f680', '\U0001f6ff'), ('\U0001f774', '\U0001f77f'),
+    ('\U0001f7d5', '\U0001f7ff'), ('\U0001f80c', '\U0001f80f'), ('\U0001f848', '\U0001f84f'),
+    ('\U0001f85a', '\U0001f85f'), ('\U0001f888', '\U0001f88f'), ('\U0001f8ae', '\U0001f8ff'),
+    ('\U0001f90c', '\U0001f93a'), ('\U0001f93c', '\U0001f945'), ('\U0001f947', '\U0001faff'),
+    ('\U0001fc00', '\U0001fffd'),
+];
+
+/// Whether East Asian ambiguous-width characters (box drawing, Greek and
+/// Cyrillic letters, many symbols) take two columns rather than one, as
+/// they do on terminals set up for CJK. Off unless set_ambiguous_wide says.
+pub fn ambiguous_wide() -> bool
+{
+    AMBIGUOUS_WIDE.load(SeqCst)
+}
+
+pub fn set_ambiguous_wide(wide: bool)
+{
+    AMBIGUOUS_WIDE.store(wide, SeqCst)
+}
+
+fn in_table(ch: char, table: &[(char, char)]) -> bool
+{
+    let (mut lo, mut hi) = (0u, table.len());
+    while lo < hi
+    {
+        let mid = (lo + hi) / 2;
+        let (first, last) = table[mid];
+        if ch < first
+        {
+            hi = mid;
+        }
+        else if ch > last
+        {
+            lo = mid + 1;
+        }
+        else
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn is_control(ch: char) -> bool
+{
+    ch < ' ' || ('\u007f' <= ch && ch < '\u00a0')
+}
+
+fn is_regional_indicator(ch: char) -> bool
+{
+    '\U0001f1e6' <= ch && ch <= '\U0001f1ff'
+}
+
+fn is_pictographic(ch: char) -> bool
+{
+    in_table(ch, PICTOGRAPHIC)
+}
+
+// the characters that take U+FE0F for an emoji, without being pictographs
+fn is_keycap_base(ch: char) -> bool
+{
+    ch == '#' || ch == '*' || ('0' <= ch && ch <= '9')
+}
+
+/// Columns taken by a single character, or None for the C0 and C1 controls
+/// and DEL, which have no width a terminal agrees on.
+pub fn char_width(ch: char, ambiguous_wide: bool) -> Option<uint>
+{
+    if is_control(ch)
+    {
+        return None;
+    }
+    if ch < '\u007f'
+    {
+        return Some(1);
+    }
+    if ch == '\u00ad'
+    {
+        // soft hyphen, which terminals show
+        return Some(1);
+    }
+    // marks and format characters, and the vowel and final jamo that draw
+    // in the cells of a Hangul syllable's first
+    if in_table(ch, tables::COMBINING) || in_table(ch, tables::FORMAT)
+        || ('\u1160' <= ch && ch <= '\u11ff') || ('\ud7b0' <= ch && ch <= '\ud7ff')
+    {
+        return Some(0);
+    }
+    if in_table(ch, tables::WIDE)
+    {
+        return Some(2);
+    }
+    if ambiguous_wide && in_table(ch, tables::AMBIGUOUS)
+    {
+        return Some(2);
+    }
+    Some(1)
+}
+
+// Grapheme_Cluster_Break, as far as the rules below need it
+#[deriving(PartialEq)]
+enum BreakClass
+{
+    OtherClass,
+    CRClass,
+    LFClass,
+    ControlClass,
+    ExtendClass,
+    ZWJClass,
+    RegionalClass,
+    SpacingMarkClass,
+    HangulL,
+    HangulV,
+    HangulT,
+    HangulLV,
+    HangulLVT,
+}
+
+fn break_class(ch: char) -> BreakClass
+{
+    match ch
+    {
+        '\r' => CRClass,
+        '\n' => LFClass,
+        '\u200c' | '\U0001f3fb'...'\U0001f3ff' | '\U000e0020'...'\U000e007f' => ExtendClass,
+        '\u200d' => ZWJClass,
+        '\u1100'...'\u115f' | '\ua960'...'\ua97c' => HangulL,
+        '\u1160'...'\u11a7' | '\ud7b0'...'\ud7c6' => HangulV,
+        '\u11a8'...'\u11ff' | '\ud7cb'...'\ud7fb' => HangulT,
+        '\uac00'...'\ud7a3' if (ch as u32 - 0xac00) % 28 == 0 => HangulLV,
+        '\uac00'...'\ud7a3' => HangulLVT,
+        _ if is_control(ch) || ch == '\u2028' || ch == '\u2029' => ControlClass,
+        _ if is_regional_indicator(ch) => RegionalClass,
+        _ if in_table(ch, tables::COMBINING) => ExtendClass,
+        _ if in_table(ch, tables::FORMAT) => ControlClass,
+        _ if in_table(ch, tables::SPACING_MARK) => SpacingMarkClass,
+        _ => OtherClass,
+    }
+}
+
+/// The length in bytes of the grapheme cluster `text` starts with.
+pub fn cluster_len(text: &str) -> uint
+{
+    let mut chars = text.char_indices();
+    let first = match chars.next()
+    {
+        Some((_, ch)) => ch,
+        None => return 0,
+    };
+    let mut prev = break_class(first);
+    // whether what came so far is a pictograph and then Extends, which a
+    // ZWJ joins to a following pictograph
+    let mut pictographic = is_pictographic(first);
+    let mut regionals = if prev == RegionalClass { 1u } else { 0 };
+    for (i, ch) in chars
+    {
+        let class = break_class(ch);
+        let join = match (&prev, &class)
+        {
+            (&CRClass, &LFClass) => true,
+            (&ControlClass, _) | (&CRClass, _) | (&LFClass, _) => false,
+            (_, &ControlClass) | (_, &CRClass) | (_, &LFClass) => false,
+            (&HangulL, &HangulL) | (&HangulL, &HangulV) | (&HangulL, &HangulLV) | (&HangulL, &HangulLVT) => true,
+            (&HangulLV, &HangulV) | (&HangulLV, &HangulT) | (&HangulV, &HangulV) | (&HangulV, &HangulT) => true,
+            (&HangulLVT, &HangulT) | (&HangulT, &HangulT) => true,
+            (_, &ExtendClass) | (_, &ZWJClass) | (_, &SpacingMarkClass) => true,
+            (&ZWJClass, _) => pictographic && is_pictographic(ch),
+            (&RegionalClass, &RegionalClass) => regionals % 2 == 1,
+            _ => false,
+        };
+        if !join
+        {
+            return i;
+        }
+        pictographic = match class
+        {
+            ExtendClass => pictographic,
+            // only a ZWJ right after the pictograph and its Extends
+            ZWJClass => pictographic && prev != ZWJClass,
+            _ => is_pictographic(ch),
+        };
+        if class == RegionalClass
+        {
+            regionals += 1;
+        }
+        prev = class;
+    }
+    text.len()
+}
+
+/// Columns taken by a grapheme cluster, or None if it is a control.
+pub fn cluster_width(cluster: &str, ambiguous_wide: bool) -> Option<uint>
+{
+    let mut chars = cluster.chars();
+    let first = match chars.next()
+    {
+        Some(ch) => ch,
+        None => return Some(0),
+    };
+    let width = match char_width(first, ambiguous_wide)
+    {
+        Some(width) => width,
+        None => return None,
+    };
+    if is_pictographic(first) || is_keycap_base(first)
+    {
+        for ch in chars
+        {
+            if ch == EMOJI_PRESENTATION
+            {
+                return Some(2);
+            }
+            if ch == TEXT_PRESENTATION
+            {
+                return Some(1);
+            }
+            if ch == ZWJ
+            {
+                break;
+            }
+        }
+    }
+    else if is_regional_indicator(first)
+    {
+        // a flag, or a lone letter that terminals draw narrow
+        return Some(if cluster.chars().count() > 1 { 2 } else { 1 });
+    }
+    Some(width)
+}
+
+/// The grapheme clusters of a string, in order.
+pub struct Graphemes<'a>
+{
+    rest: &'a str,
+}
+
+pub fn graphemes<'a>(text: &'a str) -> Graphemes<'a>
+{
+    Graphemes{rest: text}
+}
+
+impl<'a> Iterator<&'a str> for Graphemes<'a>
+{
+    fn next(&mut self) -> Option<&'a str>
+    {
+        if self.rest.is_empty()
+        {
+            return None;
+        }
+        let len = cluster_len(self.rest);
+        let cluster = self.rest.slice_to(len);
+        self.rest = self.rest.slice_from(len);
+        Some(cluster)
+    }
+}
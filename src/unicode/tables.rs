@@ -0,0 +1,423 @@
+// Generated by generate_unicode_tables.py from Unicode 14.0.0; don't edit.
+
+// Mn and Me: marks that draw over the character before them
+pub static COMBINING: &'static [(char, char)] = &[
+    ('\u0300', '\u036f'), ('\u0483', '\u0489'), ('\u0591', '\u05bd'), ('\u05bf', '\u05bf'),
+    ('\u05c1', '\u05c2'), ('\u05c4', '\u05c5'), ('\u05c7', '\u05c7'), ('\u0610', '\u061a'),
+    ('\u064b', '\u065f'), ('\u0670', '\u0670'), ('\u06d6', '\u06dc'), ('\u06df', '\u06e4'),
+    ('\u06e7', '\u06e8'), ('\u06ea', '\u06ed'), ('\u0711', '\u0711'), ('\u0730', '\u074a'),
+    ('\u07a6', '\u07b0'), ('\u07eb', '\u07f3'), ('\u07fd', '\u07fd'), ('\u0816', '\u0819'),
+    ('\u081b', '\u0823'), ('\u0825', '\u0827'), ('\u0829', '\u082d'), ('\u0859', '\u085b'),
+    ('\u0898', '\u089f'), ('\u08ca', '\u08e1'), ('\u08e3', '\u0902'), ('\u093a', '\u093a'),
+    ('\u093c', '\u093c'), ('\u0941', '\u0948'), ('\u094d', '\u094d'), ('\u0951', '\u0957'),
+    ('\u0962', '\u0963'), ('\u0981', '\u0981'), ('\u09bc', '\u09bc'), ('\u09c1', '\u09c4'),
+    ('\u09cd', '\u09cd'), ('\u09e2', '\u09e3'), ('\u09fe', '\u09fe'), ('\u0a01', '\u0a02'),
+    ('\u0a3c', '\u0a3c'), ('\u0a41', '\u0a42'), ('\u0a47', '\u0a48'), ('\u0a4b', '\u0a4d'),
+    ('\u0a51', '\u0a51'), ('\u0a70', '\u0a71'), ('\u0a75', '\u0a75'), ('\u0a81', '\u0a82'),
+    ('\u0abc', '\u0abc'), ('\u0ac1', '\u0ac5'), ('\u0ac7', '\u0ac8'), ('\u0acd', '\u0acd'),
+    ('\u0ae2', '\u0ae3'), ('\u0afa', '\u0aff'), ('\u0b01', '\u0b01'), ('\u0b3c', '\u0b3c'),
+    ('\u0b3f', '\u0b3f'), ('\u0b41', '\u0b44'), ('\u0b4d', '\u0b4d'), ('\u0b55', '\u0b56'),
+    ('\u0b62', '\u0b63'), ('\u0b82', '\u0b82'), ('\u0bc0', '\u0bc0'), ('\u0bcd', '\u0bcd'),
+    ('\u0c00', '\u0c00'), ('\u0c04', '\u0c04'), ('\u0c3c', '\u0c3c'), ('\u0c3e', '\u0c40'),
+    ('\u0c46', '\u0c48'), ('\u0c4a', '\u0c4d'), ('\u0c55', '\u0c56'), ('\u0c62', '\u0c63'),
+    ('\u0c81', '\u0c81'), ('\u0cbc', '\u0cbc'), ('\u0cbf', '\u0cbf'), ('\u0cc6', '\u0cc6'),
+    ('\u0ccc', '\u0ccd'), ('\u0ce2', '\u0ce3'), ('\u0d00', '\u0d01'), ('\u0d3b', '\u0d3c'),
+    ('\u0d41', '\u0d44'), ('\u0d4d', '\u0d4d'), ('\u0d62', '\u0d63'), ('\u0d81', '\u0d81'),
+    ('\u0dca', '\u0dca'), ('\u0dd2', '\u0dd4'), ('\u0dd6', '\u0dd6'), ('\u0e31', '\u0e31'),
+    ('\u0e34', '\u0e3a'), ('\u0e47', '\u0e4e'), ('\u0eb1', '\u0eb1'), ('\u0eb4', '\u0ebc'),
+    ('\u0ec8', '\u0ecd'), ('\u0f18', '\u0f19'), ('\u0f35', '\u0f35'), ('\u0f37', '\u0f37'),
+    ('\u0f39', '\u0f39'), ('\u0f71', '\u0f7e'), ('\u0f80', '\u0f84'), ('\u0f86', '\u0f87'),
+    ('\u0f8d', '\u0f97'), ('\u0f99', '\u0fbc'), ('\u0fc6', '\u0fc6'), ('\u102d', '\u1030'),
+    ('\u1032', '\u1037'), ('\u1039', '\u103a'), ('\u103d', '\u103e'), ('\u1058', '\u1059'),
+    ('\u105e', '\u1060'), ('\u1071', '\u1074'), ('\u1082', '\u1082'), ('\u1085', '\u1086'),
+    ('\u108d', '\u108d'), ('\u109d', '\u109d'), ('\u135d', '\u135f'), ('\u1712', '\u1714'),
+    ('\u1732', '\u1733'), ('\u1752', '\u1753'), ('\u1772', '\u1773'), ('\u17b4', '\u17b5'),
+    ('\u17b7', '\u17bd'), ('\u17c6', '\u17c6'), ('\u17c9', '\u17d3'), ('\u17dd', '\u17dd'),
+    ('\u180b', '\u180d'), ('\u180f', '\u180f'), ('\u1885', '\u1886'), ('\u18a9', '\u18a9'),
+    ('\u1920', '\u1922'), ('\u1927', '\u1928'), ('\u1932', '\u1932'), ('\u1939', '\u193b'),
+    ('\u1a17', '\u1a18'), ('\u1a1b', '\u1a1b'), ('\u1a56', '\u1a56'), ('\u1a58', '\u1a5e'),
+    ('\u1a60', '\u1a60'), ('\u1a62', '\u1a62'), ('\u1a65', '\u1a6c'), ('\u1a73', '\u1a7c'),
+    ('\u1a7f', '\u1a7f'), ('\u1ab0', '\u1ace'), ('\u1b00', '\u1b03'), ('\u1b34', '\u1b34'),
+    ('\u1b36', '\u1b3a'), ('\u1b3c', '\u1b3c'), ('\u1b42', '\u1b42'), ('\u1b6b', '\u1b73'),
+    ('\u1b80', '\u1b81'), ('\u1ba2', '\u1ba5'), ('\u1ba8', '\u1ba9'), ('\u1bab', '\u1bad'),
+    ('\u1be6', '\u1be6'), ('\u1be8', '\u1be9'), ('\u1bed', '\u1bed'), ('\u1bef', '\u1bf1'),
+    ('\u1c2c', '\u1c33'), ('\u1c36', '\u1c37'), ('\u1cd0', '\u1cd2'), ('\u1cd4', '\u1ce0'),
+    ('\u1ce2', '\u1ce8'), ('\u1ced', '\u1ced'), ('\u1cf4', '\u1cf4'), ('\u1cf8', '\u1cf9'),
+    ('\u1dc0', '\u1dff'), ('\u20d0', '\u20f0'), ('\u2cef', '\u2cf1'), ('\u2d7f', '\u2d7f'),
+    ('\u2de0', '\u2dff'), ('\u302a', '\u302d'), ('\u3099', '\u309a'), ('\ua66f', '\ua672'),
+    ('\ua674', '\ua67d'), ('\ua69e', '\ua69f'), ('\ua6f0', '\ua6f1'), ('\ua802', '\ua802'),
+    ('\ua806', '\ua806'), ('\ua80b', '\ua80b'), ('\ua825', '\ua826'), ('\ua82c', '\ua82c'),
+    ('\ua8c4', '\ua8c5'), ('\ua8e0', '\ua8f1'), ('\ua8ff', '\ua8ff'), ('\ua926', '\ua92d'),
+    ('\ua947', '\ua951'), ('\ua980', '\ua982'), ('\ua9b3', '\ua9b3'), ('\ua9b6', '\ua9b9'),
+    ('\ua9bc', '\ua9bd'), ('\ua9e5', '\ua9e5'), ('\uaa29', '\uaa2e'), ('\uaa31', '\uaa32'),
+    ('\uaa35', '\uaa36'), ('\uaa43', '\uaa43'), ('\uaa4c', '\uaa4c'), ('\uaa7c', '\uaa7c'),
+    ('\uaab0', '\uaab0'), ('\uaab2', '\uaab4'), ('\uaab7', '\uaab8'), ('\uaabe', '\uaabf'),
+    ('\uaac1', '\uaac1'), ('\uaaec', '\uaaed'), ('\uaaf6', '\uaaf6'), ('\uabe5', '\uabe5'),
+    ('\uabe8', '\uabe8'), ('\uabed', '\uabed'), ('\ufb1e', '\ufb1e'), ('\ufe00', '\ufe0f'),
+    ('\ufe20', '\ufe2f'), ('\U000101fd', '\U000101fd'), ('\U000102e0', '\U000102e0'),
+    ('\U00010376', '\U0001037a'), ('\U00010a01', '\U00010a03'), ('\U00010a05', '\U00010a06'),
+    ('\U00010a0c', '\U00010a0f'), ('\U00010a38', '\U00010a3a'), ('\U00010a3f', '\U00010a3f'),
+    ('\U00010ae5', '\U00010ae6'), ('\U00010d24', '\U00010d27'), ('\U00010eab', '\U00010eac'),
+    ('\U00010f46', '\U00010f50'), ('\U00010f82', '\U00010f85'), ('\U00011001', '\U00011001'),
+    ('\U00011038', '\U00011046'), ('\U00011070', '\U00011070'), ('\U00011073', '\U00011074'),
+    ('\U0001107f', '\U00011081'), ('\U000110b3', '\U000110b6'), ('\U000110b9', '\U000110ba'),
+    ('\U000110c2', '\U000110c2'), ('\U00011100', '\U00011102'), ('\U00011127', '\U0001112b'),
+    ('\U0001112d', '\U00011134'), ('\U00011173', '\U00011173'), ('\U00011180', '\U00011181'),
+    ('\U000111b6', '\U000111be'), ('\U000111c9', '\U000111cc'), ('\U000111cf', '\U000111cf'),
+    ('\U0001122f', '\U00011231'), ('\U00011234', '\U00011234'), ('\U00011236', '\U00011237'),
+    ('\U0001123e', '\U0001123e'), ('\U000112df', '\U000112df'), ('\U000112e3', '\U000112ea'),
+    ('\U00011300', '\U00011301'), ('\U0001133b', '\U0001133c'), ('\U00011340', '\U00011340'),
+    ('\U00011366', '\U0001136c'), ('\U00011370', '\U00011374'), ('\U00011438', '\U0001143f'),
+    ('\U00011442', '\U00011444'), ('\U00011446', '\U00011446'), ('\U0001145e', '\U0001145e'),
+    ('\U000114b3', '\U000114b8'), ('\U000114ba', '\U000114ba'), ('\U000114bf', '\U000114c0'),
+    ('\U000114c2', '\U000114c3'), ('\U000115b2', '\U000115b5'), ('\U000115bc', '\U000115bd'),
+    ('\U000115bf', '\U000115c0'), ('\U000115dc', '\U000115dd'), ('\U00011633', '\U0001163a'),
+    ('\U0001163d', '\U0001163d'), ('\U0001163f', '\U00011640'), ('\U000116ab', '\U000116ab'),
+    ('\U000116ad', '\U000116ad'), ('\U000116b0', '\U000116b5'), ('\U000116b7', '\U000116b7'),
+    ('\U0001171d', '\U0001171f'), ('\U00011722', '\U00011725'), ('\U00011727', '\U0001172b'),
+    ('\U0001182f', '\U00011837'), ('\U00011839', '\U0001183a'), ('\U0001193b', '\U0001193c'),
+    ('\U0001193e', '\U0001193e'), ('\U00011943', '\U00011943'), ('\U000119d4', '\U000119d7'),
+    ('\U000119da', '\U000119db'), ('\U000119e0', '\U000119e0'), ('\U00011a01', '\U00011a0a'),
+    ('\U00011a33', '\U00011a38'), ('\U00011a3b', '\U00011a3e'), ('\U00011a47', '\U00011a47'),
+    ('\U00011a51', '\U00011a56'), ('\U00011a59', '\U00011a5b'), ('\U00011a8a', '\U00011a96'),
+    ('\U00011a98', '\U00011a99'), ('\U00011c30', '\U00011c36'), ('\U00011c38', '\U00011c3d'),
+    ('\U00011c3f', '\U00011c3f'), ('\U00011c92', '\U00011ca7'), ('\U00011caa', '\U00011cb0'),
+    ('\U00011cb2', '\U00011cb3'), ('\U00011cb5', '\U00011cb6'), ('\U00011d31', '\U00011d36'),
+    ('\U00011d3a', '\U00011d3a'), ('\U00011d3c', '\U00011d3d'), ('\U00011d3f', '\U00011d45'),
+    ('\U00011d47', '\U00011d47'), ('\U00011d90', '\U00011d91'), ('\U00011d95', '\U00011d95'),
+    ('\U00011d97', '\U00011d97'), ('\U00011ef3', '\U00011ef4'), ('\U00016af0', '\U00016af4'),
+    ('\U00016b30', '\U00016b36'), ('\U00016f4f', '\U00016f4f'), ('\U00016f8f', '\U00016f92'),
+    ('\U00016fe4', '\U00016fe4'), ('\U0001bc9d', '\U0001bc9e'), ('\U0001cf00', '\U0001cf2d'),
+    ('\U0001cf30', '\U0001cf46'), ('\U0001d167', '\U0001d169'), ('\U0001d17b', '\U0001d182'),
+    ('\U0001d185', '\U0001d18b'), ('\U0001d1aa', '\U0001d1ad'), ('\U0001d242', '\U0001d244'),
+    ('\U0001da00', '\U0001da36'), ('\U0001da3b', '\U0001da6c'), ('\U0001da75', '\U0001da75'),
+    ('\U0001da84', '\U0001da84'), ('\U0001da9b', '\U0001da9f'), ('\U0001daa1', '\U0001daaf'),
+    ('\U0001e000', '\U0001e006'), ('\U0001e008', '\U0001e018'), ('\U0001e01b', '\U0001e021'),
+    ('\U0001e023', '\U0001e024'), ('\U0001e026', '\U0001e02a'), ('\U0001e130', '\U0001e136'),
+    ('\U0001e2ae', '\U0001e2ae'), ('\U0001e2ec', '\U0001e2ef'), ('\U0001e8d0', '\U0001e8d6'),
+    ('\U0001e944', '\U0001e94a'), ('\U000e0100', '\U000e01ef'),
+];
+
+// Cf: invisible format characters
+pub static FORMAT: &'static [(char, char)] = &[
+    ('\u00ad', '\u00ad'), ('\u0600', '\u0605'), ('\u061c', '\u061c'), ('\u06dd', '\u06dd'),
+    ('\u070f', '\u070f'), ('\u0890', '\u0891'), ('\u08e2', '\u08e2'), ('\u180e', '\u180e'),
+    ('\u200b', '\u200f'), ('\u202a', '\u202e'), ('\u2060', '\u2064'), ('\u2066', '\u206f'),
+    ('\ufeff', '\ufeff'), ('\ufff9', '\ufffb'), ('\U000110bd', '\U000110bd'),
+    ('\U000110cd', '\U000110cd'), ('\U00013430', '\U00013438'), ('\U0001bca0', '\U0001bca3'),
+    ('\U0001d173', '\U0001d17a'), ('\U000e0001', '\U000e0001'), ('\U000e0020', '\U000e007f'),
+];
+
+// Mc: marks that take space of their own but stay with the character before them
+pub static SPACING_MARK: &'static [(char, char)] = &[
+    ('\u0903', '\u0903'), ('\u093b', '\u093b'), ('\u093e', '\u0940'), ('\u0949', '\u094c'),
+    ('\u094e', '\u094f'), ('\u0982', '\u0983'), ('\u09be', '\u09c0'), ('\u09c7', '\u09c8'),
+    ('\u09cb', '\u09cc'), ('\u09d7', '\u09d7'), ('\u0a03', '\u0a03'), ('\u0a3e', '\u0a40'),
+    ('\u0a83', '\u0a83'), ('\u0abe', '\u0ac0'), ('\u0ac9', '\u0ac9'), ('\u0acb', '\u0acc'),
+    ('\u0b02', '\u0b03'), ('\u0b3e', '\u0b3e'), ('\u0b40', '\u0b40'), ('\u0b47', '\u0b48'),
+    ('\u0b4b', '\u0b4c'), ('\u0b57', '\u0b57'), ('\u0bbe', '\u0bbf'), ('\u0bc1', '\u0bc2'),
+    ('\u0bc6', '\u0bc8'), ('\u0bca', '\u0bcc'), ('\u0bd7', '\u0bd7'), ('\u0c01', '\u0c03'),
+    ('\u0c41', '\u0c44'), ('\u0c82', '\u0c83'), ('\u0cbe', '\u0cbe'), ('\u0cc0', '\u0cc4'),
+    ('\u0cc7', '\u0cc8'), ('\u0cca', '\u0ccb'), ('\u0cd5', '\u0cd6'), ('\u0d02', '\u0d03'),
+    ('\u0d3e', '\u0d40'), ('\u0d46', '\u0d48'), ('\u0d4a', '\u0d4c'), ('\u0d57', '\u0d57'),
+    ('\u0d82', '\u0d83'), ('\u0dcf', '\u0dd1'), ('\u0dd8', '\u0ddf'), ('\u0df2', '\u0df3'),
+    ('\u0f3e', '\u0f3f'), ('\u0f7f', '\u0f7f'), ('\u102b', '\u102c'), ('\u1031', '\u1031'),
+    ('\u1038', '\u1038'), ('\u103b', '\u103c'), ('\u1056', '\u1057'), ('\u1062', '\u1064'),
+    ('\u1067', '\u106d'), ('\u1083', '\u1084'), ('\u1087', '\u108c'), ('\u108f', '\u108f'),
+    ('\u109a', '\u109c'), ('\u1715', '\u1715'), ('\u1734', '\u1734'), ('\u17b6', '\u17b6'),
+    ('\u17be', '\u17c5'), ('\u17c7', '\u17c8'), ('\u1923', '\u1926'), ('\u1929', '\u192b'),
+    ('\u1930', '\u1931'), ('\u1933', '\u1938'), ('\u1a19', '\u1a1a'), ('\u1a55', '\u1a55'),
+    ('\u1a57', '\u1a57'), ('\u1a61', '\u1a61'), ('\u1a63', '\u1a64'), ('\u1a6d', '\u1a72'),
+    ('\u1b04', '\u1b04'), ('\u1b35', '\u1b35'), ('\u1b3b', '\u1b3b'), ('\u1b3d', '\u1b41'),
+    ('\u1b43', '\u1b44'), ('\u1b82', '\u1b82'), ('\u1ba1', '\u1ba1'), ('\u1ba6', '\u1ba7'),
+    ('\u1baa', '\u1baa'), ('\u1be7', '\u1be7'), ('\u1bea', '\u1bec'), ('\u1bee', '\u1bee'),
+    ('\u1bf2', '\u1bf3'), ('\u1c24', '\u1c2b'), ('\u1c34', '\u1c35'), ('\u1ce1', '\u1ce1'),
+    ('\u1cf7', '\u1cf7'), ('\u302e', '\u302f'), ('\ua823', '\ua824'), ('\ua827', '\ua827'),
+    ('\ua880', '\ua881'), ('\ua8b4', '\ua8c3'), ('\ua952', '\ua953'), ('\ua983', '\ua983'),
+    ('\ua9b4', '\ua9b5'), ('\ua9ba', '\ua9bb'), ('\ua9be', '\ua9c0'), ('\uaa2f', '\uaa30'),
+    ('\uaa33', '\uaa34'), ('\uaa4d', '\uaa4d'), ('\uaa7b', '\uaa7b'), ('\uaa7d', '\uaa7d'),
+    ('\uaaeb', '\uaaeb'), ('\uaaee', '\uaaef'), ('\uaaf5', '\uaaf5'), ('\uabe3', '\uabe4'),
+    ('\uabe6', '\uabe7'), ('\uabe9', '\uabea'), ('\uabec', '\uabec'), ('\U00011000', '\U00011000'),
+    ('\U00011002', '\U00011002'), ('\U00011082', '\U00011082'), ('\U000110b0', '\U000110b2'),
+    ('\U000110b7', '\U000110b8'), ('\U0001112c', '\U0001112c'), ('\U00011145', '\U00011146'),
+    ('\U00011182', '\U00011182'), ('\U000111b3', '\U000111b5'), ('\U000111bf', '\U000111c0'),
+    ('\U000111ce', '\U000111ce'), ('\U0001122c', '\U0001122e'), ('\U00011232', '\U00011233'),
+    ('\U00011235', '\U00011235'), ('\U000112e0', '\U000112e2'), ('\U00011302', '\U00011303'),
+    ('\U0001133e', '\U0001133f'), ('\U00011341', '\U00011344'), ('\U00011347', '\U00011348'),
+    ('\U0001134b', '\U0001134d'), ('\U00011357', '\U00011357'), ('\U00011362', '\U00011363'),
+    ('\U00011435', '\U00011437'), ('\U00011440', '\U00011441'), ('\U00011445', '\U00011445'),
+    ('\U000114b0', '\U000114b2'), ('\U000114b9', '\U000114b9'), ('\U000114bb', '\U000114be'),
+    ('\U000114c1', '\U000114c1'), ('\U000115af', '\U000115b1'), ('\U000115b8', '\U000115bb'),
+    ('\U000115be', '\U000115be'), ('\U00011630', '\U00011632'), ('\U0001163b', '\U0001163c'),
+    ('\U0001163e', '\U0001163e'), ('\U000116ac', '\U000116ac'), ('\U000116ae', '\U000116af'),
+    ('\U000116b6', '\U000116b6'), ('\U00011720', '\U00011721'), ('\U00011726', '\U00011726'),
+    ('\U0001182c', '\U0001182e'), ('\U00011838', '\U00011838'), ('\U00011930', '\U00011935'),
+    ('\U00011937', '\U00011938'), ('\U0001193d', '\U0001193d'), ('\U00011940', '\U00011940'),
+    ('\U00011942', '\U00011942'), ('\U000119d1', '\U000119d3'), ('\U000119dc', '\U000119df'),
+    ('\U000119e4', '\U000119e4'), ('\U00011a39', '\U00011a39'), ('\U00011a57', '\U00011a58'),
+    ('\U00011a97', '\U00011a97'), ('\U00011c2f', '\U00011c2f'), ('\U00011c3e', '\U00011c3e'),
+    ('\U00011ca9', '\U00011ca9'), ('\U00011cb1', '\U00011cb1'), ('\U00011cb4', '\U00011cb4'),
+    ('\U00011d8a', '\U00011d8e'), ('\U00011d93', '\U00011d94'), ('\U00011d96', '\U00011d96'),
+    ('\U00011ef5', '\U00011ef6'), ('\U00016f51', '\U00016f87'), ('\U00016ff0', '\U00016ff1'),
+    ('\U0001d165', '\U0001d166'), ('\U0001d16d', '\U0001d172'),
+];
+
+// East Asian Wide and Fullwidth, including unassigned CJK ideographs
+pub static WIDE: &'static [(char, char)] = &[
+    ('\u0378', '\u0379'), ('\u0380', '\u0383'), ('\u038b', '\u038b'), ('\u038d', '\u038d'),
+    ('\u03a2', '\u03a2'), ('\u0530', '\u0530'), ('\u0557', '\u0558'), ('\u058b', '\u058c'),
+    ('\u0590', '\u0590'), ('\u05c8', '\u05cf'), ('\u05eb', '\u05ee'), ('\u05f5', '\u05ff'),
+    ('\u070e', '\u070e'), ('\u074b', '\u074c'), ('\u07b2', '\u07bf'), ('\u07fb', '\u07fc'),
+    ('\u082e', '\u082f'), ('\u083f', '\u083f'), ('\u085c', '\u085d'), ('\u085f', '\u085f'),
+    ('\u086b', '\u086f'), ('\u088f', '\u088f'), ('\u0892', '\u0897'), ('\u0984', '\u0984'),
+    ('\u098d', '\u098e'), ('\u0991', '\u0992'), ('\u09a9', '\u09a9'), ('\u09b1', '\u09b1'),
+    ('\u09b3', '\u09b5'), ('\u09ba', '\u09bb'), ('\u09c5', '\u09c6'), ('\u09c9', '\u09ca'),
+    ('\u09cf', '\u09d6'), ('\u09d8', '\u09db'), ('\u09de', '\u09de'), ('\u09e4', '\u09e5'),
+    ('\u09ff', '\u0a00'), ('\u0a04', '\u0a04'), ('\u0a0b', '\u0a0e'), ('\u0a11', '\u0a12'),
+    ('\u0a29', '\u0a29'), ('\u0a31', '\u0a31'), ('\u0a34', '\u0a34'), ('\u0a37', '\u0a37'),
+    ('\u0a3a', '\u0a3b'), ('\u0a3d', '\u0a3d'), ('\u0a43', '\u0a46'), ('\u0a49', '\u0a4a'),
+    ('\u0a4e', '\u0a50'), ('\u0a52', '\u0a58'), ('\u0a5d', '\u0a5d'), ('\u0a5f', '\u0a65'),
+    ('\u0a77', '\u0a80'), ('\u0a84', '\u0a84'), ('\u0a8e', '\u0a8e'), ('\u0a92', '\u0a92'),
+    ('\u0aa9', '\u0aa9'), ('\u0ab1', '\u0ab1'), ('\u0ab4', '\u0ab4'), ('\u0aba', '\u0abb'),
+    ('\u0ac6', '\u0ac6'), ('\u0aca', '\u0aca'), ('\u0ace', '\u0acf'), ('\u0ad1', '\u0adf'),
+    ('\u0ae4', '\u0ae5'), ('\u0af2', '\u0af8'), ('\u0b00', '\u0b00'), ('\u0b04', '\u0b04'),
+    ('\u0b0d', '\u0b0e'), ('\u0b11', '\u0b12'), ('\u0b29', '\u0b29'), ('\u0b31', '\u0b31'),
+    ('\u0b34', '\u0b34'), ('\u0b3a', '\u0b3b'), ('\u0b45', '\u0b46'), ('\u0b49', '\u0b4a'),
+    ('\u0b4e', '\u0b54'), ('\u0b58', '\u0b5b'), ('\u0b5e', '\u0b5e'), ('\u0b64', '\u0b65'),
+    ('\u0b78', '\u0b81'), ('\u0b84', '\u0b84'), ('\u0b8b', '\u0b8d'), ('\u0b91', '\u0b91'),
+    ('\u0b96', '\u0b98'), ('\u0b9b', '\u0b9b'), ('\u0b9d', '\u0b9d'), ('\u0ba0', '\u0ba2'),
+    ('\u0ba5', '\u0ba7'), ('\u0bab', '\u0bad'), ('\u0bba', '\u0bbd'), ('\u0bc3', '\u0bc5'),
+    ('\u0bc9', '\u0bc9'), ('\u0bce', '\u0bcf'), ('\u0bd1', '\u0bd6'), ('\u0bd8', '\u0be5'),
+    ('\u0bfb', '\u0bff'), ('\u0c0d', '\u0c0d'), ('\u0c11', '\u0c11'), ('\u0c29', '\u0c29'),
+    ('\u0c3a', '\u0c3b'), ('\u0c45', '\u0c45'), ('\u0c49', '\u0c49'), ('\u0c4e', '\u0c54'),
+    ('\u0c57', '\u0c57'), ('\u0c5b', '\u0c5c'), ('\u0c5e', '\u0c5f'), ('\u0c64', '\u0c65'),
+    ('\u0c70', '\u0c76'), ('\u0c8d', '\u0c8d'), ('\u0c91', '\u0c91'), ('\u0ca9', '\u0ca9'),
+    ('\u0cb4', '\u0cb4'), ('\u0cba', '\u0cbb'), ('\u0cc5', '\u0cc5'), ('\u0cc9', '\u0cc9'),
+    ('\u0cce', '\u0cd4'), ('\u0cd7', '\u0cdc'), ('\u0cdf', '\u0cdf'), ('\u0ce4', '\u0ce5'),
+    ('\u0cf0', '\u0cf0'), ('\u0cf3', '\u0cff'), ('\u0d0d', '\u0d0d'), ('\u0d11', '\u0d11'),
+    ('\u0d45', '\u0d45'), ('\u0d49', '\u0d49'), ('\u0d50', '\u0d53'), ('\u0d64', '\u0d65'),
+    ('\u0d80', '\u0d80'), ('\u0d84', '\u0d84'), ('\u0d97', '\u0d99'), ('\u0db2', '\u0db2'),
+    ('\u0dbc', '\u0dbc'), ('\u0dbe', '\u0dbf'), ('\u0dc7', '\u0dc9'), ('\u0dcb', '\u0dce'),
+    ('\u0dd5', '\u0dd5'), ('\u0dd7', '\u0dd7'), ('\u0de0', '\u0de5'), ('\u0df0', '\u0df1'),
+    ('\u0df5', '\u0e00'), ('\u0e3b', '\u0e3e'), ('\u0e5c', '\u0e80'), ('\u0e83', '\u0e83'),
+    ('\u0e85', '\u0e85'), ('\u0e8b', '\u0e8b'), ('\u0ea4', '\u0ea4'), ('\u0ea6', '\u0ea6'),
+    ('\u0ebe', '\u0ebf'), ('\u0ec5', '\u0ec5'), ('\u0ec7', '\u0ec7'), ('\u0ece', '\u0ecf'),
+    ('\u0eda', '\u0edb'), ('\u0ee0', '\u0eff'), ('\u0f48', '\u0f48'), ('\u0f6d', '\u0f70'),
+    ('\u0f98', '\u0f98'), ('\u0fbd', '\u0fbd'), ('\u0fcd', '\u0fcd'), ('\u0fdb', '\u0fff'),
+    ('\u10c6', '\u10c6'), ('\u10c8', '\u10cc'), ('\u10ce', '\u10cf'), ('\u1100', '\u115f'),
+    ('\u1249', '\u1249'), ('\u124e', '\u124f'), ('\u1257', '\u1257'), ('\u1259', '\u1259'),
+    ('\u125e', '\u125f'), ('\u1289', '\u1289'), ('\u128e', '\u128f'), ('\u12b1', '\u12b1'),
+    ('\u12b6', '\u12b7'), ('\u12bf', '\u12bf'), ('\u12c1', '\u12c1'), ('\u12c6', '\u12c7'),
+    ('\u12d7', '\u12d7'), ('\u1311', '\u1311'), ('\u1316', '\u1317'), ('\u135b', '\u135c'),
+    ('\u137d', '\u137f'), ('\u139a', '\u139f'), ('\u13f6', '\u13f7'), ('\u13fe', '\u13ff'),
+    ('\u169d', '\u169f'), ('\u16f9', '\u16ff'), ('\u1716', '\u171e'), ('\u1737', '\u173f'),
+    ('\u1754', '\u175f'), ('\u176d', '\u176d'), ('\u1771', '\u1771'), ('\u1774', '\u177f'),
+    ('\u17de', '\u17df'), ('\u17ea', '\u17ef'), ('\u17fa', '\u17ff'), ('\u181a', '\u181f'),
+    ('\u1879', '\u187f'), ('\u18ab', '\u18af'), ('\u18f6', '\u18ff'), ('\u191f', '\u191f'),
+    ('\u192c', '\u192f'), ('\u193c', '\u193f'), ('\u1941', '\u1943'), ('\u196e', '\u196f'),
+    ('\u1975', '\u197f'), ('\u19ac', '\u19af'), ('\u19ca', '\u19cf'), ('\u19db', '\u19dd'),
+    ('\u1a1c', '\u1a1d'), ('\u1a5f', '\u1a5f'), ('\u1a7d', '\u1a7e'), ('\u1a8a', '\u1a8f'),
+    ('\u1a9a', '\u1a9f'), ('\u1aae', '\u1aaf'), ('\u1acf', '\u1aff'), ('\u1b4d', '\u1b4f'),
+    ('\u1b7f', '\u1b7f'), ('\u1bf4', '\u1bfb'), ('\u1c38', '\u1c3a'), ('\u1c4a', '\u1c4c'),
+    ('\u1c89', '\u1c8f'), ('\u1cbb', '\u1cbc'), ('\u1cc8', '\u1ccf'), ('\u1cfb', '\u1cff'),
+    ('\u1f16', '\u1f17'), ('\u1f1e', '\u1f1f'), ('\u1f46', '\u1f47'), ('\u1f4e', '\u1f4f'),
+    ('\u1f58', '\u1f58'), ('\u1f5a', '\u1f5a'), ('\u1f5c', '\u1f5c'), ('\u1f5e', '\u1f5e'),
+    ('\u1f7e', '\u1f7f'), ('\u1fb5', '\u1fb5'), ('\u1fc5', '\u1fc5'), ('\u1fd4', '\u1fd5'),
+    ('\u1fdc', '\u1fdc'), ('\u1ff0', '\u1ff1'), ('\u1ff5', '\u1ff5'), ('\u1fff', '\u1fff'),
+    ('\u2065', '\u2065'), ('\u2072', '\u2073'), ('\u208f', '\u208f'), ('\u209d', '\u209f'),
+    ('\u20c1', '\u20cf'), ('\u20f1', '\u20ff'), ('\u218c', '\u218f'), ('\u231a', '\u231b'),
+    ('\u2329', '\u232a'), ('\u23e9', '\u23ec'), ('\u23f0', '\u23f0'), ('\u23f3', '\u23f3'),
+    ('\u2427', '\u243f'), ('\u244b', '\u245f'), ('\u25fd', '\u25fe'), ('\u2614', '\u2615'),
+    ('\u2648', '\u2653'), ('\u267f', '\u267f'), ('\u2693', '\u2693'), ('\u26a1', '\u26a1'),
+    ('\u26aa', '\u26ab'), ('\u26bd', '\u26be'), ('\u26c4', '\u26c5'), ('\u26ce', '\u26ce'),
+    ('\u26d4', '\u26d4'), ('\u26ea', '\u26ea'), ('\u26f2', '\u26f3'), ('\u26f5', '\u26f5'),
+    ('\u26fa', '\u26fa'), ('\u26fd', '\u26fd'), ('\u2705', '\u2705'), ('\u270a', '\u270b'),
+    ('\u2728', '\u2728'), ('\u274c', '\u274c'), ('\u274e', '\u274e'), ('\u2753', '\u2755'),
+    ('\u2757', '\u2757'), ('\u2795', '\u2797'), ('\u27b0', '\u27b0'), ('\u27bf', '\u27bf'),
+    ('\u2b1b', '\u2b1c'), ('\u2b50', '\u2b50'), ('\u2b55', '\u2b55'), ('\u2b74', '\u2b75'),
+    ('\u2b96', '\u2b96'), ('\u2cf4', '\u2cf8'), ('\u2d26', '\u2d26'), ('\u2d28', '\u2d2c'),
+    ('\u2d2e', '\u2d2f'), ('\u2d68', '\u2d6e'), ('\u2d71', '\u2d7e'), ('\u2d97', '\u2d9f'),
+    ('\u2da7', '\u2da7'), ('\u2daf', '\u2daf'), ('\u2db7', '\u2db7'), ('\u2dbf', '\u2dbf'),
+    ('\u2dc7', '\u2dc7'), ('\u2dcf', '\u2dcf'), ('\u2dd7', '\u2dd7'), ('\u2ddf', '\u2ddf'),
+    ('\u2e5e', '\u303e'), ('\u3040', '\u3247'), ('\u3250', '\u4dbf'), ('\u4e00', '\ua4cf'),
+    ('\ua62c', '\ua63f'), ('\ua6f8', '\ua6ff'), ('\ua7cb', '\ua7cf'), ('\ua7d2', '\ua7d2'),
+    ('\ua7d4', '\ua7d4'), ('\ua7da', '\ua7f1'), ('\ua82d', '\ua82f'), ('\ua83a', '\ua83f'),
+    ('\ua878', '\ua87f'), ('\ua8c6', '\ua8cd'), ('\ua8da', '\ua8df'), ('\ua954', '\ua95e'),
+    ('\ua960', '\ua97f'), ('\ua9ce', '\ua9ce'), ('\ua9da', '\ua9dd'), ('\ua9ff', '\ua9ff'),
+    ('\uaa37', '\uaa3f'), ('\uaa4e', '\uaa4f'), ('\uaa5a', '\uaa5b'), ('\uaac3', '\uaada'),
+    ('\uaaf7', '\uab00'), ('\uab07', '\uab08'), ('\uab0f', '\uab10'), ('\uab17', '\uab1f'),
+    ('\uab27', '\uab27'), ('\uab2f', '\uab2f'), ('\uab6c', '\uab6f'), ('\uabee', '\uabef'),
+    ('\uabfa', '\ud7af'), ('\ud7c7', '\ud7ca'), ('\ud7fc', '\ud7ff'), ('\uf900', '\ufaff'),
+    ('\ufb07', '\ufb12'), ('\ufb18', '\ufb1c'), ('\ufb37', '\ufb37'), ('\ufb3d', '\ufb3d'),
+    ('\ufb3f', '\ufb3f'), ('\ufb42', '\ufb42'), ('\ufb45', '\ufb45'), ('\ufbc3', '\ufbd2'),
+    ('\ufd90', '\ufd91'), ('\ufdc8', '\ufdce'), ('\ufdd0', '\ufdef'), ('\ufe10', '\ufe1f'),
+    ('\ufe30', '\ufe6f'), ('\ufe75', '\ufe75'), ('\ufefd', '\ufefe'), ('\uff00', '\uff60'),
+    ('\uffbf', '\uffc1'), ('\uffc8', '\uffc9'), ('\uffd0', '\uffd1'), ('\uffd8', '\uffd9'),
+    ('\uffdd', '\uffe7'), ('\uffef', '\ufff8'), ('\ufffe', '\uffff'), ('\U0001000c', '\U0001000c'),
+    ('\U00010027', '\U00010027'), ('\U0001003b', '\U0001003b'), ('\U0001003e', '\U0001003e'),
+    ('\U0001004e', '\U0001004f'), ('\U0001005e', '\U0001007f'), ('\U000100fb', '\U000100ff'),
+    ('\U00010103', '\U00010106'), ('\U00010134', '\U00010136'), ('\U0001018f', '\U0001018f'),
+    ('\U0001019d', '\U0001019f'), ('\U000101a1', '\U000101cf'), ('\U000101fe', '\U0001027f'),
+    ('\U0001029d', '\U0001029f'), ('\U000102d1', '\U000102df'), ('\U000102fc', '\U000102ff'),
+    ('\U00010324', '\U0001032c'), ('\U0001034b', '\U0001034f'), ('\U0001037b', '\U0001037f'),
+    ('\U0001039e', '\U0001039e'), ('\U000103c4', '\U000103c7'), ('\U000103d6', '\U000103ff'),
+    ('\U0001049e', '\U0001049f'), ('\U000104aa', '\U000104af'), ('\U000104d4', '\U000104d7'),
+    ('\U000104fc', '\U000104ff'), ('\U00010528', '\U0001052f'), ('\U00010564', '\U0001056e'),
+    ('\U0001057b', '\U0001057b'), ('\U0001058b', '\U0001058b'), ('\U00010593', '\U00010593'),
+    ('\U00010596', '\U00010596'), ('\U000105a2', '\U000105a2'), ('\U000105b2', '\U000105b2'),
+    ('\U000105ba', '\U000105ba'), ('\U000105bd', '\U000105ff'), ('\U00010737', '\U0001073f'),
+    ('\U00010756', '\U0001075f'), ('\U00010768', '\U0001077f'), ('\U00010786', '\U00010786'),
+    ('\U000107b1', '\U000107b1'), ('\U000107bb', '\U000107ff'), ('\U00010806', '\U00010807'),
+    ('\U00010809', '\U00010809'), ('\U00010836', '\U00010836'), ('\U00010839', '\U0001083b'),
+    ('\U0001083d', '\U0001083e'), ('\U00010856', '\U00010856'), ('\U0001089f', '\U000108a6'),
+    ('\U000108b0', '\U000108df'), ('\U000108f3', '\U000108f3'), ('\U000108f6', '\U000108fa'),
+    ('\U0001091c', '\U0001091e'), ('\U0001093a', '\U0001093e'), ('\U00010940', '\U0001097f'),
+    ('\U000109b8', '\U000109bb'), ('\U000109d0', '\U000109d1'), ('\U00010a04', '\U00010a04'),
+    ('\U00010a07', '\U00010a0b'), ('\U00010a14', '\U00010a14'), ('\U00010a18', '\U00010a18'),
+    ('\U00010a36', '\U00010a37'), ('\U00010a3b', '\U00010a3e'), ('\U00010a49', '\U00010a4f'),
+    ('\U00010a59', '\U00010a5f'), ('\U00010aa0', '\U00010abf'), ('\U00010ae7', '\U00010aea'),
+    ('\U00010af7', '\U00010aff'), ('\U00010b36', '\U00010b38'), ('\U00010b56', '\U00010b57'),
+    ('\U00010b73', '\U00010b77'), ('\U00010b92', '\U00010b98'), ('\U00010b9d', '\U00010ba8'),
+    ('\U00010bb0', '\U00010bff'), ('\U00010c49', '\U00010c7f'), ('\U00010cb3', '\U00010cbf'),
+    ('\U00010cf3', '\U00010cf9'), ('\U00010d28', '\U00010d2f'), ('\U00010d3a', '\U00010e5f'),
+    ('\U00010e7f', '\U00010e7f'), ('\U00010eaa', '\U00010eaa'), ('\U00010eae', '\U00010eaf'),
+    ('\U00010eb2', '\U00010eff'), ('\U00010f28', '\U00010f2f'), ('\U00010f5a', '\U00010f6f'),
+    ('\U00010f8a', '\U00010faf'), ('\U00010fcc', '\U00010fdf'), ('\U00010ff7', '\U00010fff'),
+    ('\U0001104e', '\U00011051'), ('\U00011076', '\U0001107e'), ('\U000110c3', '\U000110cc'),
+    ('\U000110ce', '\U000110cf'), ('\U000110e9', '\U000110ef'), ('\U000110fa', '\U000110ff'),
+    ('\U00011135', '\U00011135'), ('\U00011148', '\U0001114f'), ('\U00011177', '\U0001117f'),
+    ('\U000111e0', '\U000111e0'), ('\U000111f5', '\U000111ff'), ('\U00011212', '\U00011212'),
+    ('\U0001123f', '\U0001127f'), ('\U00011287', '\U00011287'), ('\U00011289', '\U00011289'),
+    ('\U0001128e', '\U0001128e'), ('\U0001129e', '\U0001129e'), ('\U000112aa', '\U000112af'),
+    ('\U000112eb', '\U000112ef'), ('\U000112fa', '\U000112ff'), ('\U00011304', '\U00011304'),
+    ('\U0001130d', '\U0001130e'), ('\U00011311', '\U00011312'), ('\U00011329', '\U00011329'),
+    ('\U00011331', '\U00011331'), ('\U00011334', '\U00011334'), ('\U0001133a', '\U0001133a'),
+    ('\U00011345', '\U00011346'), ('\U00011349', '\U0001134a'), ('\U0001134e', '\U0001134f'),
+    ('\U00011351', '\U00011356'), ('\U00011358', '\U0001135c'), ('\U00011364', '\U00011365'),
+    ('\U0001136d', '\U0001136f'), ('\U00011375', '\U000113ff'), ('\U0001145c', '\U0001145c'),
+    ('\U00011462', '\U0001147f'), ('\U000114c8', '\U000114cf'), ('\U000114da', '\U0001157f'),
+    ('\U000115b6', '\U000115b7'), ('\U000115de', '\U000115ff'), ('\U00011645', '\U0001164f'),
+    ('\U0001165a', '\U0001165f'), ('\U0001166d', '\U0001167f'), ('\U000116ba', '\U000116bf'),
+    ('\U000116ca', '\U000116ff'), ('\U0001171b', '\U0001171c'), ('\U0001172c', '\U0001172f'),
+    ('\U00011747', '\U000117ff'), ('\U0001183c', '\U0001189f'), ('\U000118f3', '\U000118fe'),
+    ('\U00011907', '\U00011908'), ('\U0001190a', '\U0001190b'), ('\U00011914', '\U00011914'),
+    ('\U00011917', '\U00011917'), ('\U00011936', '\U00011936'), ('\U00011939', '\U0001193a'),
+    ('\U00011947', '\U0001194f'), ('\U0001195a', '\U0001199f'), ('\U000119a8', '\U000119a9'),
+    ('\U000119d8', '\U000119d9'), ('\U000119e5', '\U000119ff'), ('\U00011a48', '\U00011a4f'),
+    ('\U00011aa3', '\U00011aaf'), ('\U00011af9', '\U00011bff'), ('\U00011c09', '\U00011c09'),
+    ('\U00011c37', '\U00011c37'), ('\U00011c46', '\U00011c4f'), ('\U00011c6d', '\U00011c6f'),
+    ('\U00011c90', '\U00011c91'), ('\U00011ca8', '\U00011ca8'), ('\U00011cb7', '\U00011cff'),
+    ('\U00011d07', '\U00011d07'), ('\U00011d0a', '\U00011d0a'), ('\U00011d37', '\U00011d39'),
+    ('\U00011d3b', '\U00011d3b'), ('\U00011d3e', '\U00011d3e'), ('\U00011d48', '\U00011d4f'),
+    ('\U00011d5a', '\U00011d5f'), ('\U00011d66', '\U00011d66'), ('\U00011d69', '\U00011d69'),
+    ('\U00011d8f', '\U00011d8f'), ('\U00011d92', '\U00011d92'), ('\U00011d99', '\U00011d9f'),
+    ('\U00011daa', '\U00011edf'), ('\U00011ef9', '\U00011faf'), ('\U00011fb1', '\U00011fbf'),
+    ('\U00011ff2', '\U00011ffe'), ('\U0001239a', '\U000123ff'), ('\U0001246f', '\U0001246f'),
+    ('\U00012475', '\U0001247f'), ('\U00012544', '\U00012f8f'), ('\U00012ff3', '\U00012fff'),
+    ('\U0001342f', '\U0001342f'), ('\U00013439', '\U000143ff'), ('\U00014647', '\U000167ff'),
+    ('\U00016a39', '\U00016a3f'), ('\U00016a5f', '\U00016a5f'), ('\U00016a6a', '\U00016a6d'),
+    ('\U00016abf', '\U00016abf'), ('\U00016aca', '\U00016acf'), ('\U00016aee', '\U00016aef'),
+    ('\U00016af6', '\U00016aff'), ('\U00016b46', '\U00016b4f'), ('\U00016b5a', '\U00016b5a'),
+    ('\U00016b62', '\U00016b62'), ('\U00016b78', '\U00016b7c'), ('\U00016b90', '\U00016e3f'),
+    ('\U00016e9b', '\U00016eff'), ('\U00016f4b', '\U00016f4e'), ('\U00016f88', '\U00016f8e'),
+    ('\U00016fa0', '\U0001bbff'), ('\U0001bc6b', '\U0001bc6f'), ('\U0001bc7d', '\U0001bc7f'),
+    ('\U0001bc89', '\U0001bc8f'), ('\U0001bc9a', '\U0001bc9b'), ('\U0001bca4', '\U0001ceff'),
+    ('\U0001cf2e', '\U0001cf2f'), ('\U0001cf47', '\U0001cf4f'), ('\U0001cfc4', '\U0001cfff'),
+    ('\U0001d0f6', '\U0001d0ff'), ('\U0001d127', '\U0001d128'), ('\U0001d1eb', '\U0001d1ff'),
+    ('\U0001d246', '\U0001d2df'), ('\U0001d2f4', '\U0001d2ff'), ('\U0001d357', '\U0001d35f'),
+    ('\U0001d379', '\U0001d3ff'), ('\U0001d455', '\U0001d455'), ('\U0001d49d', '\U0001d49d'),
+    ('\U0001d4a0', '\U0001d4a1'), ('\U0001d4a3', '\U0001d4a4'), ('\U0001d4a7', '\U0001d4a8'),
+    ('\U0001d4ad', '\U0001d4ad'), ('\U0001d4ba', '\U0001d4ba'), ('\U0001d4bc', '\U0001d4bc'),
+    ('\U0001d4c4', '\U0001d4c4'), ('\U0001d506', '\U0001d506'), ('\U0001d50b', '\U0001d50c'),
+    ('\U0001d515', '\U0001d515'), ('\U0001d51d', '\U0001d51d'), ('\U0001d53a', '\U0001d53a'),
+    ('\U0001d53f', '\U0001d53f'), ('\U0001d545', '\U0001d545'), ('\U0001d547', '\U0001d549'),
+    ('\U0001d551', '\U0001d551'), ('\U0001d6a6', '\U0001d6a7'), ('\U0001d7cc', '\U0001d7cd'),
+    ('\U0001da8c', '\U0001da9a'), ('\U0001daa0', '\U0001daa0'), ('\U0001dab0', '\U0001deff'),
+    ('\U0001df1f', '\U0001dfff'), ('\U0001e007', '\U0001e007'), ('\U0001e019', '\U0001e01a'),
+    ('\U0001e022', '\U0001e022'), ('\U0001e025', '\U0001e025'), ('\U0001e02b', '\U0001e0ff'),
+    ('\U0001e12d', '\U0001e12f'), ('\U0001e13e', '\U0001e13f'), ('\U0001e14a', '\U0001e14d'),
+    ('\U0001e150', '\U0001e28f'), ('\U0001e2af', '\U0001e2bf'), ('\U0001e2fa', '\U0001e2fe'),
+    ('\U0001e300', '\U0001e7df'), ('\U0001e7e7', '\U0001e7e7'), ('\U0001e7ec', '\U0001e7ec'),
+    ('\U0001e7ef', '\U0001e7ef'), ('\U0001e7ff', '\U0001e7ff'), ('\U0001e8c5', '\U0001e8c6'),
+    ('\U0001e8d7', '\U0001e8ff'), ('\U0001e94c', '\U0001e94f'), ('\U0001e95a', '\U0001e95d'),
+    ('\U0001e960', '\U0001ec70'), ('\U0001ecb5', '\U0001ed00'), ('\U0001ed3e', '\U0001edff'),
+    ('\U0001ee04', '\U0001ee04'), ('\U0001ee20', '\U0001ee20'), ('\U0001ee23', '\U0001ee23'),
+    ('\U0001ee25', '\U0001ee26'), ('\U0001ee28', '\U0001ee28'), ('\U0001ee33', '\U0001ee33'),
+    ('\U0001ee38', '\U0001ee38'), ('\U0001ee3a', '\U0001ee3a'), ('\U0001ee3c', '\U0001ee41'),
+    ('\U0001ee43', '\U0001ee46'), ('\U0001ee48', '\U0001ee48'), ('\U0001ee4a', '\U0001ee4a'),
+    ('\U0001ee4c', '\U0001ee4c'), ('\U0001ee50', '\U0001ee50'), ('\U0001ee53', '\U0001ee53'),
+    ('\U0001ee55', '\U0001ee56'), ('\U0001ee58', '\U0001ee58'), ('\U0001ee5a', '\U0001ee5a'),
+    ('\U0001ee5c', '\U0001ee5c'), ('\U0001ee5e', '\U0001ee5e'), ('\U0001ee60', '\U0001ee60'),
+    ('\U0001ee63', '\U0001ee63'), ('\U0001ee65', '\U0001ee66'), ('\U0001ee6b', '\U0001ee6b'),
+    ('\U0001ee73', '\U0001ee73'), ('\U0001ee78', '\U0001ee78'), ('\U0001ee7d', '\U0001ee7d'),
+    ('\U0001ee7f', '\U0001ee7f'), ('\U0001ee8a', '\U0001ee8a'), ('\U0001ee9c', '\U0001eea0'),
+    ('\U0001eea4', '\U0001eea4'), ('\U0001eeaa', '\U0001eeaa'), ('\U0001eebc', '\U0001eeef'),
+    ('\U0001eef2', '\U0001efff'), ('\U0001f004', '\U0001f004'), ('\U0001f02c', '\U0001f02f'),
+    ('\U0001f094', '\U0001f09f'), ('\U0001f0af', '\U0001f0b0'), ('\U0001f0c0', '\U0001f0c0'),
+    ('\U0001f0cf', '\U0001f0d0'), ('\U0001f0f6', '\U0001f0ff'), ('\U0001f18e', '\U0001f18e'),
+    ('\U0001f191', '\U0001f19a'), ('\U0001f1ae', '\U0001f1e5'), ('\U0001f200', '\U0001f320'),
+    ('\U0001f32d', '\U0001f335'), ('\U0001f337', '\U0001f37c'), ('\U0001f37e', '\U0001f393'),
+    ('\U0001f3a0', '\U0001f3ca'), ('\U0001f3cf', '\U0001f3d3'), ('\U0001f3e0', '\U0001f3f0'),
+    ('\U0001f3f4', '\U0001f3f4'), ('\U0001f3f8', '\U0001f43e'), ('\U0001f440', '\U0001f440'),
+    ('\U0001f442', '\U0001f4fc'), ('\U0001f4ff', '\U0001f53d'), ('\U0001f54b', '\U0001f54e'),
+    ('\U0001f550', '\U0001f567'), ('\U0001f57a', '\U0001f57a'), ('\U0001f595', '\U0001f596'),
+    ('\U0001f5a4', '\U0001f5a4'), ('\U0001f5fb', '\U0001f64f'), ('\U0001f680', '\U0001f6c5'),
+    ('\U0001f6cc', '\U0001f6cc'), ('\U0001f6d0', '\U0001f6d2'), ('\U0001f6d5', '\U0001f6df'),
+    ('\U0001f6eb', '\U0001f6ef'), ('\U0001f6f4', '\U0001f6ff'), ('\U0001f774', '\U0001f77f'),
+    ('\U0001f7d9', '\U0001f7ff'), ('\U0001f80c', '\U0001f80f'), ('\U0001f848', '\U0001f84f'),
+    ('\U0001f85a', '\U0001f85f'), ('\U0001f888', '\U0001f88f'), ('\U0001f8ae', '\U0001f8af'),
+    ('\U0001f8b2', '\U0001f8ff'), ('\U0001f90c', '\U0001f93a'), ('\U0001f93c', '\U0001f945'),
+    ('\U0001f947', '\U0001f9ff'), ('\U0001fa54', '\U0001fa5f'), ('\U0001fa6e', '\U0001faff'),
+    ('\U0001fb93', '\U0001fb93'), ('\U0001fbcb', '\U0001fbef'), ('\U0001fbfa', '\U000e0000'),
+    ('\U000e0002', '\U000e001f'), ('\U000e0080', '\U000e00ff'), ('\U000e01f0', '\U000effff'),
+    ('\U000ffffe', '\U000fffff'), ('\U0010fffe', '\U0010ffff'),
+];
+
+// East Asian Ambiguous, outside the private use areas
+pub static AMBIGUOUS: &'static [(char, char)] = &[
+    ('\u00a1', '\u00a1'), ('\u00a4', '\u00a4'), ('\u00a7', '\u00a8'), ('\u00aa', '\u00aa'),
+    ('\u00ad', '\u00ae'), ('\u00b0', '\u00b4'), ('\u00b6', '\u00ba'), ('\u00bc', '\u00bf'),
+    ('\u00c6', '\u00c6'), ('\u00d0', '\u00d0'), ('\u00d7', '\u00d8'), ('\u00de', '\u00e1'),
+    ('\u00e6', '\u00e6'), ('\u00e8', '\u00ea'), ('\u00ec', '\u00ed'), ('\u00f0', '\u00f0'),
+    ('\u00f2', '\u00f3'), ('\u00f7', '\u00fa'), ('\u00fc', '\u00fc'), ('\u00fe', '\u00fe'),
+    ('\u0101', '\u0101'), ('\u0111', '\u0111'), ('\u0113', '\u0113'), ('\u011b', '\u011b'),
+    ('\u0126', '\u0127'), ('\u012b', '\u012b'), ('\u0131', '\u0133'), ('\u0138', '\u0138'),
+    ('\u013f', '\u0142'), ('\u0144', '\u0144'), ('\u0148', '\u014b'), ('\u014d', '\u014d'),
+    ('\u0152', '\u0153'), ('\u0166', '\u0167'), ('\u016b', '\u016b'), ('\u01ce', '\u01ce'),
+    ('\u01d0', '\u01d0'), ('\u01d2', '\u01d2'), ('\u01d4', '\u01d4'), ('\u01d6', '\u01d6'),
+    ('\u01d8', '\u01d8'), ('\u01da', '\u01da'), ('\u01dc', '\u01dc'), ('\u0251', '\u0251'),
+    ('\u0261', '\u0261'), ('\u02c4', '\u02c4'), ('\u02c7', '\u02c7'), ('\u02c9', '\u02cb'),
+    ('\u02cd', '\u02cd'), ('\u02d0', '\u02d0'), ('\u02d8', '\u02db'), ('\u02dd', '\u02dd'),
+    ('\u02df', '\u02df'), ('\u0391', '\u03a1'), ('\u03a3', '\u03a9'), ('\u03b1', '\u03c1'),
+    ('\u03c3', '\u03c9'), ('\u0401', '\u0401'), ('\u0410', '\u044f'), ('\u0451', '\u0451'),
+    ('\u2010', '\u2010'), ('\u2013', '\u2016'), ('\u2018', '\u2019'), ('\u201c', '\u201d'),
+    ('\u2020', '\u2022'), ('\u2024', '\u2027'), ('\u2030', '\u2030'), ('\u2032', '\u2033'),
+    ('\u2035', '\u2035'), ('\u203b', '\u203b'), ('\u203e', '\u203e'), ('\u2074', '\u2074'),
+    ('\u207f', '\u207f'), ('\u2081', '\u2084'), ('\u20ac', '\u20ac'), ('\u2103', '\u2103'),
+    ('\u2105', '\u2105'), ('\u2109', '\u2109'), ('\u2113', '\u2113'), ('\u2116', '\u2116'),
+    ('\u2121', '\u2122'), ('\u2126', '\u2126'), ('\u212b', '\u212b'), ('\u2153', '\u2154'),
+    ('\u215b', '\u215e'), ('\u2160', '\u216b'), ('\u2170', '\u2179'), ('\u2189', '\u2189'),
+    ('\u2190', '\u2199'), ('\u21b8', '\u21b9'), ('\u21d2', '\u21d2'), ('\u21d4', '\u21d4'),
+    ('\u21e7', '\u21e7'), ('\u2200', '\u2200'), ('\u2202', '\u2203'), ('\u2207', '\u2208'),
+    ('\u220b', '\u220b'), ('\u220f', '\u220f'), ('\u2211', '\u2211'), ('\u2215', '\u2215'),
+    ('\u221a', '\u221a'), ('\u221d', '\u2220'), ('\u2223', '\u2223'), ('\u2225', '\u2225'),
+    ('\u2227', '\u222c'), ('\u222e', '\u222e'), ('\u2234', '\u2237'), ('\u223c', '\u223d'),
+    ('\u2248', '\u2248'), ('\u224c', '\u224c'), ('\u2252', '\u2252'), ('\u2260', '\u2261'),
+    ('\u2264', '\u2267'), ('\u226a', '\u226b'), ('\u226e', '\u226f'), ('\u2282', '\u2283'),
+    ('\u2286', '\u2287'), ('\u2295', '\u2295'), ('\u2299', '\u2299'), ('\u22a5', '\u22a5'),
+    ('\u22bf', '\u22bf'), ('\u2312', '\u2312'), ('\u2460', '\u24e9'), ('\u24eb', '\u254b'),
+    ('\u2550', '\u2573'), ('\u2580', '\u258f'), ('\u2592', '\u2595'), ('\u25a0', '\u25a1'),
+    ('\u25a3', '\u25a9'), ('\u25b2', '\u25b3'), ('\u25b6', '\u25b7'), ('\u25bc', '\u25bd'),
+    ('\u25c0', '\u25c1'), ('\u25c6', '\u25c8'), ('\u25cb', '\u25cb'), ('\u25ce', '\u25d1'),
+    ('\u25e2', '\u25e5'), ('\u25ef', '\u25ef'), ('\u2605', '\u2606'), ('\u2609', '\u2609'),
+    ('\u260e', '\u260f'), ('\u261c', '\u261c'), ('\u261e', '\u261e'), ('\u2640', '\u2640'),
+    ('\u2642', '\u2642'), ('\u2660', '\u2661'), ('\u2663', '\u2665'), ('\u2667', '\u266a'),
+    ('\u266c', '\u266d'), ('\u266f', '\u266f'), ('\u269e', '\u269f'), ('\u26bf', '\u26bf'),
+    ('\u26c6', '\u26cd'), ('\u26cf', '\u26d3'), ('\u26d5', '\u26e1'), ('\u26e3', '\u26e3'),
+    ('\u26e8', '\u26e9'), ('\u26eb', '\u26f1'), ('\u26f4', '\u26f4'), ('\u26f6', '\u26f9'),
+    ('\u26fb', '\u26fc'), ('\u26fe', '\u26ff'), ('\u273d', '\u273d'), ('\u2776', '\u277f'),
+    ('\u2b56', '\u2b59'), ('\u3248', '\u324f'), ('\ufffd', '\ufffd'), ('\U0001f100', '\U0001f10a'),
+    ('\U0001f110', '\U0001f12d'), ('\U0001f130', '\U0001f169'), ('\U0001f170', '\U0001f18d'),
+    ('\U0001f18f', '\U0001f190'), ('\U0001f19b', '\U0001f1ac'),
+];
+
@@ -0,0 +1,646 @@
+#![experimental]
+
+use std;
+use std::cell::RefCell;
+use std::rc::{Rc,Weak};
+
+use {TickitEvent,KeyEvent,MouseEvent,ResizeEvent};
+use TickitKeyEvent;
+use {TickitMouseEvent,MousePressEvent,MouseDragEvent,MouseReleaseEvent,MouseWheelEvent,MouseMoveEvent};
+use TickitError;
+use TickitExposeScheduler;
+use TickitRect;
+use TickitRenderBuffer;
+use TickitTerm;
+
+pub type ExposeFn = Box<FnMut(&mut TickitRenderBuffer, &TickitRect) + 'static>;
+pub type KeyFn = Box<FnMut(&TickitWindow, &TickitKeyEvent) -> bool + 'static>;
+pub type MouseFn = Box<FnMut(&TickitWindow, &TickitMouseEvent) -> bool + 'static>;
+pub type FocusFn = Box<FnMut(&TickitWindow, bool) + 'static>;
+pub type GeomchangeFn = Box<FnMut(&TickitWindow) + 'static>;
+
+struct WindowData
+{
+    parent: Option<Weak<RefCell<WindowData>>>,
+    // front-most first
+    children: Vec<Rc<RefCell<WindowData>>>,
+    // relative to the parent
+    rect: TickitRect,
+    visible: bool,
+    focused: bool,
+    focused_child: Option<Weak<RefCell<WindowData>>>,
+    // pending expose, in root coordinates; only present on the root
+    damage: Option<TickitExposeScheduler>,
+
+    on_expose: Option<ExposeFn>,
+    on_key: Option<KeyFn>,
+    on_mouse: Option<MouseFn>,
+    on_focus: Option<FocusFn>,
+    on_geomchange: Option<GeomchangeFn>,
+}
+
+impl WindowData
+{
+    fn new(parent: Option<Weak<RefCell<WindowData>>>, rect: TickitRect) -> WindowData
+    {
+        WindowData
+        {
+            parent: parent,
+            children: Vec::new(),
+            rect: rect,
+            visible: true,
+            focused: false,
+            focused_child: None,
+            damage: None,
+            on_expose: None,
+            on_key: None,
+            on_mouse: None,
+            on_focus: None,
+            on_geomchange: None,
+        }
+    }
+}
+
+// The handler is taken out of its slot for the duration of the call, so
+// that it may freely use the window (even rebind itself).
+macro_rules! call_handler(
+    ($win:expr, $slot:ident, $args:expr) => ({
+        let cb = $win.data.borrow_mut().$slot.take();
+        match cb
+        {
+            Some(mut cb) =>
+            {
+                let ret = cb.call_mut($args);
+                let mut data = $win.data.borrow_mut();
+                if data.$slot.is_none()
+                {
+                    data.$slot = Some(cb);
+                }
+                Some(ret)
+            }
+            None => None,
+        }
+    })
+)
+
+fn same(a: &Rc<RefCell<WindowData>>, b: &Rc<RefCell<WindowData>>) -> bool
+{
+    (&**a as *const RefCell<WindowData>) == (&**b as *const RefCell<WindowData>)
+}
+
+fn offset_rect(rect: &TickitRect, downward: int, rightward: int) -> TickitRect
+{
+    TickitRect::init_sized(rect.top + downward, rect.left + rightward, rect.lines, rect.cols)
+}
+
+fn mouse_position(ev: &TickitMouseEvent) -> (int, int)
+{
+    match *ev
+    {
+        MousePressEvent{line, col, ..} => (line, col),
+        MouseDragEvent{line, col, ..} => (line, col),
+        MouseReleaseEvent{line, col, ..} => (line, col),
+        MouseWheelEvent{line, col, ..} => (line, col),
+        MouseMoveEvent{line, col, ..} => (line, col),
+    }
+}
+
+fn offset_mouse(ev: &TickitMouseEvent, downward: int, rightward: int) -> TickitMouseEvent
+{
+    match *ev
+    {
+        MousePressEvent{button, line, col, mod_} =>
+            MousePressEvent{button: button, line: line + downward, col: col + rightward, mod_: mod_},
+        MouseDragEvent{button, line, col, mod_} =>
+            MouseDragEvent{button: button, line: line + downward, col: col + rightward, mod_: mod_},
+        MouseReleaseEvent{button, line, col, mod_} =>
+            MouseReleaseEvent{button: button, line: line + downward, col: col + rightward, mod_: mod_},
+        MouseWheelEvent{dir, line, col, mod_} =>
+            MouseWheelEvent{dir: dir, line: line + downward, col: col + rightward, mod_: mod_},
+        MouseMoveEvent{line, col, mod_} =>
+            MouseMoveEvent{line: line + downward, col: col + rightward, mod_: mod_},
+    }
+}
+
+/// A rectangular region of the terminal, nested within a parent window.
+///
+/// This is a cheap handle; clones refer to the same window.
+#[deriving(Clone)]
+pub struct TickitWindow
+{
+    data: Rc<RefCell<WindowData>>,
+}
+
+impl TickitWindow
+{
+    fn from_rc(data: Rc<RefCell<WindowData>>) -> TickitWindow
+    {
+        TickitWindow{data: data}
+    }
+
+    /// Create the root window, covering the whole of the terminal.
+    pub fn new_root(tt: &TickitTerm) -> TickitWindow
+    {
+        let (lines, cols) = tt.get_size();
+        let rect = TickitRect::init_sized(0, 0, lines as int, cols as int);
+        let mut data = WindowData::new(None, rect);
+        data.damage = Some(TickitExposeScheduler::new(rect.lines, rect.cols));
+        let root = TickitWindow::from_rc(Rc::new(RefCell::new(data)));
+        root.expose(None);
+        root
+    }
+
+    /// Create a child window in front of any existing siblings.
+    pub fn make_sub(&self, top: int, left: int, lines: int, cols: int) -> TickitWindow
+    {
+        let rect = TickitRect::init_sized(top, left, lines, cols);
+        let data = WindowData::new(Some(self.data.downgrade()), rect);
+        let win = TickitWindow::from_rc(Rc::new(RefCell::new(data)));
+        self.data.borrow_mut().children.insert(0, win.data.clone());
+        win.expose(None);
+        win
+    }
+
+    /// Detach this window (and its children) from the tree.
+    pub fn close(&self)
+    {
+        let parent = match self.parent()
+        {
+            Some(parent) => parent,
+            None => return,
+        };
+        // the focus goes with it, if it or one of its children holds it
+        match self.focused_window()
+        {
+            Some(win) => win.set_focused(false),
+            None => {}
+        }
+        self.expose_in_parent();
+
+        {
+            let mut pdata = parent.data.borrow_mut();
+            pdata.children.retain(|c| !same(c, &self.data));
+            let was_focus = match pdata.focused_child
+            {
+                Some(ref weak) => weak.upgrade().map_or(false, |c| same(&c, &self.data)),
+                None => false,
+            };
+            if was_focus
+            {
+                pdata.focused_child = None;
+            }
+        }
+        self.data.borrow_mut().parent = None;
+    }
+
+    pub fn parent(&self) -> Option<TickitWindow>
+    {
+        let data = self.data.borrow();
+        data.parent.as_ref().and_then(|weak| weak.upgrade()).map(|rc| TickitWindow::from_rc(rc))
+    }
+    pub fn root(&self) -> TickitWindow
+    {
+        let mut win = self.clone();
+        loop
+        {
+            match win.parent()
+            {
+                Some(parent) => { win = parent; }
+                None => { return win; }
+            }
+        }
+    }
+    /// Child windows, front-most first.
+    pub fn children(&self) -> Vec<TickitWindow>
+    {
+        self.data.borrow().children.iter().map(|rc| TickitWindow::from_rc(rc.clone())).collect()
+    }
+}
+
+impl TickitWindow
+{
+    pub fn get_geometry(&self) -> TickitRect
+    {
+        self.data.borrow().rect
+    }
+    pub fn top(&self) -> int
+    {
+        self.get_geometry().top
+    }
+    pub fn left(&self) -> int
+    {
+        self.get_geometry().left
+    }
+    pub fn lines(&self) -> int
+    {
+        self.get_geometry().lines
+    }
+    pub fn cols(&self) -> int
+    {
+        self.get_geometry().cols
+    }
+
+    /// Geometry relative to the root window.
+    pub fn get_abs_geometry(&self) -> TickitRect
+    {
+        let rect = self.get_geometry();
+        match self.parent()
+        {
+            Some(parent) =>
+            {
+                let prect = parent.get_abs_geometry();
+                offset_rect(&rect, prect.top, prect.left)
+            }
+            None => rect,
+        }
+    }
+
+    pub fn set_geometry(&self, rect: TickitRect)
+    {
+        if self.get_geometry() == rect
+        {
+            return;
+        }
+
+        self.expose_in_parent();
+        {
+            let mut data = self.data.borrow_mut();
+            data.rect = rect;
+            match data.damage
+            {
+                Some(ref mut damage) => damage.resize(rect.lines, rect.cols),
+                None => {}
+            }
+        }
+        self.expose_in_parent();
+
+        call_handler!(self, on_geomchange, (self,));
+    }
+    pub fn reposition(&self, top: int, left: int)
+    {
+        let rect = self.get_geometry();
+        self.set_geometry(TickitRect::init_sized(top, left, rect.lines, rect.cols));
+    }
+    pub fn resize(&self, lines: int, cols: int)
+    {
+        let rect = self.get_geometry();
+        self.set_geometry(TickitRect::init_sized(rect.top, rect.left, lines, cols));
+    }
+}
+
+impl TickitWindow
+{
+    pub fn is_visible(&self) -> bool
+    {
+        self.data.borrow().visible
+    }
+    pub fn show(&self)
+    {
+        if self.is_visible()
+        {
+            return;
+        }
+        self.data.borrow_mut().visible = true;
+        self.expose_in_parent();
+    }
+    pub fn hide(&self)
+    {
+        if !self.is_visible()
+        {
+            return;
+        }
+        self.expose_in_parent();
+        self.data.borrow_mut().visible = false;
+    }
+
+    fn restack(&self, f: |&mut Vec<Rc<RefCell<WindowData>>>, uint|)
+    {
+        let parent = match self.parent()
+        {
+            Some(parent) => parent,
+            None => return,
+        };
+        {
+            let mut pdata = parent.data.borrow_mut();
+            let idx = pdata.children.iter().position(|c| same(c, &self.data)).unwrap();
+            f(&mut pdata.children, idx);
+        }
+        self.expose(None);
+    }
+
+    /// Move one step toward the front of the siblings.
+    pub fn raise(&self)
+    {
+        self.restack(|children, idx| if idx > 0 { children.as_mut_slice().swap(idx, idx - 1); });
+    }
+    pub fn raise_to_front(&self)
+    {
+        self.restack(|children, idx|
+        {
+            let win = children.remove(idx).unwrap();
+            children.insert(0, win);
+        });
+    }
+    /// Move one step toward the back of the siblings.
+    pub fn lower(&self)
+    {
+        self.restack(|children, idx| if idx + 1 < children.len() { children.as_mut_slice().swap(idx, idx + 1); });
+    }
+    pub fn lower_to_back(&self)
+    {
+        self.restack(|children, idx|
+        {
+            let win = children.remove(idx).unwrap();
+            children.push(win);
+        });
+    }
+}
+
+impl TickitWindow
+{
+    pub fn is_focused(&self) -> bool
+    {
+        self.data.borrow().focused
+    }
+
+    fn focused_child(&self) -> Option<TickitWindow>
+    {
+        let data = self.data.borrow();
+        data.focused_child.as_ref().and_then(|weak| weak.upgrade()).map(|rc| TickitWindow::from_rc(rc))
+    }
+
+    fn focused_window(&self) -> Option<TickitWindow>
+    {
+        if self.is_focused()
+        {
+            return Some(self.clone());
+        }
+        match self.focused_child()
+        {
+            Some(child) => child.focused_window(),
+            None => None,
+        }
+    }
+
+    fn set_focused(&self, focused: bool)
+    {
+        self.data.borrow_mut().focused = focused;
+        call_handler!(self, on_focus, (self, focused));
+    }
+
+    /// Make this the window that receives key events.
+    pub fn take_focus(&self)
+    {
+        if self.is_focused()
+        {
+            return;
+        }
+
+        match self.root().focused_window()
+        {
+            Some(old) => old.set_focused(false),
+            None => {}
+        }
+
+        // point every ancestor's focus down toward us
+        self.data.borrow_mut().focused_child = None;
+        let mut win = self.clone();
+        loop
+        {
+            match win.parent()
+            {
+                Some(parent) =>
+                {
+                    parent.data.borrow_mut().focused_child = Some(win.data.downgrade());
+                    win = parent;
+                }
+                None => break,
+            }
+        }
+
+        self.set_focused(true);
+    }
+}
+
+impl TickitWindow
+{
+    pub fn bind_expose(&self, cb: ExposeFn)
+    {
+        self.data.borrow_mut().on_expose = Some(cb);
+    }
+    pub fn bind_key(&self, cb: KeyFn)
+    {
+        self.data.borrow_mut().on_key = Some(cb);
+    }
+    pub fn bind_mouse(&self, cb: MouseFn)
+    {
+        self.data.borrow_mut().on_mouse = Some(cb);
+    }
+    pub fn bind_focus(&self, cb: FocusFn)
+    {
+        self.data.borrow_mut().on_focus = Some(cb);
+    }
+    pub fn bind_geomchange(&self, cb: GeomchangeFn)
+    {
+        self.data.borrow_mut().on_geomchange = Some(cb);
+    }
+
+    /// Route a term event into the tree; call this on the root window.
+    ///
+    /// Key events go to the focused window, mouse events to the front-most
+    /// window under the pointer; either bubbles up toward the root until a
+    /// handler returns true.
+    pub fn handle_event(&self, ev: &TickitEvent) -> bool
+    {
+        match *ev
+        {
+            ResizeEvent{lines, cols} =>
+            {
+                self.resize(lines, cols);
+                true
+            }
+            KeyEvent(ref key) =>
+            {
+                self.deliver_key(key)
+            }
+            MouseEvent(ref mouse) =>
+            {
+                self.deliver_mouse(mouse)
+            }
+            _ =>
+            {
+                false
+            }
+        }
+    }
+
+    fn deliver_key(&self, ev: &TickitKeyEvent) -> bool
+    {
+        if !self.is_visible()
+        {
+            return false;
+        }
+        match self.focused_child()
+        {
+            Some(child) =>
+            {
+                if child.deliver_key(ev)
+                {
+                    return true;
+                }
+            }
+            None => {}
+        }
+        call_handler!(self, on_key, (self, ev)).unwrap_or(false)
+    }
+
+    fn deliver_mouse(&self, ev: &TickitMouseEvent) -> bool
+    {
+        let (line, col) = mouse_position(ev);
+        for child in self.children().iter()
+        {
+            if !child.is_visible()
+            {
+                continue;
+            }
+            let geom = child.get_geometry();
+            if line >= geom.top && line < geom.bottom() && col >= geom.left && col < geom.right()
+            {
+                if child.deliver_mouse(&offset_mouse(ev, -geom.top, -geom.left))
+                {
+                    return true;
+                }
+                // windows further back are obscured at this point
+                break;
+            }
+        }
+        call_handler!(self, on_mouse, (self, ev)).unwrap_or(false)
+    }
+}
+
+impl TickitWindow
+{
+    /// Mark part (or all, for None) of the window as needing to be redrawn
+    /// at the next flush.
+    pub fn expose(&self, rect: Option<&TickitRect>)
+    {
+        let geom = self.get_geometry();
+        let bounds = TickitRect::init_sized(0, 0, geom.lines, geom.cols);
+        let mut rect = match rect
+        {
+            Some(rect) => match rect.intersect(&bounds) { Some(r) => r, None => return },
+            None => bounds,
+        };
+
+        // walk up to the root, clipping to each ancestor on the way
+        let mut win = self.clone();
+        loop
+        {
+            if !win.is_visible()
+            {
+                return;
+            }
+            match win.parent()
+            {
+                Some(parent) =>
+                {
+                    let geom = win.get_geometry();
+                    let pbounds = TickitRect::init_sized(0, 0, parent.lines(), parent.cols());
+                    rect = match offset_rect(&rect, geom.top, geom.left).intersect(&pbounds)
+                    {
+                        Some(r) => r,
+                        None => return,
+                    };
+                    win = parent;
+                }
+                None => break,
+            }
+        }
+
+        match win.data.borrow_mut().damage
+        {
+            Some(ref mut damage) => damage.damage(&rect),
+            None => {}
+        }
+    }
+
+    fn expose_in_parent(&self)
+    {
+        match self.parent()
+        {
+            Some(parent) => parent.expose(Some(&self.get_geometry())),
+            None => self.expose(None),
+        }
+    }
+
+    /// Redraw every exposed region and send it to the terminal.
+    pub fn flush(&self, tt: &mut TickitTerm) -> Result<(), TickitError>
+    {
+        let root = self.root();
+        // swapped for an empty one while rendering, which keeps whatever
+        // expose handlers expose meanwhile for the next flush
+        let mut damage =
+        {
+            let mut data = root.data.borrow_mut();
+            let (lines, cols) = match data.damage
+            {
+                Some(ref damage) => damage.get_size(),
+                None => return Ok(()),
+            };
+            std::mem::replace(&mut data.damage, Some(TickitExposeScheduler::new(lines, cols))).unwrap()
+        };
+
+        let res = damage.flush(tt, |rb, rect| root.expose_into(rb, rect));
+
+        // the new one is kept, as the window may have been resized since
+        let mut data = root.data.borrow_mut();
+        let more = data.damage.take();
+        data.damage = match more
+        {
+            Some(mut more) =>
+            {
+                for rect in damage.get_rects().iter()
+                {
+                    more.damage(rect);
+                }
+                Some(more)
+            }
+            None => Some(damage),
+        };
+        res.map(|_| ())
+    }
+
+    // 'rb' is already translated to our origin; 'rect' is in our coordinates
+    fn expose_into(&self, rb: &mut TickitRenderBuffer, rect: &TickitRect)
+    {
+        rb.save();
+        rb.clip(rect);
+
+        // Front-most children draw first, then mask themselves off from
+        // everything behind them (including us).
+        for child in self.children().iter()
+        {
+            if !child.is_visible()
+            {
+                continue;
+            }
+            let geom = child.get_geometry();
+            match rect.intersect(&geom)
+            {
+                Some(exposed) =>
+                {
+                    rb.save();
+                    rb.translate(geom.top, geom.left);
+                    child.expose_into(rb, &offset_rect(&exposed, -geom.top, -geom.left));
+                    rb.restore();
+                    rb.mask(&geom);
+                }
+                None => {}
+            }
+        }
+
+        call_handler!(self, on_expose, (rb, rect));
+
+        rb.restore();
+    }
+}